@@ -0,0 +1,212 @@
+use std::mem;
+
+use lazy_static::lazy_static;
+use log::info;
+use regex::Regex;
+
+use crate::d2m::doxygen::Registry;
+
+/// The number of times that aliases are expanded, to expand aliases used by other aliases.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+lazy_static! {
+  static ref ALIAS_NAME_REGEX: Regex =
+    Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)(?:\{([0-9]+)\})?$").unwrap();
+  static ref COMMAND_REGEX: Regex = Regex::new(r"[\\@]([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+  static ref PAR_REGEX: Regex = Regex::new(r"[\\@]par[ \t]+([^\n]+)").unwrap();
+  static ref CODE_REGEX: Regex = Regex::new(r"[\\@][cp][ \t]+(\S+)").unwrap();
+  static ref BOLD_REGEX: Regex = Regex::new(r"[\\@]b[ \t]+(\S+)").unwrap();
+  static ref EMPHASIS_REGEX: Regex = Regex::new(r"[\\@](?:e|em|a)[ \t]+(\S+)").unwrap();
+}
+
+/// A custom command that leaked into the XML output, e.g. "\sideeffect" or "\ticket{ABC-12}".
+#[derive(Debug, Clone)]
+pub struct Alias
+{
+  pub name: String,
+  /// The number of arguments, which are given in braces and separated by commas.
+  pub arity: usize,
+  /// The Markdown that replaces the command, where "\1" is replaced with the first argument.
+  pub expansion: String,
+}
+
+impl Alias
+{
+  /// Creates an alias, where the name may include the number of arguments like in the ALIASES
+  /// setting of Doxygen, e.g. "ticket{1}".
+  pub fn parse(name: &str, expansion: &str) -> Result<Self, String>
+  {
+    let captures = ALIAS_NAME_REGEX.captures(name.trim())
+                                   .ok_or_else(|| format!("invalid alias name '{}'", name))?;
+
+    let arity = match captures.get(2) {
+      Some(arity) => arity.as_str().parse().map_err(|_| format!("invalid arity of '{}'", name))?,
+      None => 0,
+    };
+
+    return Ok(Self {
+      name: captures[1].to_owned(),
+      arity,
+      expansion: expansion.to_owned(),
+    });
+  }
+
+  fn expand(&self, arguments: &[String]) -> String
+  {
+    let mut expansion = self.expansion.to_owned();
+
+    // Later arguments are substituted first, so that "\1" doesn't replace the start of "\10"
+    for (index, argument) in arguments.iter().enumerate().rev() {
+      expansion = expansion.replace(&format!("\\{}", index + 1), argument.trim());
+    }
+
+    return expansion;
+  }
+}
+
+/// Converts the most common commands in the expansion of an alias from the ALIASES setting of a
+/// Doxyfile to Markdown, e.g. "@par Title:^^" to "**Title:**" on its own line.
+pub fn to_markdown(expansion: &str) -> String
+{
+  let markdown = expansion.replace("^^", "\n");
+  let markdown = PAR_REGEX.replace_all(&markdown, "**$1**");
+  let markdown = CODE_REGEX.replace_all(&markdown, "`$1`");
+  let markdown = BOLD_REGEX.replace_all(&markdown, "**$1**");
+  let markdown = EMPHASIS_REGEX.replace_all(&markdown, "*$1*");
+
+  return markdown.to_string();
+}
+
+/// Returns the arguments in braces at the start of some text, along with the length of the braces,
+/// where commas can be escaped with a backslash. Any commas after the last expected argument are
+/// part of the last argument.
+fn parse_arguments(text: &str, arity: usize) -> Option<(Vec<String>, usize)>
+{
+  if !text.starts_with('{') {
+    return None;
+  }
+
+  let mut arguments = Vec::new();
+  let mut argument = String::new();
+  let mut depth = 0;
+  let mut is_escaped = false;
+
+  for (index, c) in text[1..].char_indices() {
+    match c {
+      _ if is_escaped => {
+        if c != ',' {
+          argument.push('\\');
+        }
+
+        argument.push(c);
+        is_escaped = false;
+      }
+      '\\' => is_escaped = true,
+      '{' => {
+        depth += 1;
+        argument.push(c);
+      }
+      '}' if depth == 0 => {
+        arguments.push(argument);
+        return Some((arguments, index + 2));
+      }
+      '}' => {
+        depth -= 1;
+        argument.push(c);
+      }
+      ',' if depth == 0 && arguments.len() + 1 < arity => arguments.push(mem::take(&mut argument)),
+      c => argument.push(c),
+    }
+  }
+
+  return None;
+}
+
+/// Expands the aliases in some text once, where unknown commands are left as they are.
+fn expand_once(text: &str, aliases: &[Alias]) -> String
+{
+  let mut result = String::with_capacity(text.len());
+  let mut last = 0;
+
+  for command in COMMAND_REGEX.captures_iter(text) {
+    let whole = command.get(0).unwrap();
+    if whole.start() < last {
+      continue;
+    }
+
+    // Commands are not part of words, e.g. e-mail addresses
+    let before = text[..whole.start()].chars().next_back();
+    if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+      continue;
+    }
+
+    let name = &command[1];
+    let rest = &text[whole.end()..];
+
+    // Aliases with arguments are used if the command is followed by enough arguments
+    let with_arguments = aliases.iter()
+                                .filter(|alias| alias.name == name && alias.arity > 0)
+                                .find_map(|alias| {
+                                  let (arguments, length) = parse_arguments(rest, alias.arity)?;
+                                  let expansion = alias.expand(&arguments);
+                                  (arguments.len() == alias.arity).then_some((expansion, length))
+                                });
+
+    let without_arguments = || {
+      aliases.iter()
+             .find(|alias| alias.name == name && alias.arity == 0)
+             .map(|alias| (alias.expansion.to_owned(), 0))
+    };
+
+    if let Some((expansion, length)) = with_arguments.or_else(without_arguments) {
+      result += &text[last..whole.start()];
+      result += &expansion;
+      last = whole.end() + length;
+    }
+  }
+
+  result += &text[last..];
+  return result;
+}
+
+/// Expands the aliases in some text, including aliases that are used by other aliases.
+pub fn expand(text: &str, aliases: &[Alias]) -> String
+{
+  let mut text = text.to_owned();
+
+  for _ in 0..MAX_EXPANSION_DEPTH {
+    if !COMMAND_REGEX.is_match(&text) {
+      break;
+    }
+
+    let expanded = expand_once(&text, aliases);
+    if expanded == text {
+      break;
+    }
+
+    text = expanded;
+  }
+
+  return text;
+}
+
+/// Expands the aliases in the comments of all entities.
+pub fn expand_aliases(registry: &mut Registry, aliases: &[Alias])
+{
+  let mut count = 0;
+
+  for comment in registry.get_comments_mut() {
+    for text in comment.get_texts_mut() {
+      let expanded = expand(text, aliases);
+
+      if expanded != *text {
+        *text = expanded;
+        count += 1;
+      }
+    }
+  }
+
+  if count > 0 {
+    info!("Expanded aliases in {} paragraph(s)", count);
+  }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::d2m::diagnostics::{Ignored, Warning};
+use crate::d2m::error::D2mError;
+use crate::d2m::logging;
+
+/// The formats of annotations, which attribute problems to files in a CI system.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum AnnotationFormat
+{
+  /// GitHub Actions workflow commands, e.g. "::warning file=xml/index.xml::...", which show up
+  /// on the pull request that triggered the workflow.
+  GITHUB,
+}
+
+lazy_static! {
+  static ref FORMAT: Mutex<Option<AnnotationFormat>> = Mutex::new(None);
+}
+
+/// Sets the format of the annotations, which are only printed if a format is set.
+pub fn set_format(format: Option<AnnotationFormat>)
+{
+  *FORMAT.lock().unwrap() = format;
+}
+
+/// Escapes the message of a workflow command, which ends at the first line break.
+fn escape_data(text: &str) -> String
+{
+  return text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+}
+
+/// Escapes a property of a workflow command, where commas and colons also have meaning.
+fn escape_property(text: &str) -> String
+{
+  return escape_data(text).replace(':', "%3A").replace(',', "%2C");
+}
+
+/// Returns the path of a file as it is shown in an annotation, i.e. relative to the working
+/// directory if possible, since annotations are matched against paths in the repository.
+pub fn get_display_path(file: &Path) -> String
+{
+  let path = match env::current_dir() {
+    Ok(dir) => file.strip_prefix(dir).unwrap_or(file),
+    Err(_) => file,
+  };
+
+  return path.to_string_lossy().replace('\\', "/");
+}
+
+fn print_annotation(level: &str, file: Option<&Path>, title: &str, message: &str)
+{
+  let mut properties = Vec::new();
+
+  if let Some(file) = file {
+    properties.push(format!("file={}", escape_property(&get_display_path(file))));
+  }
+
+  properties.push(format!("title={}", escape_property(title)));
+
+  // Failing to write to the console is not worth aborting the run over
+  let _ = writeln!(logging::get_output(),
+                   "::{} {}::{}",
+                   level,
+                   properties.join(","),
+                   escape_data(message));
+}
+
+/// Prints an annotation for each file that failed to parse, each warning, and each kind of
+/// ignored construct, if annotations are enabled.
+pub fn print_problems(failures: &[D2mError], warnings: &[Warning], ignored: &[Ignored])
+{
+  if FORMAT.lock().unwrap().is_none() {
+    return;
+  }
+
+  for failure in failures {
+    print_annotation("error", failure.get_path(), "Parse failure", &failure.to_string());
+  }
+
+  for warning in warnings {
+    print_annotation("warning",
+                     warning.file.as_deref(),
+                     warning.kind.description(),
+                     &warning.message);
+  }
+
+  // Ignored constructs tend to be repeated, so only the first occurrence of each is annotated
+  let mut groups: BTreeMap<(&str, &str), Vec<&Ignored>> = BTreeMap::new();
+  for entry in ignored {
+    groups.entry((entry.context.as_str(), entry.name.as_str())).or_default().push(entry);
+  }
+
+  for ((context, name), entries) in &groups {
+    let file = entries.iter().find_map(|entry| entry.file.as_deref());
+    let message = format!("ignored {} '{}' ({} occurrence(s))", context, name, entries.len());
+    print_annotation("notice", file, "Ignored constructs", &message);
+  }
+}
+
+/// Prints an annotation for an error that aborted the run, if annotations are enabled.
+pub fn print_error(error: &D2mError)
+{
+  if FORMAT.lock().unwrap().is_some() {
+    print_annotation("error", error.get_path(), "Conversion failed", &error.to_string());
+  }
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use log::info;
+
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// Indicates whether a path refers to an archive that the input can be extracted from, based on
+/// its extension, i.e. a ZIP archive or a tarball that may be compressed with gzip.
+pub fn is_archive(path: &Path) -> bool
+{
+  let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+  return [".zip", ".tar", ".tar.gz", ".tgz"].iter().any(|extension| name.ends_with(extension));
+}
+
+fn unpack(archive: &Path, dir: &Path) -> io::Result<()>
+{
+  let file = File::open(archive)?;
+  let name = archive.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+
+  // Entries with paths outside of the directory are skipped by both libraries
+  if name.ends_with(".zip") {
+    return zip::ZipArchive::new(file)?.extract(dir).map_err(io::Error::other);
+  } else if name.ends_with(".tar") {
+    return tar::Archive::new(file).unpack(dir);
+  } else {
+    return tar::Archive::new(GzDecoder::new(file)).unpack(dir);
+  }
+}
+
+fn has_file(dir: &Path, predicate: impl Fn(&Path) -> bool) -> io::Result<bool>
+{
+  for entry in fs::read_dir(dir)? {
+    if predicate(&entry?.path()) {
+      return Ok(true);
+    }
+  }
+
+  return Ok(false);
+}
+
+/// Returns the shallowest directory that features a file that satisfies the predicate.
+fn find_dir(root: &Path, predicate: impl Fn(&Path) -> bool) -> io::Result<Option<PathBuf>>
+{
+  let mut queue = VecDeque::from([root.to_path_buf()]);
+
+  while let Some(dir) = queue.pop_front() {
+    if has_file(&dir, &predicate)? {
+      return Ok(Some(dir));
+    }
+
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(&dir)?.filter_map(|entry| entry.ok())
+                                                       .map(|entry| entry.path())
+                                                       .filter(|path| path.is_dir())
+                                                       .collect();
+
+    // Sorted to make the choice deterministic if there are several candidates
+    subdirs.sort();
+    queue.extend(subdirs);
+  }
+
+  return Ok(None);
+}
+
+/// Extracts an archive with Doxygen XML files into a directory, returning the directory that
+/// features the XML files, since archives often wrap them in a directory such as "xml".
+pub fn extract(archive: &Path, dir: &Path) -> D2mResult<PathBuf>
+{
+  info!("Extracting {}...", archive.display());
+
+  let to_error = |err: io::Error| D2mError::ARCHIVE {
+    path: archive.to_path_buf(),
+    reason: err.to_string(),
+  };
+
+  fs::create_dir_all(dir)?;
+  unpack(archive, dir).map_err(to_error)?;
+
+  let is_index = |path: &Path| path.file_name().unwrap_or_default() == "index.xml";
+  let is_xml = |path: &Path| path.is_file() && path.extension().unwrap_or_default() == "xml";
+
+  let xml_dir = match find_dir(dir, is_index).map_err(to_error)? {
+    Some(xml_dir) => Some(xml_dir),
+    None => find_dir(dir, is_xml).map_err(to_error)?,
+  };
+
+  return Ok(xml_dir.unwrap_or(dir.to_path_buf()));
+}
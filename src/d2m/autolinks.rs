@@ -0,0 +1,133 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::d2m::generator::PageBuffer;
+
+/// The URL template used for the standard library with "--cppreference".
+pub const CPPREFERENCE_URL: &str = "https://en.cppreference.com/mwiki/index.php?search={name}";
+
+lazy_static! {
+  static ref QUALIFIED_NAME_REGEX: Regex =
+    Regex::new(r"[A-Za-z_][A-Za-z0-9_]*(?:::[A-Za-z_][A-Za-z0-9_]*)+").unwrap();
+}
+
+/// Links qualified names with known prefixes to external documentation.
+///
+/// Each rule maps a prefix, such as "std::", to a URL template, where "{name}" is replaced with
+/// the qualified name and "{path}" is replaced with the rest of the name after the prefix, with
+/// "::" replaced by "/".
+pub struct AutoLinker<'a>
+{
+  rules: &'a [(String, String)],
+}
+
+impl<'a> AutoLinker<'a>
+{
+  pub fn new(rules: &'a [(String, String)]) -> Self
+  {
+    Self { rules }
+  }
+
+  /// Returns the URL for a qualified name, using the rule with the longest matching prefix.
+  fn get_url(&self, name: &str) -> Option<String>
+  {
+    let (prefix, template) = self.rules
+                                 .iter()
+                                 .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+                                 .max_by_key(|(prefix, _)| prefix.len())?;
+
+    let path = name[prefix.len()..].replace("::", "/");
+    return Some(template.replace("{name}", name).replace("{path}", &path));
+  }
+
+  /// Links a code span if it only contains a qualified name, possibly with template arguments,
+  /// e.g. `std::vector<int>`.
+  fn link_code_span(&self, code: &str) -> Option<String>
+  {
+    let code = code.trim();
+    let name = QUALIFIED_NAME_REGEX.find(code).filter(|m| m.start() == 0)?.as_str();
+
+    let rest = &code[name.len()..];
+    let is_template = rest.starts_with('<') && rest.ends_with('>');
+    if !rest.is_empty() && !is_template {
+      return None;
+    }
+
+    return self.get_url(name).map(|url| format!("[`{}`]({})", code, url));
+  }
+
+  /// Links qualified names in text, except for names that are already part of links.
+  fn link_text(&self, text: &str) -> String
+  {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for name in QUALIFIED_NAME_REGEX.find_iter(text) {
+      let before = text[..name.start()].chars().next_back();
+      let after = text[name.end()..].chars().next();
+
+      let is_linked = before.is_some_and(|c| "[(/#?=:".contains(c)) || after == Some(']');
+      if is_linked {
+        continue;
+      }
+
+      if let Some(url) = self.get_url(name.as_str()) {
+        result += &text[last..name.start()];
+        result += &format!("[{}]({})", name.as_str(), url);
+        last = name.end();
+      }
+    }
+
+    result += &text[last..];
+    return result;
+  }
+
+  fn link_line(&self, line: &str) -> String
+  {
+    let parts: Vec<&str> = line.split('`').collect();
+
+    // Unbalanced backticks are left alone, since the code spans can't be determined
+    if parts.len().is_multiple_of(2) {
+      return self.link_text(line);
+    }
+
+    let mut result = String::with_capacity(line.len());
+
+    for (index, part) in parts.iter().enumerate() {
+      if index % 2 == 0 {
+        result += &self.link_text(part);
+        continue;
+      }
+
+      let is_linked = result.ends_with('[');
+      match self.link_code_span(part).filter(|_| !is_linked) {
+        Some(link) => result += &link,
+        None => result += &format!("`{}`", part),
+      }
+    }
+
+    return result;
+  }
+
+  /// Inserts links in a Markdown page, outside of code blocks.
+  pub fn apply(&self, content: &[u8]) -> PageBuffer
+  {
+    let text = String::from_utf8_lossy(content);
+
+    let mut output = String::with_capacity(text.len());
+    let mut in_code_block = false;
+
+    for line in text.split_inclusive('\n') {
+      if line.starts_with("```") {
+        in_code_block = !in_code_block;
+        output += line;
+      } else if in_code_block {
+        output += line;
+      } else {
+        output += &self.link_line(line);
+      }
+    }
+
+    return output.into_bytes();
+  }
+}
@@ -0,0 +1,303 @@
+use std::io;
+
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+use crate::d2m::generator::{self, EmitResult, PageBuffer};
+use crate::d2m::links::{Link, LinkResolver};
+
+/// The kinds of highlighted paragraphs in comments.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Callout
+{
+  NOTE,
+  WARNING,
+}
+
+/// Renders the elements of documentation pages in a specific output format.
+///
+/// The traversal of the documented entities is shared by all backends, see
+/// `render_compound_page` and `render_index_page`. Text passed to the backend comes straight
+/// from the comments, i.e. it may contain Markdown code spans and "* " list items, and must be
+/// escaped by the backend as needed.
+pub trait OutputBackend
+{
+  /// Emits the start of a page. The compound is absent for index pages.
+  fn begin_page(&self, writer: &mut PageBuffer, title: &str, compound: Option<&Compound>)
+    -> EmitResult;
+
+  /// Emits a section heading, where level 1 denotes the top-level sections of a page.
+  fn write_heading(&self, writer: &mut PageBuffer, level: usize, text: &str) -> EmitResult;
+
+  fn write_paragraph(&self, writer: &mut PageBuffer, text: &str) -> EmitResult;
+
+  /// Emits a block of C++ code.
+  fn write_code_block(&self, writer: &mut PageBuffer, code: &[u8]) -> EmitResult;
+
+  fn write_callout(&self, writer: &mut PageBuffer, kind: Callout, text: &str) -> EmitResult;
+
+  /// Emits a list of items that have already been formatted, e.g. by `format_link`.
+  fn write_list(&self, writer: &mut PageBuffer, items: &[String]) -> EmitResult;
+
+  /// Emits a labelled list of names and descriptions, such as function parameters.
+  fn write_definitions(&self,
+                       writer: &mut PageBuffer,
+                       label: &str,
+                       entries: &[(&str, &str)]) -> EmitResult;
+
+  /// Emits a labelled paragraph, such as the description of a return value.
+  fn write_field(&self, writer: &mut PageBuffer, label: &str, text: &str) -> EmitResult;
+
+  /// Returns a link to the page of a compound, as seen from the specified page.
+  fn format_link(&self, text: &str, target: &Compound, link: &Link, from_page: &str) -> String;
+
+  /// Indicates whether pages are generated for compounds of the specified kind.
+  fn has_page(&self, kind: CompoundKind) -> bool
+  {
+    return matches!(kind, GROUP | CLASS | STRUCT | NAMESPACE);
+  }
+
+  /// Emits the end of a page.
+  fn finish_page(&self, _writer: &mut PageBuffer) -> EmitResult
+  {
+    Ok(())
+  }
+}
+
+/// Returns the display name of a compound, i.e. the title of a group or the qualified name of
+/// other compounds, unless they have a title from the configuration.
+pub fn get_title(compound: &Compound) -> &str
+{
+  return match compound.kind {
+    GROUP => &compound.title,
+    _ if !compound.title.is_empty() => &compound.title,
+    _ => &compound.name,
+  };
+}
+
+fn render_code(render: impl FnOnce(&mut PageBuffer) -> EmitResult) -> io::Result<PageBuffer>
+{
+  let mut code = PageBuffer::new();
+  render(&mut code)?;
+  return Ok(code);
+}
+
+fn write_docs(backend: &dyn OutputBackend, writer: &mut PageBuffer, docs: &Comment)
+  -> EmitResult
+{
+  if let Some(note) = &docs.deprecated {
+    let text = match note.is_empty() {
+      true => String::from("Deprecated."),
+      false => format!("Deprecated: {}", note),
+    };
+    backend.write_callout(writer, Callout::WARNING, &text)?;
+  }
+
+  for par in docs.brief.iter().chain(&docs.details).filter(|par| !par.trim().is_empty()) {
+    backend.write_paragraph(writer, par)?;
+  }
+
+  for note in &docs.notes {
+    backend.write_callout(writer, Callout::NOTE, note)?;
+  }
+
+  for warning in docs.warnings.iter().chain(&docs.attentions) {
+    backend.write_callout(writer, Callout::WARNING, warning)?;
+  }
+
+  Ok(())
+}
+
+fn write_function(backend: &dyn OutputBackend, writer: &mut PageBuffer, func: &Function)
+  -> EmitResult
+{
+  backend.write_heading(writer, 2, &func.qualified_name)?;
+
+  let code = render_code(|code| generator::generate_function_signature(code, func))?;
+  backend.write_code_block(writer, &code)?;
+
+  write_docs(backend, writer, &func.docs)?;
+
+  if !func.parameter_names.is_empty() {
+    let parameters: Vec<(&str, &str)> =
+      func.parameter_names
+          .iter()
+          .map(|name| {
+            (name.as_str(), func.docs.parameters.get(name.as_str()).map_or("N/A", |s| s.as_str()))
+          })
+          .collect();
+
+    backend.write_definitions(writer, "Parameters", &parameters)?;
+  }
+
+  if !func.docs.returns.is_empty() {
+    backend.write_field(writer, "Returns", &func.docs.returns)?;
+  }
+
+  Ok(())
+}
+
+/// Emits a section with links to the pages of the specified compounds, if there are any.
+fn write_compound_list(backend: &dyn OutputBackend,
+                       writer: &mut PageBuffer,
+                       registry: &Registry,
+                       links: &LinkResolver,
+                       from_page: &str,
+                       heading: &str,
+                       ids: &[RefID]) -> EmitResult
+{
+  let items: Vec<String> =
+    ids.iter()
+       .filter_map(|id| registry.compounds.get(id).zip(links.get(id)))
+       .filter(|(compound, _)| backend.has_page(compound.kind))
+       .map(|(compound, link)| backend.format_link(get_title(compound), compound, link, from_page))
+       .collect();
+
+  if !items.is_empty() {
+    backend.write_heading(writer, 1, heading)?;
+    backend.write_list(writer, &items)?;
+  }
+
+  Ok(())
+}
+
+/// Renders the page of a group, class, or namespace.
+///
+/// Enums, variables, and macros are only featured on group pages, and only free functions are
+/// featured on group pages, whereas namespace pages feature no functions at all.
+pub fn render_compound_page(backend: &dyn OutputBackend,
+                            registry: &Registry,
+                            links: &LinkResolver,
+                            compound_id: &RefID,
+                            compound: &Compound) -> io::Result<PageBuffer>
+{
+  let page = &links.get(compound_id).unwrap().page;
+  let mut writer = PageBuffer::new();
+
+  backend.begin_page(&mut writer, get_title(compound), Some(compound))?;
+
+  if let Some(class) = registry.classes.get(compound_id) {
+    backend.write_heading(&mut writer, 1, "Synopsis")?;
+
+    let code = render_code(|code| generator::generate_class_declaration(code, class))?;
+    backend.write_code_block(&mut writer, &code)?;
+  }
+
+  let mut description = PageBuffer::new();
+  write_docs(backend, &mut description, &compound.docs)?;
+
+  if !description.is_empty() {
+    backend.write_heading(&mut writer, 1, "Description")?;
+    writer.append(&mut description);
+  }
+
+  for (heading, ids) in [("Groups", &compound.groups),
+                         ("Namespaces", &compound.namespaces),
+                         ("Classes", &compound.classes)] {
+    write_compound_list(backend, &mut writer, registry, links, page, heading, ids)?;
+  }
+
+  if compound.kind == GROUP {
+    let enums: Vec<&Enum> = compound.enums
+                                    .iter()
+                                    .filter_map(|id| registry.enums.get(id))
+                                    .collect();
+
+    if !enums.is_empty() {
+      backend.write_heading(&mut writer, 1, "Enums")?;
+
+      for enumeration in enums {
+        backend.write_heading(&mut writer, 2, &enumeration.qualified_name)?;
+
+        let code = render_code(|code| generator::generate_enum_declaration(code, enumeration))?;
+        backend.write_code_block(&mut writer, &code)?;
+
+        write_docs(backend, &mut writer, &enumeration.docs)?;
+      }
+    }
+  }
+
+  let functions: Vec<&Function> = compound.functions
+                                          .iter()
+                                          .filter_map(|id| registry.functions.get(id))
+                                          .filter(|f| match compound.kind {
+                                            GROUP => !f.is_member,
+                                            NAMESPACE => false,
+                                            _ => true,
+                                          })
+                                          .collect();
+
+  if !functions.is_empty() {
+    let heading = if compound.kind == GROUP { "Functions" } else { "Members" };
+    backend.write_heading(&mut writer, 1, heading)?;
+
+    for func in functions {
+      write_function(backend, &mut writer, func)?;
+    }
+  }
+
+  if compound.kind == GROUP {
+    let variables: Vec<&Variable> = compound.variables
+                                            .iter()
+                                            .filter_map(|id| registry.variables.get(id))
+                                            .collect();
+
+    if !variables.is_empty() {
+      backend.write_heading(&mut writer, 1, "Variables")?;
+
+      for variable in variables {
+        backend.write_heading(&mut writer, 2, &variable.qualified_name)?;
+        backend.write_code_block(&mut writer, format!("{};\n", &variable.definition).as_bytes())?;
+        write_docs(backend, &mut writer, &variable.docs)?;
+      }
+    }
+
+    let defines: Vec<&Define> = compound.defines
+                                        .iter()
+                                        .filter_map(|id| registry.defines.get(id))
+                                        .collect();
+
+    if !defines.is_empty() {
+      backend.write_heading(&mut writer, 1, "Macros")?;
+
+      for define in defines {
+        backend.write_heading(&mut writer, 2, &define.name)?;
+
+        let code = render_code(|code| generator::generate_define_signature(code, define))?;
+        backend.write_code_block(&mut writer, &code)?;
+
+        write_docs(backend, &mut writer, &define.docs)?;
+      }
+    }
+  }
+
+  backend.finish_page(&mut writer)?;
+  return Ok(writer);
+}
+
+/// Renders an index page, which lists the pages of the specified compounds by kind.
+pub fn render_index_page(backend: &dyn OutputBackend,
+                         registry: &Registry,
+                         links: &LinkResolver,
+                         page: &str,
+                         title: &str,
+                         ids: &[RefID]) -> io::Result<PageBuffer>
+{
+  let mut writer = PageBuffer::new();
+  backend.begin_page(&mut writer, title, None)?;
+
+  for (kind, heading) in [(GROUP, "Modules"), (CLASS, "Classes"), (NAMESPACE, "Namespaces")] {
+    let ids: Vec<RefID> = ids.iter()
+                             .filter(|id| {
+                               registry.compounds.get(*id).is_some_and(|c| {
+                                 c.kind == kind || (kind == CLASS && c.kind == STRUCT)
+                               })
+                             })
+                             .cloned()
+                             .collect();
+
+    write_compound_list(backend, &mut writer, registry, links, page, heading, &ids)?;
+  }
+
+  backend.finish_page(&mut writer)?;
+  return Ok(writer);
+}
@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(feature = "serde")]
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use log::debug;
+#[cfg(feature = "serde")]
+use log::info;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::d2m::diagnostics::{self, FileDiagnostics};
+use crate::d2m::doxygen::{RefID, Registry};
+
+/// The name of the cache file, which is stored in the output directory.
+pub const CACHE_FILE: &str = ".doxy-to-md-cache";
+
+/// The parsed contents of an input file, along with the diagnostics reported while parsing it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CachedFile
+{
+  pub sha256: String,
+  pub registry: Registry,
+  pub references: Vec<RefID>,
+  pub ignored: Vec<(String, String)>,
+}
+
+/// The state of a previous run, used to avoid parsing and writing files that haven't changed.
+///
+/// The cache can only be loaded and saved with the "serde" feature, otherwise it only lasts as
+/// long as it is kept in memory.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cache
+{
+  /// The version of the tool that wrote the cache, since the parsing may change between versions.
+  #[cfg(feature = "serde")]
+  version: String,
+  /// A checksum of the tag files, which are used to resolve external references when parsing.
+  #[cfg(feature = "serde")]
+  tag_files: String,
+  /// The checksum of the index file, which provides the declarations of all entities.
+  index: Option<String>,
+  /// The parsed input files, by file name.
+  files: HashMap<String, CachedFile>,
+  /// The checksums of the written files, by path relative to the output directory.
+  outputs: BTreeMap<String, String>,
+}
+
+/// Returns the SHA-256 checksum of a file, which is read incrementally.
+pub fn hash_file(path: &Path) -> io::Result<String>
+{
+  let mut hasher = Sha256::new();
+  io::copy(&mut File::open(path)?, &mut hasher)?;
+
+  return Ok(format!("{:x}", hasher.finalize()));
+}
+
+#[cfg(feature = "serde")]
+fn hash_tag_files(specs: &[String]) -> String
+{
+  let mut hasher = Sha256::new();
+
+  for spec in specs {
+    let path = spec.split_once('=').map_or(spec.as_str(), |(path, _)| path);
+    hasher.update(spec.as_bytes());
+    hasher.update(hash_file(Path::new(path)).unwrap_or_default().as_bytes());
+  }
+
+  return format!("{:x}", hasher.finalize());
+}
+
+impl Cache
+{
+  /// Loads the cache in the output directory, discarding parsed files that may be outdated.
+  ///
+  /// A missing or unreadable cache is simply treated as empty.
+  #[cfg(feature = "serde")]
+  pub fn load(output_dir: &Path, tag_files: &[String]) -> Self
+  {
+    let path = output_dir.join(CACHE_FILE);
+
+    let mut cache: Cache = match fs::read(&path) {
+      Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|err| {
+                        info!("Ignoring invalid cache file {}: {}", path.display(), err);
+                        Cache::default()
+                      }),
+      Err(_) => Cache::default(),
+    };
+
+    let version = env!("CARGO_PKG_VERSION");
+    let tag_files = hash_tag_files(tag_files);
+
+    if cache.version != version || cache.tag_files != tag_files {
+      cache.files.clear();
+    }
+
+    cache.version = version.to_owned();
+    cache.tag_files = tag_files;
+
+    return cache;
+  }
+
+  /// Writes the cache to the output directory.
+  #[cfg(feature = "serde")]
+  pub fn save(&self, output_dir: &Path) -> io::Result<()>
+  {
+    let contents = serde_json::to_vec(self).map_err(io::Error::from)?;
+    return fs::write(output_dir.join(CACHE_FILE), contents);
+  }
+
+  /// Sets the checksum of the index file, discarding all parsed files if it has changed.
+  pub fn set_index(&mut self, sha256: Option<String>)
+  {
+    if self.index != sha256 {
+      self.files.clear();
+      self.index = sha256;
+    }
+  }
+
+  /// Returns the cached contents of an input file, if it hasn't changed since it was parsed.
+  pub fn get(&self, name: &str, sha256: &str) -> Option<&CachedFile>
+  {
+    return self.files.get(name).filter(|file| file.sha256 == sha256);
+  }
+
+  /// Stores the contents of an input file, along with the diagnostics that it reported.
+  pub fn insert(&mut self,
+                path: &Path,
+                sha256: String,
+                registry: &Registry,
+                diagnostics: FileDiagnostics)
+  {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    self.files.insert(name, CachedFile {
+      sha256,
+      registry: registry.clone(),
+      references: diagnostics.references,
+      ignored: diagnostics.ignored,
+    });
+  }
+
+  /// Removes input files that no longer exist.
+  pub fn retain_files(&mut self, names: &HashSet<String>)
+  {
+    self.files.retain(|name, _| names.contains(name));
+  }
+
+  /// Returns the checksums of the files written by the previous run.
+  pub fn get_outputs(&self) -> &BTreeMap<String, String>
+  {
+    return &self.outputs;
+  }
+
+  pub fn set_outputs(&mut self, outputs: BTreeMap<String, String>)
+  {
+    self.outputs = outputs;
+  }
+}
+
+impl CachedFile
+{
+  /// Reports the diagnostics of the file again, as if it had been parsed.
+  pub fn replay_diagnostics(&self, path: &Path)
+  {
+    debug!("Using cached contents of {}", path.display());
+
+    diagnostics::set_current_file(Some(path));
+
+    for id in &self.references {
+      diagnostics::reference(id);
+    }
+
+    for (context, name) in &self.ignored {
+      diagnostics::ignore(context, name);
+    }
+
+    diagnostics::set_current_file(None);
+  }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+/// The kinds of callouts, as used in templates and in the configuration file.
+pub const CALLOUT_KINDS: [&str; 6] = ["note", "warning", "attention", "pre", "post", "invariant"];
+
+/// The syntaxes used for callouts, i.e. notes, warnings, and conditions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum AdmonitionStyle
+{
+  /// Callouts are paragraphs with bold labels, e.g. "**Note:** ...".
+  PLAIN,
+  /// Material for MkDocs admonitions, e.g. "!!! note".
+  MKDOCS,
+  /// GitHub alerts, e.g. "> [!NOTE]".
+  GITHUB,
+}
+
+/// Returns the label of a kind of callout, e.g. "Note" for "note".
+fn get_label(kind: &str) -> &str
+{
+  return match kind {
+    "note" => "Note",
+    "warning" => "Warning",
+    "attention" => "Attention",
+    "pre" => "Pre-conditions",
+    "post" => "Post-conditions",
+    "invariant" => "Invariants",
+    _ => kind,
+  };
+}
+
+/// Returns the MkDocs admonition type of a kind of callout.
+fn get_mkdocs_type(kind: &str) -> &str
+{
+  return match kind {
+    "note" => "note",
+    "warning" => "warning",
+    "attention" => "attention",
+    _ => "info",
+  };
+}
+
+/// Returns the GitHub alert type of a kind of callout.
+fn get_github_type(kind: &str) -> &str
+{
+  return match kind {
+    "warning" => "WARNING",
+    "attention" => "IMPORTANT",
+    _ => "NOTE",
+  };
+}
+
+fn indent(text: &str, prefix: &str) -> String
+{
+  let lines: Vec<String> = text.lines()
+                               .map(|line| {
+                                 if line.trim().is_empty() {
+                                   String::new()
+                                 } else {
+                                   format!("{}{}", prefix, line)
+                                 }
+                               })
+                               .collect();
+  return lines.join("\n");
+}
+
+/// The syntax of each kind of callout.
+pub struct CalloutFormat
+{
+  pub style: AdmonitionStyle,
+  /// Templates from the configuration file that override the style for specific kinds.
+  pub custom: BTreeMap<String, String>,
+}
+
+/// Checks that the custom templates only refer to known kinds of callouts.
+pub fn validate_custom_callouts(custom: &BTreeMap<String, String>) -> Result<(), String>
+{
+  for kind in custom.keys() {
+    if !CALLOUT_KINDS.contains(&kind.as_str()) {
+      return Err(format!("unknown callout kind '{}', expected one of: {}",
+                         kind,
+                         CALLOUT_KINDS.join(", ")));
+    }
+  }
+
+  Ok(())
+}
+
+/// Formats a callout of the specified kind, e.g. "note" or "pre".
+///
+/// A single item is rendered as a paragraph, whereas several items are rendered as a list, which
+/// is used for conditions and invariants.
+pub fn format_callout(format: &CalloutFormat, kind: &str, items: &[String], as_list: bool)
+  -> String
+{
+  let label = get_label(kind);
+
+  let body = if as_list {
+    items.iter().map(|item| format!("- {}", item)).collect::<Vec<String>>().join("\n")
+  } else {
+    items.join("\n\n")
+  };
+
+  // Custom templates without a content placeholder are used as prefixes
+  if let Some(template) = format.custom.get(kind) {
+    let template = template.replace("{label}", label);
+
+    return if template.contains("{content}") {
+      template.replace("{content}", &body)
+    } else {
+      template + body.as_str()
+    };
+  }
+
+  return match format.style {
+    AdmonitionStyle::PLAIN if as_list => format!("**{}**\n\n{}", label, body),
+    AdmonitionStyle::PLAIN => format!("**{}:** {}", label, body),
+    AdmonitionStyle::MKDOCS => {
+      let header = match get_mkdocs_type(kind) {
+        admonition if admonition == kind => format!("!!! {}", admonition),
+        admonition => format!("!!! {} \"{}\"", admonition, label),
+      };
+
+      format!("{}\n\n{}", header, indent(&body, "    "))
+    }
+    AdmonitionStyle::GITHUB => {
+      // Alerts have no titles, so the labels of lists are emitted as part of the content
+      let content = if as_list { format!("**{}**\n\n{}", label, body) } else { body };
+      let quoted: Vec<String> = content.lines()
+                                       .map(|line| format!("> {}", line).trim_end().to_owned())
+                                       .collect();
+
+      format!("> [!{}]\n{}", get_github_type(kind), quoted.join("\n"))
+    }
+  };
+}
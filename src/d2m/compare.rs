@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::d2m::cache::CACHE_FILE;
+
+/// How a file differs between two directories.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ChangeKind
+{
+  CREATED,
+  MODIFIED,
+  DELETED,
+}
+
+impl fmt::Display for ChangeKind
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+  {
+    write!(f, "{}", match self {
+      ChangeKind::CREATED => "created",
+      ChangeKind::MODIFIED => "modified",
+      ChangeKind::DELETED => "deleted",
+    })
+  }
+}
+
+/// A file that differs between two directories, with the number of added and removed lines.
+#[derive(Debug)]
+pub struct FileChange
+{
+  /// The path of the file, relative to the compared directories.
+  pub path: String,
+  pub kind: ChangeKind,
+  pub added_lines: usize,
+  pub removed_lines: usize,
+}
+
+impl fmt::Display for FileChange
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+  {
+    write!(f, "{:<8} {} (+{} -{})", self.kind, self.path, self.added_lines, self.removed_lines)
+  }
+}
+
+/// Collects the files in a directory and its subdirectories, by path relative to the root.
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) -> io::Result<()>
+{
+  if !dir.exists() {
+    return Ok(());
+  }
+
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+
+    if path.is_dir() {
+      collect_files(root, &path, files)?;
+    } else if path.file_name().unwrap_or_default() != CACHE_FILE {
+      let relative = path.strip_prefix(root).unwrap_or(&path);
+      files.insert(relative.to_string_lossy().replace('\\', "/"), fs::read(&path)?);
+    }
+  }
+
+  return Ok(());
+}
+
+/// Returns the number of lines that were added to and removed from a file, where lines are
+/// matched regardless of where they are in the file.
+fn count_changed_lines(old: &str, new: &str) -> (usize, usize)
+{
+  let mut counts: HashMap<&str, isize> = HashMap::new();
+
+  for line in old.lines() {
+    *counts.entry(line).or_default() -= 1;
+  }
+
+  for line in new.lines() {
+    *counts.entry(line).or_default() += 1;
+  }
+
+  let added = counts.values().filter(|&&n| n > 0).sum::<isize>();
+  let removed = -counts.values().filter(|&&n| n < 0).sum::<isize>();
+
+  return (added as usize, removed as usize);
+}
+
+/// Compares the files in a directory with the files in another, e.g. freshly generated output
+/// with the output of a previous run. Files in the new directory that are missing from the old
+/// directory are listed as created, and vice versa for deleted files.
+pub fn compare_dirs(old_dir: &Path, new_dir: &Path) -> io::Result<Vec<FileChange>>
+{
+  let mut old_files = BTreeMap::new();
+  let mut new_files = BTreeMap::new();
+
+  collect_files(old_dir, old_dir, &mut old_files)?;
+  collect_files(new_dir, new_dir, &mut new_files)?;
+
+  let mut changes = Vec::new();
+
+  for (path, new_contents) in &new_files {
+    let old_contents = old_files.get(path);
+
+    if old_contents == Some(new_contents) {
+      continue;
+    }
+
+    let old_text = old_contents.map(|c| String::from_utf8_lossy(c)).unwrap_or_default();
+    let (added_lines, removed_lines) =
+      count_changed_lines(&old_text, &String::from_utf8_lossy(new_contents));
+
+    changes.push(FileChange {
+      path: path.to_owned(),
+      kind: if old_contents.is_some() { ChangeKind::MODIFIED } else { ChangeKind::CREATED },
+      added_lines,
+      removed_lines,
+    });
+  }
+
+  for (path, old_contents) in &old_files {
+    if !new_files.contains_key(path) {
+      changes.push(FileChange {
+        path: path.to_owned(),
+        kind: ChangeKind::DELETED,
+        added_lines: 0,
+        removed_lines: String::from_utf8_lossy(old_contents).lines().count(),
+      });
+    }
+  }
+
+  changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+  return Ok(changes);
+}
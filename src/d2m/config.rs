@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::d2m::aliases::Alias;
+use crate::d2m::callouts;
+use crate::d2m::error::{D2mError, D2mResult};
+use crate::d2m::hook::PostHook;
+use crate::d2m::links;
+use crate::d2m::overrides::CompoundOverride;
+use crate::d2m::replacements::{self, ReplacementRule};
+use crate::d2m::signature::SignatureFormat;
+
+/// The configuration file that is used if none is specified, in the working directory.
+pub const DEFAULT_CONFIG_FILE: &str = "doxy-to-md.toml";
+
+/// The order in which pages of the same category are navigated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageOrder
+{
+  ALPHABETICAL,
+  GROUP,
+}
+
+/// The syntax used for page front matter, where applicable.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatterFormat
+{
+  #[default]
+  YAML,
+  TOML,
+}
+
+/// The origin of the documentation that is noted in the provenance footer of every page.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Provenance
+{
+  /// The documented project, e.g. a repository URL, which may be written in Markdown.
+  pub source: Option<String>,
+  /// The commit of the documented sources, e.g. a Git hash.
+  pub commit: Option<String>,
+}
+
+/// Represents the contents of a configuration file.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config
+{
+  /// The name of the project, which is shown on the main index and available to templates.
+  pub project_name: Option<String>,
+
+  /// Adds links to the previous and next pages to the bottom of compound pages.
+  pub page_navigation: Option<PageOrder>,
+
+  /// A prefix for the page paths in generated navigation files, e.g. "api/".
+  pub nav_prefix: String,
+
+  /// The syntax of the front matter emitted with "--front-matter", YAML by default.
+  pub front_matter_format: FrontMatterFormat,
+
+  /// Front matter entries added to every page, where strings may contain "{title}",
+  /// "{description}", and "{path}" placeholders.
+  pub front_matter: toml::Table,
+
+  /// A pattern for the paths of class, group, and namespace pages, e.g.
+  /// "{kind}/{qualified_name_slug}.md".
+  pub filename_scheme: Option<String>,
+
+  /// Maps prefixes of qualified names, e.g. "std::", to URL templates used to link such names to
+  /// external documentation.
+  pub autolinks: BTreeMap<String, String>,
+
+  /// A Tera template rendered at the top of every Markdown page, e.g. a "do not edit" notice.
+  pub page_header: Option<String>,
+
+  /// A Tera template rendered at the bottom of every Markdown page.
+  pub page_footer: Option<String>,
+
+  /// How function signatures are laid out in code blocks.
+  pub signatures: SignatureFormat,
+
+  /// Maps kinds of callouts, e.g. "note", to templates that replace the "--admonitions" syntax,
+  /// where "{label}" and "{content}" are replaced with the label and content of the callout.
+  pub callouts: BTreeMap<String, String>,
+
+  /// The Markdown that replaces custom commands that appear literally in comments, by name, e.g.
+  /// "ticket{1}" for a command with an argument, like the ALIASES setting of Doxygen.
+  pub aliases: BTreeMap<String, String>,
+
+  /// Rules that rewrite text in pages, outside of code blocks, which are applied in order.
+  pub replacements: Vec<ReplacementRule>,
+
+  /// Adds a footer to every Markdown page that notes the versions of the tools and the origin of
+  /// the documentation, which is left out by default to keep diffs of the output clean.
+  pub provenance: Option<Provenance>,
+
+  /// A command that is run after the output has been generated, e.g. a formatter.
+  pub post_hook: Option<PostHook>,
+
+  /// Attributes that replace the ones from the XML files for specific compounds, by qualified
+  /// name, e.g. a custom page title.
+  pub compounds: BTreeMap<String, CompoundOverride>,
+
+  /// Command line options by long name, e.g. "layout" or "input-dir", which are used unless the
+  /// same options are specified on the command line.
+  pub options: toml::Table,
+}
+
+/// Converts a table of command line options to the equivalent arguments.
+fn to_arguments(options: &toml::Table) -> Result<Vec<String>, String>
+{
+  let mut arguments = Vec::new();
+
+  for (name, value) in options {
+    let values = match value {
+      toml::Value::Array(values) => values.iter().collect(),
+      value => vec![value],
+    };
+
+    for value in values {
+      match value {
+        toml::Value::Boolean(true) => arguments.push(format!("--{}", name)),
+        toml::Value::Boolean(false) => (),
+        toml::Value::String(text) => arguments.push(format!("--{}={}", name, text)),
+        toml::Value::Integer(number) => arguments.push(format!("--{}={}", name, number)),
+        _ => return Err(format!("unsupported value of option '{}'", name)),
+      }
+    }
+  }
+
+  return Ok(arguments);
+}
+
+impl Config
+{
+  pub fn load(path: &Path) -> D2mResult<Self>
+  {
+    return Self::read(path).map_err(|reason| D2mError::CONFIG {
+                                      path: path.to_path_buf(),
+                                      reason,
+                                    });
+  }
+
+  fn read(path: &Path) -> Result<Self, String>
+  {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let config: Self = toml::from_str(&contents).map_err(|e| e.to_string())?;
+
+    if let Some(scheme) = &config.filename_scheme {
+      links::validate_filename_scheme(scheme)?;
+    }
+
+    callouts::validate_custom_callouts(&config.callouts)?;
+    replacements::compile_rules(&config.replacements)?;
+
+    for (name, expansion) in &config.aliases {
+      Alias::parse(name, expansion)?;
+    }
+
+    for (name, entry) in &config.compounds {
+      entry.validate(name)?;
+    }
+
+    if let Some(hook) = &config.post_hook {
+      hook.validate()?;
+    }
+
+    to_arguments(&config.options)?;
+
+    return Ok(config);
+  }
+
+  /// Returns the command line arguments that are equivalent to the configured options.
+  pub fn get_option_arguments(&self) -> Vec<String>
+  {
+    return to_arguments(&self.options).unwrap_or_default();
+  }
+
+  /// Returns the aliases, leaving out any with invalid names, e.g. from a Doxyfile.
+  pub fn get_aliases(&self) -> Vec<Alias>
+  {
+    return self.aliases
+               .iter()
+               .filter_map(|(name, expansion)| Alias::parse(name, expansion).ok())
+               .collect();
+  }
+
+  /// Returns the replacement rules with compiled patterns.
+  pub fn get_replacements(&self) -> Vec<(Regex, String)>
+  {
+    return replacements::compile_rules(&self.replacements).unwrap_or_default();
+  }
+}
@@ -0,0 +1,247 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use log::info;
+use serde_json::json;
+
+use crate::d2m::backend::{self, Callout, OutputBackend};
+use crate::d2m::doxygen::*;
+use crate::d2m::error::D2mResult;
+use crate::d2m::generator::{EmitResult, PageBuffer};
+use crate::d2m::links::{Link, LinkOptions, LinkResolver};
+use crate::d2m::stats;
+
+/// The title of the root page, which lists all other pages.
+const ROOT_TITLE: &str = "API";
+
+/// Returns the path of the storage format file of a page, relative to the output directory.
+fn get_page_file(page: &str) -> String
+{
+  return format!("{}.xhtml", page.strip_suffix(".md").unwrap_or(page));
+}
+
+fn escape(text: &str) -> String
+{
+  return text.replace('&', "&amp;")
+             .replace('<', "&lt;")
+             .replace('>', "&gt;")
+             .replace('"', "&quot;");
+}
+
+/// Converts the Markdown produced for inline code spans in comments to HTML.
+fn convert_inline(text: &str) -> String
+{
+  let mut result = String::new();
+
+  for (index, part) in escape(text).split('`').enumerate() {
+    if index % 2 == 1 {
+      result += &format!("<code>{}</code>", part.trim());
+    } else {
+      result += part;
+    }
+  }
+
+  return result;
+}
+
+/// Emits a comment paragraph, where lines that start with "* " are list items.
+fn write_paragraph(writer: &mut PageBuffer, text: &str) -> EmitResult
+{
+  let mut in_list = false;
+
+  for line in text.lines().filter(|line| !line.trim().is_empty()) {
+    match line.trim().strip_prefix("* ") {
+      Some(item) => {
+        if !in_list {
+          write!(writer, "<ul>")?;
+          in_list = true;
+        }
+
+        write!(writer, "<li>{}</li>", convert_inline(item))?;
+      }
+      None => {
+        if in_list {
+          write!(writer, "</ul>")?;
+          in_list = false;
+        }
+
+        write!(writer, "<p>{}</p>\n", convert_inline(line))?;
+      }
+    }
+  }
+
+  if in_list {
+    write!(writer, "</ul>\n")?;
+  }
+
+  Ok(())
+}
+
+struct ConfluenceBackend;
+
+impl OutputBackend for ConfluenceBackend
+{
+  fn begin_page(&self, _writer: &mut PageBuffer, _title: &str, _compound: Option<&Compound>)
+    -> EmitResult
+  {
+    // Page titles are not part of the storage format, see the manifest
+    Ok(())
+  }
+
+  fn write_heading(&self, writer: &mut PageBuffer, level: usize, text: &str) -> EmitResult
+  {
+    write!(writer, "<h{}>{}</h{}>\n", level + 1, escape(text), level + 1)?;
+    Ok(())
+  }
+
+  fn write_paragraph(&self, writer: &mut PageBuffer, text: &str) -> EmitResult
+  {
+    return write_paragraph(writer, text);
+  }
+
+  fn write_code_block(&self, writer: &mut PageBuffer, code: &[u8]) -> EmitResult
+  {
+    // The code is embedded in a CDATA section, which cannot contain its own terminator
+    let code = String::from_utf8_lossy(code).replace("]]>", "]]]]><![CDATA[>");
+
+    write!(writer, "<ac:structured-macro ac:name=\"code\">")?;
+    write!(writer, "<ac:parameter ac:name=\"language\">cpp</ac:parameter>")?;
+    write!(writer, "<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body>", code.trim_end())?;
+    write!(writer, "</ac:structured-macro>\n")?;
+
+    Ok(())
+  }
+
+  /// Emits a callout, using the "info" or "warning" macros.
+  fn write_callout(&self, writer: &mut PageBuffer, kind: Callout, text: &str) -> EmitResult
+  {
+    let name = match kind {
+      Callout::NOTE => "info",
+      Callout::WARNING => "warning",
+    };
+
+    write!(writer, "<ac:structured-macro ac:name=\"{}\"><ac:rich-text-body>", name)?;
+    write_paragraph(writer, text)?;
+    write!(writer, "</ac:rich-text-body></ac:structured-macro>\n")?;
+
+    Ok(())
+  }
+
+  fn write_list(&self, writer: &mut PageBuffer, items: &[String]) -> EmitResult
+  {
+    write!(writer, "<ul>")?;
+
+    for item in items {
+      write!(writer, "<li>{}</li>", item)?;
+    }
+
+    write!(writer, "</ul>\n")?;
+    Ok(())
+  }
+
+  fn write_definitions(&self,
+                       writer: &mut PageBuffer,
+                       label: &str,
+                       entries: &[(&str, &str)]) -> EmitResult
+  {
+    write!(writer, "<p><strong>{}</strong></p>\n<ul>", escape(label))?;
+
+    for (name, desc) in entries {
+      write!(writer, "<li><code>{}</code> {}</li>", escape(name), convert_inline(desc))?;
+    }
+
+    write!(writer, "</ul>\n")?;
+    Ok(())
+  }
+
+  fn write_field(&self, writer: &mut PageBuffer, label: &str, text: &str) -> EmitResult
+  {
+    write!(writer,
+           "<p><strong>{}:</strong> {}</p>\n",
+           escape(label),
+           convert_inline(text))?;
+    Ok(())
+  }
+
+  /// Returns a link to a page by its title, since Confluence requires page titles to be unique
+  /// within a space.
+  fn format_link(&self, text: &str, target: &Compound, _link: &Link, _from_page: &str) -> String
+  {
+    return format!("<ac:link><ri:page ri:content-title=\"{}\" /><ac:plain-text-link-body>\
+                    <![CDATA[{}]]></ac:plain-text-link-body></ac:link>",
+                   escape(backend::get_title(target)),
+                   text.replace("]]>", "]]]]><![CDATA[>"));
+  }
+}
+
+fn write_file(output_dir: &Path, file: &str, content: &[u8]) -> EmitResult
+{
+  let path = output_dir.join(file);
+  if let Some(directory) = path.parent() {
+    fs::create_dir_all(directory)?;
+  }
+
+  return stats::write_file(path, content);
+}
+
+/// Generates a Confluence storage format file for each group, class, and namespace.
+///
+/// Since the page titles are not part of the storage format, a "pages.json" file is also
+/// generated, which lists the title and parent page of each file, e.g. for use by upload scripts.
+pub fn generate_confluence(output_dir: &Path, registry: &Registry) -> D2mResult<()>
+{
+  let start_time = SystemTime::now();
+  info!("Generating Confluence output...");
+
+  let links = LinkResolver::new(registry, &LinkOptions::new());
+
+  let backend = ConfluenceBackend;
+
+  let mut compounds: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(id, c)| backend.has_page(c.kind) && links.get(id).is_some())
+            .collect();
+
+  // Parent pages are listed before their children, so that pages can be created in order
+  compounds.sort_by_cached_key(|(id, c)| {
+    (links.get_ancestors(id).len(), backend::get_title(c).to_lowercase())
+  });
+
+  let mut manifest = Vec::new();
+
+  let ids: Vec<RefID> = compounds.iter().map(|(id, _)| (*id).clone()).collect();
+  let root = backend::render_index_page(&backend, registry, &links, "index.md", ROOT_TITLE, &ids)?;
+
+  let root_file = String::from("index.xhtml");
+  write_file(output_dir, &root_file, &root)?;
+  manifest.push(json!({ "file": root_file, "title": ROOT_TITLE, "parent": null }));
+
+  for (compound_id, compound) in &compounds {
+    let file = get_page_file(&links.get(compound_id).unwrap().page);
+
+    let content = backend::render_compound_page(&backend, registry, &links, compound_id, compound)?;
+    write_file(output_dir, &file, &content)?;
+
+    // Pages are nested beneath the pages of their groups or outer namespaces
+    let ancestors = links.get_ancestors(compound_id);
+    let parent = match ancestors.iter().rev().find(|id| links.get(id).is_some()) {
+      Some(parent_id) => backend::get_title(registry.compounds.get(*parent_id).unwrap()),
+      None => ROOT_TITLE,
+    };
+
+    let title = backend::get_title(compound);
+    manifest.push(json!({ "file": file, "title": title, "parent": parent }));
+  }
+
+  let output = serde_json::to_string_pretty(&manifest).map_err(io::Error::from)?;
+  stats::write_file(output_dir.join("pages.json"), output + "\n")?;
+
+  let end_time = SystemTime::now();
+  info!("Generated Confluence files in {} ms",
+        end_time.duration_since(start_time).unwrap().as_millis());
+
+  Ok(())
+}
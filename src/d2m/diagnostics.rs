@@ -0,0 +1,286 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::{debug, info, warn};
+
+use crate::d2m::doxygen::{RefID, Registry};
+
+/// Describes an input construct that the converter does not know how to represent.
+#[derive(Debug, Clone)]
+pub struct Ignored
+{
+  pub context: String,
+  pub name: String,
+  pub file: Option<PathBuf>,
+}
+
+/// The categories of warnings, in the order that they are summarized.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum WarningKind
+{
+  MISSING_INDEX,
+  SKIPPED_FILE,
+  PAGE_COLLISION,
+  BROKEN_REFERENCE,
+  BROKEN_LINK,
+  ORPHANED_COMPOUND,
+}
+
+impl WarningKind
+{
+  /// Returns the name of the kind, e.g. "broken_link", as seen in the JSON log.
+  pub fn name(self) -> &'static str
+  {
+    return match self {
+      WarningKind::MISSING_INDEX => "missing_index",
+      WarningKind::SKIPPED_FILE => "skipped_file",
+      WarningKind::PAGE_COLLISION => "page_collision",
+      WarningKind::BROKEN_REFERENCE => "broken_reference",
+      WarningKind::BROKEN_LINK => "broken_link",
+      WarningKind::ORPHANED_COMPOUND => "orphaned_compound",
+    };
+  }
+
+  pub fn description(self) -> &'static str
+  {
+    return match self {
+      WarningKind::MISSING_INDEX => "Missing index file",
+      WarningKind::SKIPPED_FILE => "Skipped input files",
+      WarningKind::PAGE_COLLISION => "Page collisions",
+      WarningKind::BROKEN_REFERENCE => "Broken references",
+      WarningKind::BROKEN_LINK => "Broken links",
+      WarningKind::ORPHANED_COMPOUND => "Orphaned compounds",
+    };
+  }
+}
+
+/// Describes a problem that didn't prevent the conversion, but that may affect the output.
+#[derive(Debug, Clone)]
+pub struct Warning
+{
+  pub kind: WarningKind,
+  pub message: String,
+  /// The file that the warning is about, if it is known.
+  pub file: Option<PathBuf>,
+}
+
+/// The references and ignored constructs recorded in an input file.
+#[derive(Debug, Default)]
+pub struct FileDiagnostics
+{
+  pub references: Vec<RefID>,
+  pub ignored: Vec<(String, String)>,
+}
+
+struct Collector
+{
+  ignored: Vec<Ignored>,
+  references: Vec<(RefID, Option<PathBuf>)>,
+  excluded: HashSet<RefID>,
+  warnings: Vec<Warning>,
+}
+
+lazy_static! {
+  static ref COLLECTOR: Mutex<Collector> = Mutex::new(Collector {
+    ignored: Vec::new(),
+    references: Vec::new(),
+    excluded: HashSet::new(),
+    warnings: Vec::new(),
+  });
+}
+
+thread_local! {
+  // Files are parsed in parallel, so each thread keeps track of its own file
+  static CURRENT_FILE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Forgets everything reported so far, e.g. before converting the input again.
+pub fn reset()
+{
+  let mut collector = COLLECTOR.lock().unwrap();
+  collector.ignored.clear();
+  collector.references.clear();
+  collector.excluded.clear();
+  collector.warnings.clear();
+}
+
+/// Sets the input file that subsequently reported constructs on this thread are attributed to.
+pub fn set_current_file(path: Option<&Path>)
+{
+  CURRENT_FILE.with(|file| *file.borrow_mut() = path.map(|p| p.to_path_buf()));
+}
+
+fn get_current_file() -> Option<PathBuf>
+{
+  return CURRENT_FILE.with(|file| file.borrow().clone());
+}
+
+/// Records (and prints) a construct that was ignored by the converter.
+pub fn ignore(context: &str, name: &str)
+{
+  debug!(event = "ignore", context, name; "Ignoring {} '{}'", context, name);
+
+  COLLECTOR.lock().unwrap().ignored.push(Ignored {
+    context: context.to_owned(),
+    name: name.to_owned(),
+    file: get_current_file(),
+  });
+}
+
+/// Records a reference to an entity in a comment, which is used to detect broken references.
+pub fn reference(id: &str)
+{
+  COLLECTOR.lock().unwrap().references.push((RefID::from(id), get_current_file()));
+}
+
+/// Records (and prints) a warning, which is attributed to the current input file, if any.
+pub fn warn(kind: WarningKind, message: String)
+{
+  warn_in_file(kind, message, get_current_file());
+}
+
+/// Records (and prints) a warning about a specific file.
+pub fn warn_in_file(kind: WarningKind, message: String, file: Option<PathBuf>)
+{
+  warn!(event = "warning", kind = kind.name(); "{}", message);
+  COLLECTOR.lock().unwrap().warnings.push(Warning { kind, message, file });
+}
+
+/// Returns the references and ignored constructs recorded so far, grouped by input file.
+pub fn get_file_diagnostics() -> HashMap<PathBuf, FileDiagnostics>
+{
+  let collector = COLLECTOR.lock().unwrap();
+  let mut files: HashMap<PathBuf, FileDiagnostics> = HashMap::new();
+
+  for (id, file) in &collector.references {
+    if let Some(file) = file {
+      files.entry(file.to_owned()).or_default().references.push(id.clone());
+    }
+  }
+
+  for entry in &collector.ignored {
+    if let Some(file) = &entry.file {
+      let diagnostics = files.entry(file.to_owned()).or_default();
+      diagnostics.ignored.push((entry.context.to_owned(), entry.name.to_owned()));
+    }
+  }
+
+  return files;
+}
+
+/// Records entities that were deliberately left out of the registry, so that references to them
+/// aren't reported as broken.
+pub fn exclude(ids: impl IntoIterator<Item = RefID>)
+{
+  COLLECTOR.lock().unwrap().excluded.extend(ids);
+}
+
+/// Reports a warning for each recorded reference to an entity that is missing from the registry.
+pub fn check_references(registry: &Registry)
+{
+  let (references, excluded) = {
+    let mut collector = COLLECTOR.lock().unwrap();
+    (std::mem::take(&mut collector.references), std::mem::take(&mut collector.excluded))
+  };
+
+  for (id, file) in references {
+    let is_known = excluded.contains(&id)
+                   || registry.compounds.contains_key(&id)
+                   || registry.functions.contains_key(&id)
+                   || registry.enums.contains_key(&id)
+                   || registry.enum_values.contains_key(&id)
+                   || registry.variables.contains_key(&id)
+                   || registry.defines.contains_key(&id);
+
+    if !is_known {
+      let location = file.as_ref().map(|f| format!(" in {}", f.display())).unwrap_or_default();
+      warn_in_file(WarningKind::BROKEN_REFERENCE,
+                   format!("reference to unknown entity '{}'{}", id, location),
+                   file);
+    }
+  }
+}
+
+/// Returns the number of warnings of the specified kind reported so far.
+pub fn warning_count(kind: WarningKind) -> usize
+{
+  return COLLECTOR.lock().unwrap().warnings.iter().filter(|w| w.kind == kind).count();
+}
+
+/// Returns all warnings reported so far.
+pub fn warnings() -> Vec<Warning>
+{
+  return COLLECTOR.lock().unwrap().warnings.clone();
+}
+
+/// Returns the number of ignored constructs reported so far.
+pub fn ignored_count() -> usize
+{
+  return COLLECTOR.lock().unwrap().ignored.len();
+}
+
+/// Returns all ignored constructs reported so far, clearing the internal list.
+pub fn take_ignored() -> Vec<Ignored>
+{
+  return std::mem::take(&mut COLLECTOR.lock().unwrap().ignored);
+}
+
+/// Prints a summary of ignored constructs, grouped by context and name.
+pub fn print_ignored_report(ignored: &[Ignored])
+{
+  let mut groups: BTreeMap<(&str, &str), Vec<&Ignored>> = BTreeMap::new();
+  for entry in ignored {
+    groups.entry((entry.context.as_str(), entry.name.as_str())).or_default().push(entry);
+  }
+
+  info!("\nFound {} unhandled construct(s):", ignored.len());
+
+  for ((context, name), entries) in &groups {
+    let example = entries.iter()
+                         .find_map(|e| e.file.as_ref())
+                         .map(|file| format!(", e.g. in {}", file.display()))
+                         .unwrap_or_default();
+
+    info!("  {} '{}' ({} occurrence(s){})", context, name, entries.len(), example);
+  }
+}
+
+/// Prints a table with the number of warnings and ignored constructs of each kind, along with an
+/// example of each kind of warning.
+pub fn print_warning_summary(warnings: &[Warning], ignored: &[Ignored])
+{
+  let mut groups: BTreeMap<WarningKind, Vec<&Warning>> = BTreeMap::new();
+  for warning in warnings {
+    groups.entry(warning.kind).or_default().push(warning);
+  }
+
+  let mut rows: Vec<(&str, usize, Option<&str>)> =
+    groups.iter()
+          .map(|(kind, entries)| {
+            (kind.description(), entries.len(), Some(entries[0].message.as_str()))
+          })
+          .collect();
+
+  if !ignored.is_empty() {
+    rows.push(("Ignored constructs", ignored.len(), None));
+  }
+
+  info!("\nWarnings:");
+
+  if rows.is_empty() {
+    info!("  None");
+    return;
+  }
+
+  let width = rows.iter().map(|(description, _, _)| description.len()).max().unwrap();
+  for (description, count, example) in rows {
+    match example {
+      Some(message) => info!("  {:width$}  {} (e.g. {})", description, count, message),
+      None => info!("  {:width$}  {}", description, count),
+    }
+  }
+}
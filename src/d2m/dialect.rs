@@ -0,0 +1,106 @@
+use crate::d2m::generator::PageBuffer;
+
+/// The flavors of Markdown that the generated pages can be written in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum Dialect
+{
+  /// GitHub Flavored Markdown, which supports tables.
+  GFM,
+  /// Strict CommonMark, which doesn't support tables, so these are written as lists instead.
+  COMMONMARK,
+  /// Pandoc Markdown, where lists must be preceded by blank lines, and where characters such as
+  /// '~' and '@' denote subscripts and citations.
+  PANDOC,
+}
+
+impl Dialect
+{
+  /// Returns the name of the dialect, as seen by templates.
+  pub fn name(self) -> &'static str
+  {
+    return match self {
+      Dialect::GFM => "gfm",
+      Dialect::COMMONMARK => "commonmark",
+      Dialect::PANDOC => "pandoc",
+    };
+  }
+
+  pub fn supports_tables(self) -> bool
+  {
+    return self != Dialect::COMMONMARK;
+  }
+}
+
+fn is_list_item(line: &str) -> bool
+{
+  let line = line.trim_start();
+  return line.starts_with("- ") || line.starts_with("* ");
+}
+
+/// Escapes the characters that only have special meaning in Pandoc Markdown, outside of code
+/// spans and link targets.
+fn escape_pandoc_line(line: &str) -> String
+{
+  let mut result = String::with_capacity(line.len());
+
+  let mut in_code_span = false;
+  let mut in_link_target = false;
+  let mut previous = '\0';
+
+  for c in line.chars() {
+    if c == '`' && !in_link_target {
+      in_code_span = !in_code_span;
+    } else if c == '(' && previous == ']' && !in_code_span {
+      in_link_target = true;
+    } else if c == ')' && in_link_target {
+      in_link_target = false;
+    } else if "~^@$".contains(c) && !in_code_span && !in_link_target && previous != '\\' {
+      result.push('\\');
+    }
+
+    result.push(c);
+    previous = c;
+  }
+
+  return result;
+}
+
+/// Adjusts a generated page to the conventions of a dialect, the templates are expected to
+/// produce GitHub Flavored Markdown otherwise.
+pub fn adjust_page(dialect: Dialect, content: &[u8]) -> PageBuffer
+{
+  if dialect != Dialect::PANDOC {
+    return content.to_vec();
+  }
+
+  let text = String::from_utf8_lossy(content);
+  let mut result = String::with_capacity(text.len());
+
+  let mut in_code_block = false;
+  let mut previous_line = "";
+
+  for line in text.lines() {
+    if line.starts_with("```") {
+      in_code_block = !in_code_block;
+      result += line;
+    } else if in_code_block {
+      result += line;
+    } else {
+      // Lists can't interrupt paragraphs in Pandoc Markdown
+      if is_list_item(line) && !previous_line.trim().is_empty() && !is_list_item(previous_line) {
+        result.push('\n');
+      }
+
+      result += &escape_pandoc_line(line);
+    }
+
+    result.push('\n');
+    previous_line = line;
+  }
+
+  if !text.ends_with('\n') {
+    result.pop();
+  }
+
+  return result.into_bytes();
+}
@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use log::info;
+use serde::Serialize;
+
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::doxygen::*;
+use crate::d2m::error::D2mResult;
+use crate::d2m::generator::{self, EmitResult, PageBuffer};
+use crate::d2m::links::{self, FileNames, FilenameCase};
+use crate::d2m::stats;
+
+/// The first line of every generated file, which tells DocFX how to interpret it.
+const MANAGED_REFERENCE_HEADER: &str = "### YamlMime:ManagedReference\n";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Parameter
+{
+  id: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  description: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Return
+{
+  #[serde(rename = "type")]
+  type_name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  description: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Syntax
+{
+  content: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  parameters: Vec<Parameter>,
+  #[serde(rename = "return", skip_serializing_if = "Option::is_none")]
+  return_value: Option<Return>,
+}
+
+/// Represents an item in a ManagedReference file, i.e. a documented entity.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Item
+{
+  uid: String,
+  id: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  parent: Option<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  children: Vec<String>,
+  langs: Vec<&'static str>,
+  name: String,
+  name_with_type: String,
+  full_name: String,
+  #[serde(rename = "type")]
+  item_type: &'static str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  namespace: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  summary: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  remarks: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  syntax: Option<Syntax>,
+}
+
+#[derive(Serialize)]
+struct ManagedReference
+{
+  items: Vec<Item>,
+}
+
+#[derive(Serialize)]
+struct TocEntry
+{
+  uid: String,
+  name: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  items: Vec<TocEntry>,
+}
+
+/// Returns the UID of an entity with the specified qualified name, e.g. "foo.bar" for "foo::bar".
+fn get_uid(qualified_name: &str) -> String
+{
+  return qualified_name.replace("::", ".");
+}
+
+/// Returns the name of the enclosing scope of a qualified name, if there is one.
+fn get_scope(qualified_name: &str) -> Option<&str>
+{
+  return qualified_name.rfind("::").map(|index| &qualified_name[..index]);
+}
+
+fn get_unqualified_name(qualified_name: &str) -> &str
+{
+  return qualified_name.rsplit("::").next().unwrap();
+}
+
+fn join_paragraphs(paragraphs: &[String]) -> Option<String>
+{
+  let paragraphs: Vec<&str> = paragraphs.iter()
+                                        .map(|p| p.trim())
+                                        .filter(|p| !p.is_empty())
+                                        .collect();
+
+  return if paragraphs.is_empty() { None } else { Some(paragraphs.join("\n\n")) };
+}
+
+/// Creates an item with the properties shared by all kinds of entities.
+fn create_item(qualified_name: &str, item_type: &'static str, docs: &Comment) -> Item
+{
+  let name = get_unqualified_name(qualified_name);
+  let scope = get_scope(qualified_name);
+
+  return Item {
+    uid: get_uid(qualified_name),
+    id: name.to_owned(),
+    parent: scope.map(get_uid),
+    children: Vec::new(),
+    langs: vec!["cpp"],
+    name: name.to_owned(),
+    name_with_type: match scope.map(get_unqualified_name) {
+      Some(scope) => format!("{}::{}", scope, name),
+      None => name.to_owned(),
+    },
+    full_name: qualified_name.to_owned(),
+    item_type,
+    namespace: None,
+    summary: join_paragraphs(&docs.brief),
+    remarks: join_paragraphs(&docs.details),
+    syntax: None,
+  };
+}
+
+fn create_function_item(func: &Function, uids: &mut HashSet<String>) -> io::Result<Item>
+{
+  let item_type = match get_scope(&func.qualified_name).map(get_unqualified_name) {
+    Some(scope) if func.is_member && func.name == scope => "Constructor",
+    _ if func.name.starts_with("operator") => "Operator",
+    _ => "Method",
+  };
+
+  let mut item = create_item(&func.qualified_name, item_type, &func.docs);
+
+  // Overloads share qualified names, but UIDs must be unique
+  let mut index = 1;
+  while !uids.insert(item.uid.to_owned()) {
+    index += 1;
+    item.uid = format!("{}_{}", get_uid(&func.qualified_name), index);
+  }
+
+  let parameters = func.parameter_names
+                       .iter()
+                       .map(|name| Parameter {
+                         id: name.to_string(),
+                         description: func.docs.parameters.get(name.as_str()).cloned(),
+                       })
+                       .collect();
+
+  let return_value = if func.return_type.is_empty() || func.return_type == "void" {
+    None
+  } else {
+    Some(Return {
+      type_name: func.return_type.to_string(),
+      description: Some(func.docs.returns.to_owned()).filter(|s| !s.is_empty()),
+    })
+  };
+
+  item.syntax = Some(Syntax {
+    content: generator::render_code(|code| generator::generate_function_signature(code, func))?,
+    parameters,
+    return_value,
+  });
+
+  return Ok(item);
+}
+
+fn create_enum_items(enumeration: &Enum) -> io::Result<Vec<Item>>
+{
+  let mut item = create_item(&enumeration.qualified_name, "Enum", &enumeration.docs);
+  let content =
+    generator::render_code(|code| generator::generate_enum_declaration(code, enumeration))?;
+  item.syntax = Some(Syntax {
+    content,
+    parameters: Vec::new(),
+    return_value: None,
+  });
+
+  let mut items = Vec::new();
+
+  for value in &enumeration.values {
+    let qualified_name = format!("{}::{}", &enumeration.qualified_name, &value.name);
+
+    let mut value_item = create_item(&qualified_name, "Field", &value.docs);
+    item.children.push(value_item.uid.to_owned());
+
+    if !value.initializer.is_empty() {
+      value_item.syntax = Some(Syntax {
+        content: format!("{} = {}", &value.name, &value.initializer),
+        parameters: Vec::new(),
+        return_value: None,
+      });
+    }
+
+    items.push(value_item);
+  }
+
+  items.insert(0, item);
+  return Ok(items);
+}
+
+/// Writes the file of an entity, which is named after its UID unless another file already has
+/// that name, e.g. for UIDs that only differ in case on a case-insensitive file system. Characters
+/// that aren't portable in file names, e.g. in template arguments, are replaced.
+fn write_file(output_dir: &Path, files: &mut FileNames, uid: &str, items: Vec<Item>)
+  -> EmitResult
+{
+  let filename = links::sanitize_filename(&format!("{}.yml", uid));
+  let file = files.claim(&filename);
+  if file != filename {
+    diagnostics::warn(WarningKind::PAGE_COLLISION,
+                      format!("the file of {} would overwrite another file, using {} instead",
+                              uid,
+                              &file));
+  }
+
+  let path = output_dir.join(file);
+
+  let yaml = serde_yaml::to_string(&ManagedReference { items })
+    .map_err(io::Error::other)?;
+
+  let mut output = PageBuffer::new();
+  write!(output, "{}{}", MANAGED_REFERENCE_HEADER, yaml)?;
+
+  return stats::write_file(path, output);
+}
+
+/// Generates the file for a class, which also features its member functions.
+fn generate_class_file(output_dir: &Path,
+                       files: &mut FileNames,
+                       registry: &Registry,
+                       compound_id: &RefID,
+                       compound: &Compound) -> io::Result<TocEntry>
+{
+  let class = registry.classes.get(compound_id).unwrap();
+
+  let mut item = create_item(&compound.name,
+                             if class.is_struct { "Struct" } else { "Class" },
+                             &compound.docs);
+  item.namespace = get_scope(&compound.name).map(get_uid);
+  item.syntax = Some(Syntax {
+    content: generator::render_code(|code| generator::generate_class_declaration(code, class))?,
+    parameters: Vec::new(),
+    return_value: None,
+  });
+
+  let mut uids = HashSet::new();
+  let mut members = Vec::new();
+
+  for func in compound.functions.iter().filter_map(|id| registry.functions.get(id)) {
+    let member = create_function_item(func, &mut uids)?;
+    item.children.push(member.uid.to_owned());
+    members.push(member);
+  }
+
+  let entry = TocEntry { uid: item.uid.to_owned(), name: item.name.to_owned(), items: Vec::new() };
+
+  members.insert(0, item);
+  write_file(output_dir, files, &entry.uid, members)?;
+
+  return Ok(entry);
+}
+
+/// Generates the files for a namespace and its enums, free functions are featured in the
+/// namespace file.
+fn generate_namespace_files(output_dir: &Path,
+                            files: &mut FileNames,
+                            registry: &Registry,
+                            compound: &Compound,
+                            mut toc_items: Vec<TocEntry>) -> io::Result<TocEntry>
+{
+  let mut item = create_item(&compound.name, "Namespace", &compound.docs);
+  item.parent = None;
+
+  let mut uids = HashSet::new();
+  let mut functions = Vec::new();
+
+  for func in compound.functions.iter().filter_map(|id| registry.functions.get(id)) {
+    if !func.is_member {
+      let function = create_function_item(func, &mut uids)?;
+      item.children.push(function.uid.to_owned());
+      functions.push(function);
+    }
+  }
+
+  for enumeration in compound.enums.iter().filter_map(|id| registry.enums.get(id)) {
+    if enumeration.name.contains('@') {
+      continue;
+    }
+
+    let items = create_enum_items(enumeration)?;
+    let uid = items[0].uid.to_owned();
+
+    item.children.push(uid.to_owned());
+    toc_items.push(TocEntry {
+      uid: uid.to_owned(),
+      name: items[0].name.to_owned(),
+      items: Vec::new(),
+    });
+
+    write_file(output_dir, files, &uid, items)?;
+  }
+
+  for entry in &toc_items {
+    if !item.children.contains(&entry.uid) {
+      item.children.push(entry.uid.to_owned());
+    }
+  }
+
+  toc_items.sort_by_key(|entry| entry.name.to_lowercase());
+
+  let entry = TocEntry {
+    uid: item.uid.to_owned(),
+    name: compound.name.to_string(),
+    items: toc_items,
+  };
+
+  functions.insert(0, item);
+  write_file(output_dir, files, &entry.uid, functions)?;
+
+  return Ok(entry);
+}
+
+/// Generates DocFX ManagedReference files for all classes, structs, namespaces, and enums in
+/// namespaces, along with a table of contents file.
+///
+/// Every namespace, class, and enum is written to a file named after its UID, which is its
+/// qualified name with "::" replaced by ".", or a numbered variant of it if the file name is
+/// already taken. Groups are not represented, since DocFX has no equivalent concept.
+pub fn generate_docfx(output_dir: &Path,
+                      registry: &Registry,
+                      filename_case: FilenameCase) -> D2mResult<()>
+{
+  let start_time = SystemTime::now();
+  info!("Generating DocFX output...");
+
+  let mut namespaces: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(_, c)| c.kind == NAMESPACE && !c.name.contains('@'))
+            .collect();
+  namespaces.sort_by_key(|(_, c)| &c.name);
+
+  let mut classes: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(_, c)| c.kind == CLASS || c.kind == STRUCT)
+            .collect();
+  classes.sort_by_key(|(_, c)| &c.name);
+
+  let mut toc = Vec::new();
+  let mut class_entries = Vec::new();
+
+  let mut files = FileNames::new(filename_case);
+  files.reserve("toc.yml");
+
+  for (compound_id, compound) in classes {
+    let entry = generate_class_file(output_dir, &mut files, registry, compound_id, compound)?;
+    class_entries.push((get_scope(&compound.name).map(str::to_owned), entry));
+  }
+
+  // Namespaces are listed at the top level of the table of contents, with their classes and enums
+  for (_, compound) in namespaces {
+    let mut items = Vec::new();
+
+    let mut index = 0;
+    while index < class_entries.len() {
+      if class_entries[index].0.as_deref() == Some(compound.name.as_str()) {
+        items.push(class_entries.remove(index).1);
+      } else {
+        index += 1;
+      }
+    }
+
+    toc.push(generate_namespace_files(output_dir, &mut files, registry, compound, items)?);
+  }
+
+  // Classes outside of namespaces, or in namespaces without files, are listed last
+  toc.extend(class_entries.into_iter().map(|(_, entry)| entry));
+
+  let yaml = serde_yaml::to_string(&toc).map_err(io::Error::other)?;
+  stats::write_file(output_dir.join("toc.yml"), format!("### YamlMime:TableOfContent\n{}", yaml))?;
+
+  let end_time = SystemTime::now();
+  info!("Generated DocFX files in {} ms",
+        end_time.duration_since(start_time).unwrap().as_millis());
+
+  Ok(())
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+use lazy_static::lazy_static;
+use log::info;
+use regex::{Captures, Regex};
+
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// Settings that override the Doxyfile when running Doxygen, so that only XML output is generated.
+const XML_ONLY_SETTINGS: [&str; 6] = ["GENERATE_XML = YES",
+                                      "GENERATE_HTML = NO",
+                                      "GENERATE_LATEX = NO",
+                                      "GENERATE_RTF = NO",
+                                      "GENERATE_MAN = NO",
+                                      "GENERATE_DOCBOOK = NO"];
+
+lazy_static! {
+  static ref ENV_VAR_REGEX: Regex = Regex::new(r"\$\(([A-Za-z0-9_]+)\)").unwrap();
+}
+
+/// The settings of a Doxygen configuration file, which provide defaults for some options.
+#[derive(Debug, Clone)]
+pub struct Doxyfile
+{
+  /// The directory of the Doxyfile, which relative paths are resolved against.
+  dir: PathBuf,
+  /// The values of the settings by name, e.g. "XML_OUTPUT".
+  settings: HashMap<String, Vec<String>>,
+}
+
+/// Splits the value of a setting into separate values, which are separated by whitespace unless
+/// they are quoted.
+fn split_values(text: &str) -> Vec<String>
+{
+  let mut values = Vec::new();
+  let mut value = String::new();
+  let mut has_value = false;
+  let mut is_quoted = false;
+
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => {
+        is_quoted = !is_quoted;
+        has_value = true;
+      }
+      '\\' if is_quoted && chars.peek() == Some(&'"') => value.push(chars.next().unwrap()),
+      c if c.is_whitespace() && !is_quoted => {
+        if has_value {
+          values.push(std::mem::take(&mut value));
+          has_value = false;
+        }
+      }
+      c => {
+        value.push(c);
+        has_value = true;
+      }
+    }
+  }
+
+  if has_value {
+    values.push(value);
+  }
+
+  return values;
+}
+
+/// Replaces references to environment variables, e.g. "$(PROJECT_VERSION)", with their values.
+fn expand_env_vars(text: &str) -> String
+{
+  return ENV_VAR_REGEX.replace_all(text, |captures: &Captures| {
+                        env::var(&captures[1]).unwrap_or_default()
+                      })
+                      .to_string();
+}
+
+/// Converts a symbol from the EXCLUDE_SYMBOLS setting, which may feature wildcards, to a regular
+/// expression, e.g. "detail::*" to "detail::.*".
+fn wildcard_to_regex(symbol: &str) -> String
+{
+  return regex::escape(symbol).replace(r"\*", ".*").replace(r"\?", ".");
+}
+
+impl Doxyfile
+{
+  /// Loads a Doxyfile, where unknown settings and directives such as "@INCLUDE" are ignored.
+  pub fn load(path: &Path) -> D2mResult<Self>
+  {
+    let contents = fs::read_to_string(path).map_err(|source| D2mError::READ {
+                                              path: path.to_path_buf(),
+                                              source,
+                                            })?;
+
+    let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    return Ok(Self::parse(dir, &contents));
+  }
+
+  fn parse(dir: PathBuf, contents: &str) -> Self
+  {
+    let mut settings: HashMap<String, Vec<String>> = HashMap::new();
+
+    // Lines that end with a backslash are continued on the next line
+    let contents = contents.replace("\\\r\n", " ").replace("\\\n", " ");
+
+    for line in contents.lines().map(str::trim) {
+      if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+        continue;
+      }
+
+      let (name, is_appended, text) = match line.split_once("+=") {
+        Some((name, text)) if !name.contains('=') => (name, true, text),
+        _ => match line.split_once('=') {
+          Some((name, text)) => (name, false, text),
+          None => continue,
+        },
+      };
+
+      let values = split_values(&expand_env_vars(text));
+      let entry = settings.entry(name.trim().to_owned()).or_default();
+
+      if is_appended {
+        entry.extend(values);
+      } else {
+        *entry = values;
+      }
+    }
+
+    return Self { dir, settings };
+  }
+
+  /// Returns the values of a setting, which are empty if the setting is absent.
+  pub fn get(&self, name: &str) -> &[String]
+  {
+    return self.settings.get(name).map_or(&[], |values| values.as_slice());
+  }
+
+  /// Returns the value of a setting that holds a single value, e.g. a path or a name.
+  fn get_value(&self, name: &str) -> Option<String>
+  {
+    return Some(self.get(name).join(" ")).filter(|value| !value.is_empty());
+  }
+
+  /// Returns the value of PROJECT_NAME, if it is set.
+  pub fn get_project_name(&self) -> Option<String>
+  {
+    return self.get_value("PROJECT_NAME");
+  }
+
+  /// Returns the directory that Doxygen writes the XML output to, based on OUTPUT_DIRECTORY and
+  /// XML_OUTPUT, where relative paths are resolved against the directory of the Doxyfile.
+  pub fn get_xml_dir(&self) -> PathBuf
+  {
+    let output_dir = self.get_value("OUTPUT_DIRECTORY").unwrap_or_default();
+    let xml_output = self.get_value("XML_OUTPUT").unwrap_or(String::from("xml"));
+
+    return self.dir.join(output_dir).join(xml_output);
+  }
+
+  /// Returns a regular expression equivalent to EXCLUDE_SYMBOLS, if any symbols are excluded.
+  pub fn get_exclude_symbols(&self) -> Option<String>
+  {
+    let symbols: Vec<String> = self.get("EXCLUDE_SYMBOLS")
+                                   .iter()
+                                   .map(|symbol| wildcard_to_regex(symbol))
+                                   .collect();
+
+    return Some(symbols.join("|")).filter(|pattern| !pattern.is_empty());
+  }
+
+  /// Returns the names and expansions of the custom commands in ALIASES, e.g. "sideeffect" and
+  /// "@par Side Effects:^^", where the names of aliases with arguments include the number of
+  /// arguments, e.g. "myalias{1}".
+  pub fn get_aliases(&self) -> Vec<(String, String)>
+  {
+    return self.get("ALIASES")
+               .iter()
+               .filter_map(|alias| alias.split_once('='))
+               .map(|(name, expansion)| (name.trim().to_owned(), expansion.to_owned()))
+               .collect();
+  }
+}
+
+/// Runs Doxygen with a Doxyfile, in the directory of the Doxyfile, and waits for it to finish.
+///
+/// The Doxyfile is included by a configuration that is passed on stdin, which overrides it so
+/// that only XML output is generated. The executable is taken from the DOXYGEN environment
+/// variable, if it is set. The output of Doxygen is discarded unless it should be shown, whereas
+/// warnings are always shown.
+pub fn run_doxygen(path: &Path, show_output: bool) -> D2mResult<()>
+{
+  let start_time = SystemTime::now();
+
+  let path = fs::canonicalize(path).map_err(|source| D2mError::READ {
+                                       path: path.to_path_buf(),
+                                       source,
+                                     })?;
+
+  let executable = env::var("DOXYGEN").unwrap_or(String::from("doxygen"));
+  info!("Running {} with {}...", executable, path.display());
+
+  let stdout = if show_output { Stdio::inherit() } else { Stdio::null() };
+  let mut child = Command::new(&executable).arg("-")
+                                           .current_dir(path.parent().unwrap_or(Path::new("")))
+                                           .stdin(Stdio::piped())
+                                           .stdout(stdout)
+                                           .spawn()
+                                           .map_err(|err| {
+                                             D2mError::DOXYGEN(format!("could not start {}: {}",
+                                                                       executable, err))
+                                           })?;
+
+  let config = format!("@INCLUDE = \"{}\"\n{}\n", path.display(), XML_ONLY_SETTINGS.join("\n"));
+
+  // The configuration is complete once stdin is closed, which happens when it is dropped
+  if let Some(mut stdin) = child.stdin.take() {
+    stdin.write_all(config.as_bytes())?;
+  }
+
+  let status = child.wait()?;
+  if !status.success() {
+    return Err(D2mError::DOXYGEN(format!("{} failed with {}", executable, status)));
+  }
+
+  info!("Ran {} in {} ms",
+        executable,
+        start_time.elapsed().map_or(0, |duration| duration.as_millis()));
+
+  return Ok(());
+}
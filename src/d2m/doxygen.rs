@@ -1,12 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Formatter};
 use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::d2m::symbol::Symbol;
 use AccessModifier::*;
 
-pub type RefID = String;
+pub type RefID = Symbol;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AccessModifier
 {
   PRIVATE,
@@ -26,6 +31,22 @@ impl fmt::Display for AccessModifier
   }
 }
 
+impl AccessModifier
+{
+  /// Indicates whether an entity with this access is visible at the specified visibility, e.g.
+  /// protected members are visible at the protected and private visibilities.
+  pub fn is_visible_at(self, visibility: AccessModifier) -> bool
+  {
+    let rank = |access: AccessModifier| match access {
+      PUBLIC => 0,
+      PROTECTED => 1,
+      PRIVATE => 2,
+    };
+
+    return rank(self) <= rank(visibility);
+  }
+}
+
 impl FromStr for AccessModifier
 {
   type Err = &'static str;
@@ -41,7 +62,8 @@ impl FromStr for AccessModifier
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Comment
 {
   pub brief: Vec<String>,
@@ -51,10 +73,18 @@ pub struct Comment
   pub returns: String,
   pub pre_conditions: Vec<String>,
   pub post_conditions: Vec<String>,
+  pub invariants: Vec<String>,
   pub exceptions: HashMap<String, String>,
   pub see_also: Vec<String>,
   pub notes: Vec<String>,
   pub warnings: Vec<String>,
+  pub attentions: Vec<String>,
+  /// The deprecation note, which is empty if the entity is deprecated without an explanation.
+  pub deprecated: Option<String>,
+  /// The contents of "\par Example" sections, where code is written as fenced code blocks.
+  pub examples: Vec<String>,
+  /// The example files that are referenced, see "\example".
+  pub example_refs: Vec<RefID>,
 }
 
 impl Comment
@@ -69,22 +99,70 @@ impl Comment
       returns: String::new(),
       pre_conditions: Vec::new(),
       post_conditions: Vec::new(),
+      invariants: Vec::new(),
       exceptions: HashMap::new(),
       see_also: Vec::new(),
       notes: Vec::new(),
       warnings: Vec::new(),
+      attentions: Vec::new(),
+      deprecated: None,
+      examples: Vec::new(),
+      example_refs: Vec::new(),
     }
   }
+  /// Indicates whether there is a brief or detailed description.
+  pub fn is_documented(&self) -> bool
+  {
+    return self.brief.iter().chain(self.details.iter()).any(|s| !s.is_empty());
+  }
+
+  /// Returns all text of the comment, e.g. to rewrite it, except for the names of parameters and
+  /// exceptions.
+  pub fn get_texts_mut(&mut self) -> impl Iterator<Item=&mut String>
+  {
+    let lists = [&mut self.brief,
+                 &mut self.details,
+                 &mut self.pre_conditions,
+                 &mut self.post_conditions,
+                 &mut self.invariants,
+                 &mut self.see_also,
+                 &mut self.notes,
+                 &mut self.warnings,
+                 &mut self.attentions,
+                 &mut self.examples];
+
+    let maps = [&mut self.parameters, &mut self.template_parameters, &mut self.exceptions];
+
+    return lists.into_iter()
+                .flatten()
+                .chain(maps.into_iter().flat_map(|map| map.values_mut()))
+                .chain(Some(&mut self.returns))
+                .chain(self.deprecated.as_mut());
+  }
+}
+
+/// The place in the source code where an entity is declared.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Location
+{
+  /// The path of the source file, as written by Doxygen, e.g. relative to STRIP_FROM_PATH.
+  pub file: String,
+  pub line: Option<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Variable
 {
-  pub name: String,
-  pub qualified_name: String,
+  pub name: Symbol,
+  pub qualified_name: Symbol,
   pub definition: String,
+  /// The initial value, without the leading '=', which is empty if there is no initializer.
+  pub initializer: String,
   pub access: AccessModifier,
   pub docs: Comment,
+  pub location: Option<Location>,
   pub is_static: bool,
   pub is_constexpr: bool,
   pub is_mutable: bool,
@@ -95,11 +173,13 @@ impl Variable
   pub fn new() -> Self
   {
     Self {
-      name: String::new(),
-      qualified_name: String::new(),
+      name: Symbol::EMPTY,
+      qualified_name: Symbol::EMPTY,
       definition: String::new(),
+      initializer: String::new(),
       access: PRIVATE,
       docs: Comment::new(),
+      location: None,
       is_static: false,
       is_constexpr: false,
       is_mutable: false,
@@ -107,18 +187,20 @@ impl Variable
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Function
 {
-  pub name: String,
-  pub qualified_name: String,
-  pub return_type: String,
+  pub name: Symbol,
+  pub qualified_name: Symbol,
+  pub return_type: Symbol,
   pub args: String,
-  pub parameter_names: Vec<String>,
-  pub template_args: Vec<String>,
+  pub parameter_names: Vec<Symbol>,
+  pub template_args: Vec<Symbol>,
   pub definition: String,
   pub access: AccessModifier,
   pub docs: Comment,
+  pub location: Option<Location>,
   pub is_static: bool,
   pub is_const: bool,
   pub is_inline: bool,
@@ -126,6 +208,11 @@ pub struct Function
   pub is_virtual: bool,
   pub is_explicit: bool,
   pub is_member: bool,
+  /// Whether the function is a non-member function that is related to a class, see `\relates`.
+  pub is_related: bool,
+  /// The entities referenced by the return, parameter, and template parameter types, along with
+  /// the referencing text, e.g. "point" in "const point &".
+  pub type_refs: Vec<(Symbol, RefID)>,
 }
 
 impl Function
@@ -133,15 +220,16 @@ impl Function
   pub fn new(is_member: bool) -> Self
   {
     Self {
-      name: String::new(),
-      qualified_name: String::new(),
-      return_type: String::new(),
+      name: Symbol::EMPTY,
+      qualified_name: Symbol::EMPTY,
+      return_type: Symbol::EMPTY,
       args: String::new(),
       parameter_names: Vec::new(),
       template_args: Vec::new(),
       definition: String::new(),
       access: PRIVATE,
       docs: Comment::new(),
+      location: None,
       is_static: false,
       is_const: false,
       is_inline: false,
@@ -149,16 +237,20 @@ impl Function
       is_virtual: false,
       is_explicit: false,
       is_member,
+      is_related: false,
+      type_refs: Vec::new(),
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Class
 {
   pub unqualified_name: String,
-  pub template_args: Vec<String>,
+  pub template_args: Vec<Symbol>,
   pub is_struct: bool,
+  #[allow(dead_code)]
   pub is_interface: bool,
 }
 
@@ -195,10 +287,16 @@ impl Class
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Define
 {
-  pub name: String,
+  pub name: Symbol,
+  pub parameters: Vec<String>,
+  pub initializer: String,
+  pub docs: Comment,
+  pub location: Option<Location>,
+  pub is_function_like: bool,
 }
 
 impl Define
@@ -206,15 +304,21 @@ impl Define
   pub fn new() -> Self
   {
     Self {
-      name: String::from("?"),
+      name: Symbol::intern("?"),
+      parameters: Vec::new(),
+      initializer: String::new(),
+      docs: Comment::new(),
+      location: None,
+      is_function_like: false,
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnumValue
 {
-  pub name: String,
+  pub name: Symbol,
   pub initializer: String,
   pub docs: Comment,
 }
@@ -224,20 +328,22 @@ impl EnumValue
   pub fn new() -> Self
   {
     Self {
-      name: String::new(),
+      name: Symbol::EMPTY,
       initializer: String::new(),
       docs: Comment::new(),
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum
 {
-  pub name: String,
-  pub qualified_name: String,
+  pub name: Symbol,
+  pub qualified_name: Symbol,
   pub values: Vec<EnumValue>,
   pub docs: Comment,
+  pub location: Option<Location>,
   pub is_scoped: bool,
 }
 
@@ -246,16 +352,18 @@ impl Enum
   pub fn new() -> Self
   {
     Self {
-      name: String::new(),
-      qualified_name: String::new(),
+      name: Symbol::EMPTY,
+      qualified_name: Symbol::EMPTY,
       values: Vec::new(),
       docs: Comment::new(),
+      location: None,
       is_scoped: false,
     }
   }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CompoundKind
 {
   UNKNOWN,
@@ -268,6 +376,28 @@ pub enum CompoundKind
   CONCEPT,
   PAGE,
   GROUP,
+  EXAMPLE,
+}
+
+impl CompoundKind
+{
+  /// Returns the name of the kind as used by Doxygen, e.g. "class".
+  pub fn name(self) -> &'static str
+  {
+    return match self {
+      Self::UNKNOWN => "unknown",
+      Self::FILE => "file",
+      Self::DIRECTORY => "dir",
+      Self::NAMESPACE => "namespace",
+      Self::CLASS => "class",
+      Self::STRUCT => "struct",
+      Self::INTERFACE => "interface",
+      Self::CONCEPT => "concept",
+      Self::PAGE => "page",
+      Self::GROUP => "group",
+      Self::EXAMPLE => "example",
+    };
+  }
 }
 
 impl FromStr for CompoundKind
@@ -286,15 +416,17 @@ impl FromStr for CompoundKind
       "concept" => Ok(Self::CONCEPT),
       "page" => Ok(Self::PAGE),
       "group" => Ok(Self::GROUP),
+      "example" => Ok(Self::EXAMPLE),
       _ => Err("Unsupported compound kind string!"),
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Compound
 {
-  pub name: String,
+  pub name: Symbol,
   pub title: String,
   pub kind: CompoundKind,
   pub groups: Vec<RefID>,
@@ -306,6 +438,7 @@ pub struct Compound
   pub variables: Vec<RefID>,
   pub defines: Vec<RefID>,
   pub docs: Comment,
+  pub location: Option<Location>,
 }
 
 impl Compound
@@ -313,7 +446,7 @@ impl Compound
   pub fn new() -> Self
   {
     Self {
-      name: String::new(),
+      name: Symbol::EMPTY,
       title: String::new(),
       kind: CompoundKind::UNKNOWN,
       groups: Vec::new(),
@@ -325,11 +458,64 @@ impl Compound
       variables: Vec::new(),
       defines: Vec::new(),
       docs: Comment::new(),
+      location: None,
     }
   }
 }
 
-#[derive(Debug)]
+/// A documented entity of a registry, which is either a compound or a member of one.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone)]
+pub enum Entity<'a>
+{
+  COMPOUND(&'a Compound),
+  FUNCTION(&'a Function),
+  ENUM(&'a Enum),
+  VARIABLE(&'a Variable),
+  DEFINE(&'a Define),
+}
+
+impl<'a> Entity<'a>
+{
+  /// Returns the qualified name of the entity, e.g. "foo::bar".
+  pub fn get_name(&self) -> &'a str
+  {
+    return match self {
+      Self::COMPOUND(compound) => compound.name.as_str(),
+      Self::FUNCTION(func) => func.qualified_name.as_str(),
+      Self::ENUM(enum_def) => enum_def.qualified_name.as_str(),
+      Self::VARIABLE(var) => var.qualified_name.as_str(),
+      Self::DEFINE(define) => define.name.as_str(),
+    };
+  }
+
+  /// Returns the name of the kind of the entity, e.g. "class" or "function".
+  pub fn get_kind_name(&self) -> &'static str
+  {
+    return match self {
+      Self::COMPOUND(compound) => compound.kind.name(),
+      Self::FUNCTION(_) => "function",
+      Self::ENUM(_) => "enum",
+      Self::VARIABLE(_) => "variable",
+      Self::DEFINE(_) => "define",
+    };
+  }
+
+  /// Returns the documentation of the entity.
+  pub fn get_docs(&self) -> &'a Comment
+  {
+    return match self {
+      Self::COMPOUND(compound) => &compound.docs,
+      Self::FUNCTION(func) => &func.docs,
+      Self::ENUM(enum_def) => &enum_def.docs,
+      Self::VARIABLE(var) => &var.docs,
+      Self::DEFINE(define) => &define.docs,
+    };
+  }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Registry
 {
   pub compounds: HashMap<RefID, Compound>,
@@ -339,6 +525,9 @@ pub struct Registry
   pub functions: HashMap<RefID, Function>,
   pub variables: HashMap<RefID, Variable>,
   pub defines: HashMap<RefID, Define>,
+  /// The version of Doxygen that generated the XML files, e.g. "1.9.3", which may be unknown.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub doxygen_version: String,
 }
 
 impl Registry
@@ -353,14 +542,264 @@ impl Registry
       functions: HashMap::new(),
       variables: HashMap::new(),
       defines: HashMap::new(),
+      doxygen_version: String::new(),
+    }
+  }
+
+  /// Indicates whether any entity is deprecated.
+  pub fn has_deprecated(&self) -> bool
+  {
+    return self.compounds.values().any(|c| c.docs.deprecated.is_some())
+           || self.functions.values().any(|f| f.docs.deprecated.is_some())
+           || self.enums.values().any(|e| e.docs.deprecated.is_some())
+           || self.variables.values().any(|v| v.docs.deprecated.is_some())
+           || self.defines.values().any(|d| d.docs.deprecated.is_some());
+  }
+
+  /// Removes the functions and variables that aren't visible at the specified visibility.
+  pub fn remove_hidden_members(&mut self, visibility: AccessModifier)
+  {
+    self.functions.retain(|_, func| func.access.is_visible_at(visibility));
+    self.variables.retain(|_, var| var.access.is_visible_at(visibility));
+
+    for compound in self.compounds.values_mut() {
+      compound.functions.retain(|id| self.functions.contains_key(id));
+      compound.variables.retain(|id| self.variables.contains_key(id));
+    }
+  }
+
+  /// Removes compounds, along with their members that aren't listed by any other compound, and
+  /// returns the IDs of the removed members.
+  pub fn remove_compounds(&mut self, ids: &HashSet<RefID>) -> HashSet<RefID>
+  {
+    let mut members = HashSet::new();
+
+    for id in ids {
+      if let Some(compound) = self.compounds.remove(id) {
+        self.classes.remove(id);
+
+        members.extend(compound.enums);
+        members.extend(compound.enum_values);
+        members.extend(compound.functions);
+        members.extend(compound.variables);
+        members.extend(compound.defines);
+      }
+    }
+
+    // Members may be listed by several compounds, e.g. by both a namespace and a file
+    for compound in self.compounds.values_mut() {
+      compound.groups.retain(|id| !ids.contains(id));
+      compound.namespaces.retain(|id| !ids.contains(id));
+      compound.classes.retain(|id| !ids.contains(id));
+
+      for id in compound.enums
+                        .iter()
+                        .chain(&compound.enum_values)
+                        .chain(&compound.functions)
+                        .chain(&compound.variables)
+                        .chain(&compound.defines)
+      {
+        members.remove(id);
+      }
+    }
+
+    self.remove_members(&members);
+
+    return members;
+  }
+
+  /// Removes members, e.g. functions and enums, including from the compounds that list them.
+  pub fn remove_members(&mut self, ids: &HashSet<RefID>)
+  {
+    for id in ids {
+      self.enums.remove(id);
+      self.enum_values.remove(id);
+      self.functions.remove(id);
+      self.variables.remove(id);
+      self.defines.remove(id);
+    }
+
+    for compound in self.compounds.values_mut() {
+      compound.enums.retain(|id| !ids.contains(id));
+      compound.enum_values.retain(|id| !ids.contains(id));
+      compound.functions.retain(|id| !ids.contains(id));
+      compound.variables.retain(|id| !ids.contains(id));
+      compound.defines.retain(|id| !ids.contains(id));
+    }
+  }
+
+  /// Adds the entities of a registry that was parsed separately, e.g. from a single file, which
+  /// replace any entities with the same IDs.
+  pub fn merge(&mut self, other: Registry)
+  {
+    self.compounds.extend(other.compounds);
+    self.classes.extend(other.classes);
+    self.enums.extend(other.enums);
+    self.enum_values.extend(other.enum_values);
+    self.variables.extend(other.variables);
+    self.defines.extend(other.defines);
+
+    if self.doxygen_version.is_empty() {
+      self.doxygen_version = other.doxygen_version;
+    }
+
+    for (id, mut func) in other.functions {
+      // Functions may be defined by several compounds, where the first declaration determines
+      // whether it is a member, unless any of them lists it as related to a class
+      if let Some(existing) = self.functions.get(&id) {
+        func.is_member = existing.is_member && !func.is_related;
+        func.is_related |= existing.is_related;
+      }
+
+      self.functions.insert(id, func);
     }
   }
 
   pub fn add_compound(&mut self, id: RefID, kind: CompoundKind, name: String)
   {
     let mut compound = Compound::new();
-    compound.name = name;
+    compound.name = Symbol::from(name);
     compound.kind = kind;
     self.compounds.insert(id, compound);
   }
+
+  /// Returns the comments of all entities, e.g. to rewrite their text.
+  pub fn get_comments_mut(&mut self) -> impl Iterator<Item=&mut Comment>
+  {
+    let enums = self.enums.values_mut().flat_map(|enumeration| {
+                                         enumeration.values
+                                                    .iter_mut()
+                                                    .map(|value| &mut value.docs)
+                                                    .chain(Some(&mut enumeration.docs))
+                                       });
+
+    return self.compounds
+               .values_mut()
+               .map(|compound| &mut compound.docs)
+               .chain(enums)
+               .chain(self.enum_values.values_mut().map(|value| &mut value.docs))
+               .chain(self.functions.values_mut().map(|func| &mut func.docs))
+               .chain(self.variables.values_mut().map(|var| &mut var.docs))
+               .chain(self.defines.values_mut().map(|define| &mut define.docs));
+  }
+
+  /// Returns the classes of a compound, e.g. a group or namespace, which are in the registry.
+  pub fn get_classes<'a>(&'a self, id: &RefID) -> impl Iterator<Item=(&'a RefID, &'a Compound)>
+  {
+    return self.get_children(id, |compound| &compound.classes);
+  }
+
+  /// Returns the subgroups of a group, which are in the registry, sorted by title.
+  pub fn get_subgroups(&self, id: &RefID) -> Vec<(&RefID, &Compound)>
+  {
+    let mut subgroups: Vec<(&RefID, &Compound)> =
+      self.get_children(id, |compound| &compound.groups).collect();
+    subgroups.sort_by_key(|(_, group)| &group.title);
+    return subgroups;
+  }
+
+  fn get_children<'a, F>(&'a self, id: &RefID, get_ids: F)
+    -> impl Iterator<Item=(&'a RefID, &'a Compound)>
+    where F: Fn(&'a Compound) -> &'a Vec<RefID>
+  {
+    return self.compounds
+               .get(id)
+               .map(get_ids)
+               .into_iter()
+               .flatten()
+               .filter_map(|child_id| self.compounds.get_key_value(child_id));
+  }
+
+  /// Returns the groups in depth-first order along with their depths, where the groups that are
+  /// not nested in other groups are at depth zero, and siblings are sorted by title.
+  pub fn walk_groups(&self) -> Vec<(&RefID, &Compound, usize)>
+  {
+    let nested: HashSet<&RefID> = self.compounds
+                                      .values()
+                                      .filter(|c| c.kind == CompoundKind::GROUP)
+                                      .flat_map(|c| c.groups.iter())
+                                      .collect();
+
+    let mut top_level: Vec<(&RefID, &Compound)> =
+      self.compounds
+          .iter()
+          .filter(|(id, c)| c.kind == CompoundKind::GROUP && !nested.contains(id))
+          .collect();
+    top_level.sort_by_key(|(_, c)| &c.title);
+
+    let mut groups = Vec::new();
+    let mut visited = HashSet::new();
+
+    // Walks the groups in reverse, so that the first sibling is popped first
+    let mut stack: Vec<(&RefID, &Compound, usize)> =
+      top_level.into_iter().rev().map(|(id, group)| (id, group, 0)).collect();
+
+    while let Some((id, group, depth)) = stack.pop() {
+      // Guards against groups that are (indirectly) nested in themselves
+      if !visited.insert(id) {
+        continue;
+      }
+
+      groups.push((id, group, depth));
+
+      for (child_id, child) in self.get_subgroups(id).into_iter().rev() {
+        stack.push((child_id, child, depth + 1));
+      }
+    }
+
+    return groups;
+  }
+
+  /// Returns all compounds, functions, enums, variables, and macros in the registry.
+  pub fn get_entities(&self) -> impl Iterator<Item=(&RefID, Entity<'_>)>
+  {
+    let compounds = self.compounds.iter().map(|(id, c)| (id, Entity::COMPOUND(c)));
+    let functions = self.functions.iter().map(|(id, f)| (id, Entity::FUNCTION(f)));
+    let enums = self.enums.iter().map(|(id, e)| (id, Entity::ENUM(e)));
+    let variables = self.variables.iter().map(|(id, v)| (id, Entity::VARIABLE(v)));
+    let defines = self.defines.iter().map(|(id, d)| (id, Entity::DEFINE(d)));
+
+    return compounds.chain(functions).chain(enums).chain(variables).chain(defines);
+  }
+
+  /// Returns the entities with a qualified name, e.g. "foo::bar", sorted by ID, where there may
+  /// be several due to overloads.
+  pub fn find_by_name(&self, qualified_name: &str) -> Vec<(&RefID, Entity<'_>)>
+  {
+    let mut entities: Vec<(&RefID, Entity<'_>)> =
+      self.get_entities().filter(|(_, entity)| entity.get_name() == qualified_name).collect();
+    entities.sort_by_key(|(id, _)| *id);
+    return entities;
+  }
+
+  /// Returns the compound that owns a member, e.g. a function, preferring classes over
+  /// namespaces, groups, and files, since members are listed by all of them.
+  pub fn find_owner(&self, member_id: &RefID) -> Option<&RefID>
+  {
+    let get_rank = |kind: CompoundKind| -> Option<usize> {
+      return match kind {
+        CompoundKind::CLASS
+        | CompoundKind::STRUCT
+        | CompoundKind::INTERFACE
+        | CompoundKind::CONCEPT => Some(0),
+        CompoundKind::NAMESPACE => Some(1),
+        CompoundKind::GROUP => Some(2),
+        CompoundKind::FILE => Some(3),
+        _ => None,
+      };
+    };
+
+    return self.compounds
+               .iter()
+               .filter(|(_, c)| {
+                 c.functions.contains(member_id)
+                 || c.variables.contains(member_id)
+                 || c.enums.contains(member_id)
+                 || c.enum_values.contains(member_id)
+                 || c.defines.contains(member_id)
+               })
+               .filter_map(|(id, c)| get_rank(c.kind).map(|rank| (rank, id)))
+               .min()
+               .map(|(_, id)| id);
+  }
 }
@@ -0,0 +1,135 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::d2m::doxygen::RefID;
+
+/// The errors that abort a conversion.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Error)]
+pub enum D2mError
+{
+  /// An input or output operation failed, e.g. when writing a page.
+  #[error(transparent)]
+  IO(#[from] io::Error),
+
+  /// The input directory doesn't exist.
+  #[error("input directory {} does not exist", .0.display())]
+  MISSING_INPUT(PathBuf),
+
+  /// An input file could not be read.
+  #[error("could not read {}: {source}", path.display())]
+  READ { path: PathBuf, source: io::Error },
+
+  /// An input file is not well-formed XML.
+  #[error("{} is not valid XML: {reason}", path.display())]
+  MALFORMED_XML { path: PathBuf, reason: String },
+
+  /// An element lacks an attribute that Doxygen always emits.
+  #[error("<{element}> element is missing the '{attribute}' attribute")]
+  MISSING_ATTRIBUTE { element: String, attribute: &'static str },
+
+  /// An element lacks a child element that Doxygen always emits.
+  #[error("<{element}> element is missing a <{child}> element")]
+  MISSING_CHILD { element: String, child: &'static str },
+
+  /// An attribute has a value that Doxygen never emits.
+  #[error("unexpected value '{value}' of the '{attribute}' attribute of <{element}>")]
+  INVALID_ATTRIBUTE { element: String, attribute: &'static str, value: String },
+
+  /// A compound file defines an entity that isn't declared in the index file.
+  #[error("found definition of '{0}', which is not declared in the index file")]
+  UNDECLARED(RefID),
+
+  /// An error in an input file, with the path of the file.
+  #[error("{}: {source}", path.display())]
+  IN_FILE { path: PathBuf, source: Box<D2mError> },
+
+  /// The configuration file could not be loaded.
+  #[error("failed to load configuration file {}: {reason}", path.display())]
+  CONFIG { path: PathBuf, reason: String },
+
+  /// A tag file could not be loaded.
+  #[error("failed to load tag file {spec}: {reason}")]
+  TAG_FILE { spec: String, reason: String },
+
+  /// The templates in a directory could not be loaded.
+  #[error("failed to load templates in {}: {reason}", path.display())]
+  TEMPLATES { path: PathBuf, reason: String },
+
+  /// A template from the configuration file could not be loaded.
+  #[error("failed to load the {name} template: {reason}")]
+  TEMPLATE { name: String, reason: String },
+
+  /// A compound that was asked for doesn't exist.
+  #[error("found no class, group, or namespace '{0}'")]
+  UNKNOWN_COMPOUND(String),
+
+  /// An input file doesn't define a compound that has a page.
+  #[error("{} does not define a class, group, or namespace", .0.display())]
+  NO_COMPOUND(PathBuf),
+
+  /// An archive with input files could not be extracted.
+  #[error("could not extract {}: {reason}", path.display())]
+  ARCHIVE { path: PathBuf, reason: String },
+
+  /// Doxygen could not be run, or it failed.
+  #[error("failed to run Doxygen: {0}")]
+  DOXYGEN(String),
+
+  /// A script could not be compiled, or one of its functions failed.
+  #[error("error in script {}: {reason}", path.display())]
+  SCRIPT { path: PathBuf, reason: String },
+
+  /// The post-generation hook could not be run, or it failed.
+  #[error("failed to run the post-generation hook: {0}")]
+  HOOK(String),
+
+  /// The input directory could not be watched for changes.
+  #[error("could not watch {} for changes: {reason}", path.display())]
+  WATCH { path: PathBuf, reason: String },
+}
+
+pub type D2mResult<T> = Result<T, D2mError>;
+
+impl D2mError
+{
+  /// Attaches the path of the file that caused an error, unless it is already known.
+  pub fn in_file(self, path: PathBuf) -> Self
+  {
+    return match self {
+      D2mError::READ { .. } | D2mError::MALFORMED_XML { .. } | D2mError::IN_FILE { .. } => self,
+      error => D2mError::IN_FILE { path, source: Box::new(error) },
+    };
+  }
+
+  /// Returns the path of the file that caused the error, if it is known.
+  pub fn get_path(&self) -> Option<&Path>
+  {
+    return match self {
+      D2mError::READ { path, .. }
+      | D2mError::MALFORMED_XML { path, .. }
+      | D2mError::IN_FILE { path, .. }
+      | D2mError::CONFIG { path, .. }
+      | D2mError::TEMPLATES { path, .. }
+      | D2mError::SCRIPT { path, .. }
+      | D2mError::ARCHIVE { path, .. } => Some(path),
+      _ => None,
+    };
+  }
+
+  /// Indicates whether the error was caused by an input file that could not be parsed.
+  pub fn is_parse_failure(&self) -> bool
+  {
+    return matches!(self,
+                    D2mError::READ { .. }
+                    | D2mError::MALFORMED_XML { .. }
+                    | D2mError::MISSING_ATTRIBUTE { .. }
+                    | D2mError::MISSING_CHILD { .. }
+                    | D2mError::INVALID_ATTRIBUTE { .. }
+                    | D2mError::UNDECLARED(_)
+                    | D2mError::IN_FILE { .. }
+                    | D2mError::NO_COMPOUND(_));
+  }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use glob::Pattern;
+use log::info;
+use regex::Regex;
+
+use crate::d2m::diagnostics;
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::{Compound, RefID, Registry, Symbol};
+
+/// Selects the input files that are parsed, by matching their names against glob patterns.
+#[derive(Debug, Clone)]
+pub struct FileFilter
+{
+  /// If not empty, only files that match any of these patterns are parsed.
+  pub include: Vec<Pattern>,
+  /// Files that match any of these patterns are never parsed, even if they are included.
+  pub exclude: Vec<Pattern>,
+  /// Whether the compounds of skipped files are kept as declared by the index, so that they can
+  /// still be linked to, rather than being removed.
+  pub keep_skipped: bool,
+}
+
+impl FileFilter
+{
+  pub fn new() -> Self
+  {
+    Self {
+      include: Vec::new(),
+      exclude: Vec::new(),
+      keep_skipped: false,
+    }
+  }
+
+  /// Indicates whether a file should be parsed, based on its name.
+  pub fn is_included(&self, path: &Path) -> bool
+  {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    let is_included = self.include.is_empty() || self.include.iter().any(|p| p.matches(&name));
+    return is_included && !self.exclude.iter().any(|p| p.matches(&name));
+  }
+}
+
+/// Returns a pattern of excluded symbols, e.g. "detail::.*|.*_impl", which must match either a
+/// whole qualified name or a trailing part of it, such as "detail::foo" in "cen::detail::foo".
+pub fn parse_symbol_pattern(pattern: &str) -> Result<Regex, regex::Error>
+{
+  return Regex::new(&format!("^(?:{})$", pattern));
+}
+
+fn is_symbol_match(pattern: &Regex, name: &str) -> bool
+{
+  return pattern.is_match(name)
+         || name.match_indices("::").any(|(index, _)| pattern.is_match(&name[index + 2..]));
+}
+
+/// Removes the classes, namespaces, and members whose qualified names match a pattern, along with
+/// everything in the removed classes and namespaces. This mirrors the EXCLUDE_SYMBOLS option of
+/// Doxygen, and references to the removed entities are rendered as plain text without warnings.
+pub fn exclude_symbols(registry: &mut Registry, pattern: &Regex)
+{
+  let is_scope = |compound: &Compound| {
+    matches!(compound.kind, NAMESPACE | CLASS | STRUCT | INTERFACE | CONCEPT)
+  };
+
+  let scopes: Vec<Symbol> = registry.compounds
+                                    .values()
+                                    .filter(|compound| is_scope(compound))
+                                    .filter(|compound| is_symbol_match(pattern, &compound.name))
+                                    .map(|compound| compound.name.clone())
+                                    .collect();
+
+  let is_excluded = |name: &str| {
+    is_symbol_match(pattern, name)
+    || scopes.iter().any(|scope| {
+                      name.strip_prefix(scope.as_str()).is_some_and(|rest| rest.starts_with("::"))
+                    })
+  };
+
+  let compounds: HashSet<RefID> =
+    registry.compounds
+            .iter()
+            .filter(|(_, compound)| is_scope(compound) && is_excluded(&compound.name))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+  let mut members: HashSet<RefID> = HashSet::new();
+  members.extend(registry.functions
+                         .iter()
+                         .filter(|(_, func)| is_excluded(&func.qualified_name))
+                         .map(|(id, _)| id.clone()));
+  members.extend(registry.enums
+                         .iter()
+                         .filter(|(_, enumeration)| is_excluded(&enumeration.qualified_name))
+                         .map(|(id, _)| id.clone()));
+  members.extend(registry.variables
+                         .iter()
+                         .filter(|(_, var)| is_excluded(&var.qualified_name))
+                         .map(|(id, _)| id.clone()));
+  members.extend(registry.defines
+                         .iter()
+                         .filter(|(_, define)| is_excluded(&define.name))
+                         .map(|(id, _)| id.clone()));
+
+  if compounds.is_empty() && members.is_empty() {
+    return;
+  }
+
+  info!("Excluding {} compound(s) and {} member(s) matching the symbol pattern",
+        compounds.len(),
+        members.len());
+
+  let orphans = registry.remove_compounds(&compounds);
+  registry.remove_members(&members);
+
+  diagnostics::exclude(compounds.into_iter().chain(orphans).chain(members));
+}
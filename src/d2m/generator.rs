@@ -1,181 +1,1342 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufWriter, Write};
-use std::path::PathBuf;
-use std::time::SystemTime;
-
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, trace};
+use regex::Regex;
+use serde_json::json;
+
+use crate::d2m::autolinks::AutoLinker;
+use crate::d2m::backend;
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::dialect::{self, Dialect};
+use crate::d2m::config::{FrontMatterFormat, PageOrder, Provenance};
 use crate::d2m::doxygen::*;
 use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::error::D2mResult;
+use crate::d2m::links::{FilenameCase, Index, Layout, LinkOptions, LinkResolver, LinkStyle,
+                        SlugStyle};
+use crate::d2m::llms;
+use crate::d2m::navigation::{self, NavItem, OrphanPolicy};
+use crate::d2m::profile;
+use crate::d2m::progress;
+use crate::d2m::replacements;
+use crate::d2m::script::Script;
+use crate::d2m::signature;
+use crate::d2m::stats::{self, FileSource};
+use crate::d2m::templates::Templates;
+
+pub type EmitResult = io::Result<()>;
+
+/// Pages are rendered into memory before being written to disk.
+pub type PageBuffer = Vec<u8>;
+
+/// The kinds of pages that can be generated selectively.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum PageKind
+{
+  /// The pages of groups, i.e. modules.
+  GROUPS,
+  /// The pages of classes and structs.
+  CLASSES,
+  /// The pages of namespaces.
+  NAMESPACES,
+  /// The main index and the indexes of modules, classes, namespaces, functions, and so on.
+  INDEXES,
+}
+
+/// The site generators that front matter can be emitted for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum FrontMatterStyle
+{
+  HUGO,
+}
+
+/// Options that control the generated output.
+pub struct GeneratorOptions
+{
+  /// The name of the project, if known.
+  pub project_name: Option<String>,
+  /// The layout of the generated files.
+  pub layout: Layout,
+  /// The conventions used to compute heading anchors.
+  pub slug_style: SlugStyle,
+  /// The flavor of Markdown that pages are written in.
+  pub dialect: Dialect,
+  /// A pattern for the paths of compound pages, the default paths are used if absent.
+  pub filename_scheme: Option<String>,
+  /// How page paths are compared when checking that they are unique.
+  pub filename_case: FilenameCase,
+  /// A URL or path that links to other pages are rooted at, links are relative if absent.
+  pub link_base: Option<String>,
+  /// Whether to emit a table of contents at the top of class, group, and namespace pages.
+  pub toc: bool,
+  /// Whether to show badges with the specifiers of members below their headings.
+  pub badges: bool,
+  /// Whether to link the classes and enums used by function signatures below the signatures.
+  pub type_links: bool,
+  /// The order used for links to the previous and next pages, if any.
+  pub page_navigation: Option<PageOrder>,
+  /// Whether to emit a navigation fragment for MkDocs.
+  pub mkdocs_nav: bool,
+  /// Whether to emit a summary for mdBook.
+  pub mdbook: bool,
+  /// Whether to emit a sidebar for VitePress.
+  pub vitepress_sidebar: bool,
+  /// Whether to emit a JSON map of the pages and anchors of all entities.
+  pub symbol_map: bool,
+  /// Whether to emit an llms.txt file that summarizes the API with links to its pages.
+  pub llms_txt: bool,
+  /// Whether to emit an llms-full.txt file with the contents of all pages.
+  pub llms_full_txt: bool,
+  /// Whether to emit wiki links and alias front matter for Obsidian.
+  pub obsidian: bool,
+  /// Whether to emit a sidebar and page front matter for Docusaurus.
+  pub docusaurus: bool,
+  /// A prefix for the page paths in navigation files.
+  pub nav_prefix: String,
+  /// The site generator to emit page front matter for, if any.
+  pub front_matter: Option<FrontMatterStyle>,
+  pub front_matter_format: FrontMatterFormat,
+  /// Front matter entries added to every page, where strings may contain page placeholders.
+  pub extra_front_matter: toml::Table,
+  /// The templates used to render pages.
+  pub templates: Templates,
+  /// Prefixes of qualified names and the URL templates that such names are linked to.
+  pub autolinks: Vec<(String, String)>,
+  /// Patterns and the text that replaces their matches in pages, outside of code blocks.
+  pub replacements: Vec<(Regex, String)>,
+  /// A script that customizes rendering decisions, e.g. the headings of functions.
+  pub script: Option<Script>,
+  /// Paths of compound pages that replace the ones from the layout, e.g. from the configuration.
+  pub page_paths: HashMap<RefID, String>,
+  /// The origin of the documentation, which is noted at the bottom of every page if present.
+  pub provenance: Option<Provenance>,
+  /// What to do with compounds that aren't featured in the module or namespace trees.
+  pub orphans: OrphanPolicy,
+  /// The number of entries above which the class and symbol indexes are split by letter.
+  pub split_indexes: Option<usize>,
+  /// Whether to only emit the signatures and briefs of entities, without detailed descriptions.
+  pub minimal: bool,
+  /// Whether to list the free functions of all groups in a table on the main index.
+  pub functions_by_module: bool,
+  /// The kinds of pages to generate, where all pages are generated if empty.
+  pub only: Vec<PageKind>,
+  /// The only compound to generate a page for, if any, in which case the indexes and navigation
+  /// files are left as they are.
+  pub compound: Option<RefID>,
+}
+
+impl GeneratorOptions
+{
+  pub fn new() -> Self
+  {
+    Self {
+      project_name: None,
+      layout: Layout::DEFAULT,
+      slug_style: SlugStyle::GITHUB,
+      dialect: Dialect::GFM,
+      filename_scheme: None,
+      filename_case: FilenameCase::AUTO,
+      link_base: None,
+      toc: false,
+      badges: false,
+      type_links: false,
+      page_navigation: None,
+      mkdocs_nav: false,
+      mdbook: false,
+      vitepress_sidebar: false,
+      symbol_map: false,
+      llms_txt: false,
+      llms_full_txt: false,
+      obsidian: false,
+      docusaurus: false,
+      nav_prefix: String::new(),
+      front_matter: None,
+      front_matter_format: FrontMatterFormat::YAML,
+      extra_front_matter: toml::Table::new(),
+      templates: Templates::new(),
+      autolinks: Vec::new(),
+      replacements: Vec::new(),
+      script: None,
+      page_paths: HashMap::new(),
+      provenance: None,
+      orphans: OrphanPolicy::IGNORE,
+      split_indexes: None,
+      minimal: false,
+      functions_by_module: false,
+      only: Vec::new(),
+      compound: None,
+    }
+  }
+
+  /// Indicates whether pages of the specified kind are generated.
+  pub fn generates(&self, kind: PageKind) -> bool
+  {
+    if kind == PageKind::INDEXES && self.compound.is_some() {
+      return false;
+    }
+
+    return self.only.is_empty() || self.only.contains(&kind);
+  }
+}
+
+/// State shared by the generated pages.
+struct Context<'a>
+{
+  registry: &'a Registry,
+  options: &'a GeneratorOptions,
+  output_dir: &'a Path,
+  links: LinkResolver,
+  /// The previous and next pages of each compound page.
+  neighbors: HashMap<RefID, (Option<RefID>, Option<RefID>)>,
+  /// The position of each page in the navigation structure, starting at 1.
+  weights: HashMap<String, usize>,
+  /// The compounds listed in the "Miscellaneous" section of the module index.
+  miscellaneous: Vec<RefID>,
+  /// The headings of the functions whose sections aren't titled with their qualified names.
+  headings: HashMap<RefID, String>,
+  /// The date of the run, available to the page header and footer templates.
+  date: String,
+  /// The line at the bottom of every page that notes the origin of the documentation, if any.
+  provenance: Option<String>,
+}
+
+/// Describes a generated page.
+struct Page<'a>
+{
+  /// The page path, relative to the output directory.
+  path: &'a str,
+  title: &'a str,
+  /// A short summary of the page, may be empty.
+  description: &'a str,
+  /// The name of the documented entity, e.g. "foo::bar", or the title of an index page.
+  name: &'a str,
+  /// The kind of the documented entity, e.g. "class", or "index" for index pages.
+  kind: &'a str,
+  /// The ID of the documented compound, which is absent for index pages.
+  id: Option<&'a RefID>,
+}
+
+/// The names of the optional templates rendered at the top and bottom of every page.
+pub const PAGE_HEADER_TEMPLATE: &str = "page_header";
+pub const PAGE_FOOTER_TEMPLATE: &str = "page_footer";
+
+/// Returns the current date in UTC as "YYYY-MM-DD", where the "SOURCE_DATE_EPOCH" environment
+/// variable takes precedence over the system time, for reproducible output.
+pub fn get_current_date() -> String
+{
+  let seconds = env::var("SOURCE_DATE_EPOCH")
+    .ok()
+    .and_then(|value| value.trim().parse::<u64>().ok())
+    .unwrap_or_else(|| {
+      SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+    });
+
+  // Converts days since the epoch to a civil date, using the algorithm described at
+  // http://howardhinnant.github.io/date_algorithms.html
+  let days = (seconds / 86400) as i64 + 719468;
+  let era = days.div_euclid(146097);
+  let day_of_era = days.rem_euclid(146097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let shifted_month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+  let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  return format!("{:04}-{:02}-{:02}", year, month, day);
+}
+
+/// Returns the line that notes the versions of the tools that generated a page, along with the
+/// project and commit of the documented sources, if they are known.
+fn get_provenance(registry: &Registry, provenance: &Provenance) -> String
+{
+  let mut text = format!("Generated by doxy-to-md {} from ", env!("CARGO_PKG_VERSION"));
 
-type EmitResult = io::Result<()>;
+  match registry.doxygen_version.as_str() {
+    "" => text += "the output of Doxygen",
+    version => text += &format!("the output of Doxygen {}", version),
+  }
+
+  if let Some(source) = &provenance.source {
+    text += &format!(" for {}", source);
+  }
+
+  if let Some(commit) = &provenance.commit {
+    text += &format!(" at commit `{}`", commit);
+  }
+
+  return format!("*{}.*", text);
+}
+
+/// Renders the page header or footer template, if there is one.
+fn render_page_decoration(ctx: &Context, page: &Page, template: &str) -> io::Result<Option<String>>
+{
+  if !ctx.options.templates.contains(template) {
+    return Ok(None);
+  }
+
+  let mut context = tera::Context::new();
+  context.insert("name", page.name);
+  context.insert("title", page.title);
+  context.insert("kind", page.kind);
+  context.insert("path", page.path);
+  context.insert("date", &ctx.date);
+  context.insert("project", &ctx.options.project_name);
+
+  let text = ctx.options.templates.render(template, &context)?;
+  return Ok(Some(text.trim().to_owned()).filter(|text| !text.is_empty()));
+}
+
+/// Returns the path of a page relative to the output directory, without the file extension.
+fn get_page_stem(path: &str) -> &str
+{
+  return path.strip_suffix(".md").unwrap_or(path);
+}
+
+/// The entries of the front matter of a page, in order.
+type FrontMatter = Vec<(String, toml::Value)>;
 
-fn generate_group_filename(name: &str) -> String
+/// Returns the front matter that Docusaurus uses to identify and route a page.
+fn get_docusaurus_front_matter(ctx: &Context, page: &Page) -> FrontMatter
 {
-  return format!("group_{}.md", name.to_lowercase().replace(" ", "_"));
+  let stem = get_page_stem(page.path);
+  let id = stem.rsplit('/').next().unwrap();
+
+  // Index pages are served at the URL of their directory
+  let route = format!("{}{}", ctx.options.nav_prefix, stem);
+  let route = route.strip_suffix("index").unwrap_or(&route).trim_end_matches('/');
+
+  let mut entries = vec![(String::from("id"), toml::Value::from(id)),
+                         (String::from("title"), toml::Value::from(page.title)),
+                         (String::from("slug"), toml::Value::from(format!("/{}", route)))];
+
+  if !page.description.is_empty() {
+    entries.push((String::from("description"), toml::Value::from(page.description)));
+  }
+
+  // The pages are plain Markdown, which might not be valid MDX
+  entries.push((String::from("format"), toml::Value::from("md")));
+
+  return entries;
 }
 
-fn get_class_filename(name: &str) -> String
+/// Returns the title, weight, and description front matter used by Hugo.
+fn get_hugo_front_matter(ctx: &Context, page: &Page) -> FrontMatter
 {
-  return format!("class_{}.md",
-                 name.to_lowercase()
-                     .replace("::", "_")
-                     .replace("<", "_")
-                     .replace(">", "_")
-                     .replace(" ", ""));
+  let mut entries = vec![(String::from("title"), toml::Value::from(page.title))];
+
+  if let Some(weight) = ctx.weights.get(page.path) {
+    entries.push((String::from("weight"), toml::Value::from(*weight as i64)));
+  }
+
+  if !page.description.is_empty() {
+    entries.push((String::from("description"), toml::Value::from(page.description)));
+  }
+
+  return entries;
 }
 
-fn generate_function_comment(writer: &mut BufWriter<&File>, func: &Function) -> EmitResult
+/// Returns front matter with the page title as an alias, so that Obsidian can link to the page
+/// by the name of the documented entity.
+fn get_obsidian_front_matter(page: &Page) -> FrontMatter
 {
-  if !func.docs.brief.is_empty() {
-    for docs in &func.docs.brief {
-      write!(writer, "\n**Brief:** {}\n", docs)?;
+  let aliases = toml::Value::Array(vec![toml::Value::from(page.title)]);
+  return vec![(String::from("aliases"), aliases)];
+}
+
+/// Replaces the page placeholders in the strings of a front matter value from the config file.
+fn expand_front_matter_value(value: &toml::Value, page: &Page) -> toml::Value
+{
+  return match value {
+    toml::Value::String(text) => {
+      toml::Value::from(text.replace("{title}", page.title)
+                            .replace("{description}", page.description)
+                            .replace("{path}", page.path))
+    }
+    toml::Value::Array(items) => {
+      toml::Value::Array(items.iter().map(|item| expand_front_matter_value(item, page)).collect())
+    }
+    _ => value.clone(),
+  };
+}
+
+fn write_yaml_value(writer: &mut PageBuffer, value: &toml::Value) -> EmitResult
+{
+  match value {
+    toml::Value::String(text) => write!(writer, " {}", navigation::quote_yaml(text))?,
+    toml::Value::Array(items) if !items.is_empty() => {
+      for item in items {
+        write!(writer, "\n  -")?;
+        write_yaml_value(writer, item)?;
+      }
+    }
+    // JSON is valid YAML, which handles nested arrays and tables
+    toml::Value::Array(_) | toml::Value::Table(_) => {
+      write!(writer, " {}", serde_json::to_string(value)?)?
     }
+    _ => write!(writer, " {}", value)?,
   }
 
-  if !func.docs.pre_conditions.is_empty() {
-    write!(writer, "\n**Pre-conditions**\n\n")?;
+  Ok(())
+}
 
-    for pre in &func.docs.pre_conditions {
-      write!(writer, "- {}\n", pre)?;
+fn write_front_matter(writer: &mut PageBuffer, entries: &FrontMatter, format: FrontMatterFormat)
+  -> EmitResult
+{
+  match format {
+    FrontMatterFormat::YAML => {
+      write!(writer, "---\n")?;
+      for (key, value) in entries {
+        write!(writer, "{}:", key)?;
+        write_yaml_value(writer, value)?;
+        write!(writer, "\n")?;
+      }
+      write!(writer, "---\n\n")?;
+    }
+    FrontMatterFormat::TOML => {
+      write!(writer, "+++\n")?;
+      for (key, value) in entries {
+        write!(writer, "{} = {}\n", key, value)?;
+      }
+      write!(writer, "+++\n\n")?;
     }
   }
 
-  if !func.docs.post_conditions.is_empty() {
-    write!(writer, "\n**Post-conditions**\n\n")?;
+  Ok(())
+}
+
+fn write_page(ctx: &Context, page: &Page, content: &[u8]) -> EmitResult
+{
+  let path = ctx.output_dir.join(page.path);
+  if let Some(directory) = path.parent() {
+    fs::create_dir_all(directory)?;
+  }
 
-    for post in &func.docs.post_conditions {
-      write!(writer, "- {}\n", post)?;
+  // Docusaurus and Obsidian only support YAML front matter
+  let (mut entries, format) = if ctx.options.docusaurus {
+    (get_docusaurus_front_matter(ctx, page), FrontMatterFormat::YAML)
+  } else if ctx.options.obsidian {
+    (get_obsidian_front_matter(page), FrontMatterFormat::YAML)
+  } else if ctx.options.front_matter == Some(FrontMatterStyle::HUGO) {
+    (get_hugo_front_matter(ctx, page), ctx.options.front_matter_format)
+  } else {
+    (FrontMatter::new(), ctx.options.front_matter_format)
+  };
+
+  // Keys from the config file replace the preset entries with the same keys
+  for (key, value) in &ctx.options.extra_front_matter {
+    let value = expand_front_matter_value(value, page);
+
+    match entries.iter_mut().find(|(existing, _)| existing == key) {
+      Some(entry) => entry.1 = value,
+      None => entries.push((key.to_owned(), value)),
     }
   }
 
-  if !func.docs.details.is_empty() {
-    for details in &func.docs.details {
-      write!(writer, "{}\n", details)?;
+  let mut output = PageBuffer::with_capacity(content.len());
+
+  if !entries.is_empty() {
+    write_front_matter(&mut output, &entries, format)?;
+  }
+
+  let mut content = if ctx.options.autolinks.is_empty() {
+    content.to_vec()
+  } else {
+    AutoLinker::new(&ctx.options.autolinks).apply(content)
+  };
+
+  if !ctx.options.replacements.is_empty() {
+    content = replacements::apply(&ctx.options.replacements, &content);
+  }
+
+  if let Some(header) = render_page_decoration(ctx, page, PAGE_HEADER_TEMPLATE)? {
+    content.splice(0..0, format!("{}\n\n", header).into_bytes());
+  }
+
+  if let Some(footer) = render_page_decoration(ctx, page, PAGE_FOOTER_TEMPLATE)? {
+    while content.last() == Some(&b'\n') {
+      content.pop();
     }
+
+    write!(content, "\n\n{}\n", footer)?;
   }
 
-  if !func.docs.notes.is_empty() {
-    for note in &func.docs.notes {
-      write!(writer, "\n**Note:** {}\n", note)?;
+  if let Some(provenance) = &ctx.provenance {
+    while content.last() == Some(&b'\n') {
+      content.pop();
     }
+
+    write!(content, "\n\n---\n\n{}\n", provenance)?;
   }
 
-  if !func.docs.warnings.is_empty() {
-    for warning in &func.docs.warnings {
-      write!(writer, "\n**Warning:** {}\n", warning)?;
+  output.append(&mut dialect::adjust_page(ctx.options.dialect, &content));
+
+  let source = FileSource {
+    refid: page.id.cloned(),
+    kind: page.kind.to_owned(),
+    title: page.title.to_owned(),
+  };
+  return stats::write_source_file(path, output, Some(source));
+}
+
+fn write_index_page(ctx: &Context, index: Index, content: &[u8]) -> EmitResult
+{
+  let page = Page {
+    path: &ctx.links.get_index(index).page,
+    title: index.title(),
+    description: index.description(),
+    name: index.title(),
+    kind: "index",
+    id: None,
+  };
+
+  return write_page(ctx, &page, content);
+}
+
+/// Writes a class, group, or namespace page, after applying optional post-processing.
+fn write_compound_page(ctx: &Context, compound_id: &RefID, title: &str, content: PageBuffer)
+  -> EmitResult
+{
+  let compound = ctx.registry.compounds.get(compound_id).unwrap();
+
+  let page = Page {
+    path: &ctx.links.get(compound_id).unwrap().page,
+    title,
+    description: compound.docs.brief.first().map_or("", |s| s.as_str()),
+    name: &compound.name,
+    kind: compound.kind.name(),
+    id: Some(compound_id),
+  };
+
+  let content = if ctx.options.toc {
+    insert_table_of_contents(&content, &ctx.links)
+  } else {
+    content
+  };
+  return write_page(ctx, &page, &content);
+}
+
+/// Inserts a list of links to all second and third level headings, right after the page title.
+fn insert_table_of_contents(content: &[u8], links: &LinkResolver) -> PageBuffer
+{
+  let text = String::from_utf8_lossy(content);
+
+  let mut entries = Vec::new();
+  let mut in_code_block = false;
+
+  // Headings with the same anchors are numbered, e.g. overloads
+  let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+  for line in text.lines() {
+    if line.starts_with("```") {
+      in_code_block = !in_code_block;
+    } else if !in_code_block {
+      let (depth, heading) = if let Some(heading) = line.strip_prefix("### ") {
+        (1, heading)
+      } else if let Some(heading) = line.strip_prefix("## ") {
+        (0, heading)
+      } else {
+        continue;
+      };
+
+      let heading = heading.trim_matches('*');
+      let occurrence = occurrences.entry(links.anchor(heading)).or_default();
+      entries.push(format!("{}* {}\n",
+                           "  ".repeat(depth),
+                           links.numbered_local_link(heading, heading, *occurrence)));
+      *occurrence += 1;
     }
   }
 
-  if func.is_member {
-    write!(writer, "\n*This is a {} function.*\n", func.access)?;
+  if entries.is_empty() {
+    return content.to_vec();
   }
 
-  generate_parameter_list(writer, &func.parameter_names, &func.docs)?;
-  generate_template_parameter_docs(writer, &func.docs.template_parameters)?;
+  // The title may be preceded by breadcrumbs
+  let title_end = match text.find("\n# ") {
+    Some(index) => text[index + 1..].find('\n').map_or(text.len(), |i| index + 1 + i),
+    None => text.find('\n').unwrap_or(text.len()),
+  };
+
+  let head = &text[..title_end];
+  let body = text.get(title_end + 1..).unwrap_or("");
 
-  if !func.docs.exceptions.is_empty() {
-    write!(writer, "\n**Exceptions**\n\n")?;
+  let mut result = PageBuffer::with_capacity(content.len());
+  result.extend_from_slice(head.as_bytes());
+  result.extend_from_slice(b"\n\n## Contents\n\n");
+  for entry in &entries {
+    result.extend_from_slice(entry.as_bytes());
+  }
+  result.extend_from_slice(body.as_bytes());
 
-    for (name, desc) in &func.docs.exceptions {
-      write!(writer, "- `{}` {}\n", name, desc)?;
+  return result;
+}
+
+fn get_breadcrumb_label(registry: &Registry, compound_id: &RefID) -> String
+{
+  let compound = registry.compounds.get(compound_id).unwrap();
+
+  return match compound.kind {
+    GROUP => compound.title.to_owned(),
+    _ if !compound.title.is_empty() => compound.title.to_owned(),
+    _ => compound.name.split("::").last().unwrap().to_owned(),
+  };
+}
+
+/// Returns a line of links to the main index and the ancestors of a compound.
+fn get_breadcrumbs(registry: &Registry, links: &LinkResolver, compound_id: &RefID) -> String
+{
+  let page = &links.get(compound_id).unwrap().page;
+  let root = links.get_index(Index::MAIN);
+
+  let mut breadcrumbs = links.format_link(Index::MAIN.title(), &root, page);
+
+  for ancestor_id in links.get_ancestors(compound_id) {
+    let label = get_breadcrumb_label(registry, ancestor_id);
+    match links.link_to(&label, ancestor_id, page) {
+      Some(link) => breadcrumbs += &format!(" / {}", link),
+      None => breadcrumbs += &format!(" / {}", label),
     }
+  }
 
-    write!(writer, "\n")?;
+  breadcrumbs += &format!(" / {}", get_breadcrumb_label(registry, compound_id));
+  return breadcrumbs;
+}
+
+/// Appends the first brief paragraph, if any, to an index entry.
+fn write_brief_suffix(writer: &mut PageBuffer, docs: &Comment) -> EmitResult
+{
+  if let Some(brief) = docs.brief.first().filter(|b| !b.is_empty()) {
+    write!(writer, " — {}", brief)?;
   }
 
-  if !func.docs.returns.is_empty() {
-    write!(writer, "\n**Returns:** {}\n", &func.docs.returns)?;
+  Ok(())
+}
+
+/// Returns the rows of the table of free functions on the main index, sorted by module.
+fn get_functions_by_module(ctx: &Context, page: &str) -> Vec<serde_json::Value>
+{
+  let registry = ctx.registry;
+
+  let mut groups: Vec<(&RefID, &Compound)> =
+    registry.compounds.iter().filter(|(_, compound)| compound.kind == GROUP).collect();
+  groups.sort_by(|(_, a), (_, b)| a.title.cmp(&b.title));
+
+  let mut rows = Vec::new();
+  for (group_id, group) in groups {
+    let module = ctx.links.link_to(&group.title, group_id, page).unwrap();
+
+    for func_id in &group.functions {
+      let func = registry.functions.get(func_id).unwrap();
+      if func.is_member {
+        continue;
+      }
+
+      let signature = get_function_signature_line(func);
+      let brief = func.docs.brief.first().map_or("", |s| s.as_str());
+
+      // The functions are a list instead of a table in dialects without tables
+      rows.push(if ctx.options.dialect.supports_tables() {
+                  json!({
+                    "signature": escape_table_cell(&signature),
+                    "brief": escape_table_cell(brief),
+                    "module": escape_table_cell(&module),
+                  })
+                } else {
+                  json!({ "signature": signature, "brief": brief, "module": module })
+                });
+    }
+  }
+
+  return rows;
+}
+
+fn emit_main_index(ctx: &Context) -> EmitResult
+{
+  info!("Generating main index...");
+
+  let page = ctx.links.get_index(Index::MAIN).page;
+
+  let mut listed = Index::LISTED.to_vec();
+  if ctx.registry.has_deprecated() {
+    listed.push(Index::DEPRECATED);
+  }
+
+  let indexes: Vec<serde_json::Value> =
+    listed.iter()
+          .map(|index| {
+            let link = ctx.links.get_index(*index);
+            json!({
+              "link": ctx.links.format_link(index.title(), &link, &page),
+              "description": index.description(),
+            })
+          })
+          .collect();
+
+  let functions = if ctx.options.functions_by_module {
+    get_functions_by_module(ctx, &page)
+  } else {
+    Vec::new()
+  };
+
+  let mut context = tera::Context::new();
+  context.insert("project", &ctx.options.project_name);
+  context.insert("indexes", &indexes);
+  context.insert("functions", &functions);
+
+  let content = ctx.options.templates.render("index.md", &context)?;
+  write_index_page(ctx, Index::MAIN, content.as_bytes())
+}
+
+/// Returns true if an index with the specified amount of entries should be split by letter.
+fn should_split_index(ctx: &Context, entries: usize) -> bool
+{
+  return ctx.options.split_indexes.is_some_and(|max| entries > max);
+}
+
+/// Emits an index as a landing page that links to one page per initial letter, which in turn
+/// list the entries that start with that letter.
+fn emit_split_index<T>(ctx: &Context,
+                       index: Index,
+                       summary: &str,
+                       entries: &[T],
+                       letter_of: impl Fn(&T) -> char,
+                       write_entry: impl Fn(&mut PageBuffer, &T, &str) -> EmitResult)
+                       -> EmitResult
+{
+  let page = ctx.links.get_index(index).page;
+
+  let mut letters: BTreeMap<char, Vec<&T>> = BTreeMap::new();
+  for entry in entries {
+    letters.entry(letter_of(entry)).or_default().push(entry);
+  }
+
+  let mut writer = PageBuffer::new();
+  write!(writer, "# {}\n", index.title())?;
+  write!(writer, "\n{}\n\n", summary)?;
+
+  for (letter, letter_entries) in &letters {
+    let link = ctx.links.get_index_part(index, *letter);
+    write!(writer,
+           "* {} ({})\n",
+           ctx.links.format_link(&letter.to_string(), &link, &page),
+           letter_entries.len())?;
+  }
+
+  write_index_page(ctx, index, &writer)?;
+
+  for (letter, letter_entries) in &letters {
+    let part_page = ctx.links.get_index_part(index, *letter).page;
+    let title = format!("{}: {}", index.title(), letter);
+
+    let mut writer = PageBuffer::new();
+    write!(writer, "# {}\n\n", title)?;
+    write!(writer,
+           "{}\n\n",
+           ctx.links.format_link(index.title(), &ctx.links.get_index(index), &part_page))?;
+
+    for entry in letter_entries {
+      write_entry(&mut writer, entry, &part_page)?;
+    }
+
+    let part = Page {
+      path: &part_page,
+      title: &title,
+      description: index.description(),
+      name: index.title(),
+      kind: "index",
+      id: None,
+    };
+    write_page(ctx, &part, &writer)?;
   }
 
   Ok(())
 }
 
-fn emit_class_index(output_dir: &PathBuf, registry: &Registry) -> EmitResult
+fn emit_class_index(ctx: &Context) -> EmitResult
 {
-  println!("Generating class index...");
+  let registry = ctx.registry;
+
+  info!("Generating class index...");
+
+  let mut classes: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(_, c)| c.kind == CLASS || c.kind == STRUCT || c.kind == INTERFACE)
+            .collect();
+
+  // Sorted to make the output deterministic, which matters when it is compared with earlier output
+  classes.sort_by_cached_key(|(id, compound)| {
+    (registry.classes[*id].unqualified_name.to_lowercase(), compound.name.clone())
+  });
+
+  if should_split_index(ctx, classes.len()) {
+    let letter_of = |(id, _): &(&RefID, &Compound)| {
+      get_symbol_letter(&registry.classes[*id].unqualified_name)
+    };
+    let write_entry = |writer: &mut PageBuffer, (id, compound): &(&RefID, &Compound), page: &str| {
+      let name = &registry.classes[*id].unqualified_name;
+      write!(writer, "* {}", ctx.links.link_to(name, id, page).unwrap())?;
+      write_brief_suffix(writer, &compound.docs)?;
+      write!(writer, "\n")
+    };
+
+    return emit_split_index(ctx,
+                            Index::CLASSES,
+                            "Here is a list of all classes, by their initial letters.",
+                            &classes,
+                            letter_of,
+                            write_entry);
+  }
 
-  let path = output_dir.join("classes.md");
-  let file = File::create(path)?;
-  let mut writer = BufWriter::new(&file);
+  let page = ctx.links.get_index(Index::CLASSES).page;
+  let mut writer = PageBuffer::new();
 
   write!(writer, "# Classes\n")?;
   write!(writer, "\nHere is a list of all classes.\n\n")?;
 
-  for (compound_id, compound) in &registry.compounds {
-    if compound.kind == CLASS || compound.kind == STRUCT || compound.kind == INTERFACE {
-      let clazz = registry.classes.get(compound_id).unwrap();
-      write!(writer, "* [{}](classes/{})\n",
-             &clazz.unqualified_name,
-             get_class_filename(&compound.name))?;
-    }
+  for (compound_id, compound) in &classes {
+    let clazz = registry.classes.get(*compound_id).unwrap();
+    write!(writer,
+           "* {}",
+           ctx.links.link_to(&clazz.unqualified_name, compound_id, &page).unwrap())?;
+    write_brief_suffix(&mut writer, &compound.docs)?;
+    write!(writer, "\n")?;
   }
 
-  writer.flush()?;
-  Ok(())
+  write_index_page(ctx, Index::CLASSES, &writer)
 }
 
-fn emit_module_index(output_dir: &PathBuf, registry: &Registry) -> EmitResult
+fn emit_module_index(ctx: &Context) -> EmitResult
 {
-  println!("Generating module index...");
+  let registry = ctx.registry;
+
+  info!("Generating module index...");
 
-  let path = output_dir.join("modules.md");
-  let file = File::create(path)?;
-  let mut writer = BufWriter::new(&file);
+  let page = ctx.links.get_index(Index::MODULES).page;
+  let mut writer = PageBuffer::new();
 
   write!(writer, "# Modules\n")?;
   write!(writer, "\nHere is a list of all modules.\n\n")?;
 
-  // TODO emit alphabetically sorted list
-  for (_, compound) in &registry.compounds {
-    // TODO arrange by group relations (subgroups)
-    if compound.kind == GROUP {
-      write!(writer, "* [{}](groups/{})\n", &compound.title, generate_group_filename(&compound.name))?;
+  // Subgroups are listed beneath their parent groups instead of at the top level
+  for (group_id, group, depth) in registry.walk_groups() {
+    write!(writer, "{}* {}",
+           "  ".repeat(depth),
+           ctx.links.link_to(&group.title, group_id, &page).unwrap())?;
+    write_brief_suffix(&mut writer, &group.docs)?;
+    write!(writer, "\n")?;
+  }
+
+  if !ctx.miscellaneous.is_empty() {
+    write!(writer, "\n## Miscellaneous\n")?;
+    write!(writer, "\nThese entities are not featured in any module or namespace.\n\n")?;
+
+    for compound_id in &ctx.miscellaneous {
+      let compound = registry.compounds.get(compound_id).unwrap();
+      write!(writer, "* {}", ctx.links.link_to(&compound.name, compound_id, &page).unwrap())?;
+      write_brief_suffix(&mut writer, &compound.docs)?;
+      write!(writer, "\n")?;
+    }
+  }
+
+  write_index_page(ctx, Index::MODULES, &writer)
+}
+
+fn is_anonymous_namespace(compound: &Compound) -> bool
+{
+  return compound.name.contains('@');
+}
+
+fn emit_namespace_tree_entry(writer: &mut PageBuffer,
+                             ctx: &Context,
+                             page: &str,
+                             namespace_id: &RefID,
+                             depth: usize) -> EmitResult
+{
+  let registry = ctx.registry;
+  let namespace = registry.compounds.get(namespace_id).unwrap();
+  if is_anonymous_namespace(namespace) {
+    return Ok(());
+  }
+
+  write!(writer, "{}* {}",
+         "  ".repeat(depth),
+         ctx.links.link_to(&namespace.name, namespace_id, page).unwrap())?;
+
+  write_brief_suffix(writer, &namespace.docs)?;
+
+  write!(writer, "\n")?;
+
+  let mut children: Vec<&RefID> = namespace.namespaces
+                                           .iter()
+                                           .filter(|id| registry.compounds.contains_key(*id))
+                                           .collect();
+  children.sort_by_key(|id| &registry.compounds.get(*id).unwrap().name);
+
+  for child_id in children {
+    emit_namespace_tree_entry(writer, ctx, page, child_id, depth + 1)?;
+  }
+
+  Ok(())
+}
+
+fn emit_namespace_index(ctx: &Context) -> EmitResult
+{
+  let registry = ctx.registry;
+
+  info!("Generating namespace index...");
+
+  let page = ctx.links.get_index(Index::NAMESPACES).page;
+  let mut writer = PageBuffer::new();
+
+  write!(writer, "# Namespaces\n")?;
+  write!(writer, "\nHere is a list of all namespaces.\n\n")?;
+
+  let nested: HashSet<&RefID> = registry.compounds
+                                        .values()
+                                        .filter(|c| c.kind == NAMESPACE)
+                                        .flat_map(|c| c.namespaces.iter())
+                                        .collect();
+
+  let mut top_level: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(id, c)| c.kind == NAMESPACE && !nested.contains(id))
+            .collect();
+  top_level.sort_by_key(|(_, c)| &c.name);
+
+  for (namespace_id, _) in top_level {
+    emit_namespace_tree_entry(&mut writer, ctx, &page, namespace_id, 0)?;
+  }
+
+  write_index_page(ctx, Index::NAMESPACES, &writer)
+}
+
+fn emit_function_index(ctx: &Context) -> EmitResult
+{
+  let registry = ctx.registry;
+
+  info!("Generating function index...");
+
+  let mut writer = PageBuffer::new();
+
+  write!(writer, "# Functions\n")?;
+  write!(writer, "\nHere is a list of all free functions that are not part of any module.\n")?;
+
+  // Functions in groups are already documented on the corresponding group pages
+  let mut emitted: HashSet<&RefID> = registry.compounds
+                                             .values()
+                                             .filter(|c| c.kind == GROUP)
+                                             .flat_map(|c| c.functions.iter())
+                                             .collect();
+
+  let mut namespaces: Vec<&Compound> = registry.compounds
+                                               .values()
+                                               .filter(|c| c.kind == NAMESPACE)
+                                               .collect();
+  namespaces.sort_by_key(|c| &c.name);
+
+  // Functions in the global namespace are only listed by the file compounds
+  let mut files: Vec<&Compound> =
+    registry.compounds.values().filter(|c| c.kind == FILE).collect();
+  files.sort_by_key(|c| &c.name);
+
+  let sections = namespaces.iter()
+                           .map(|c| (c.name.as_str(), &c.functions))
+                           .chain(files.iter().map(|c| ("Global Namespace", &c.functions)));
+
+  let mut previous_title = "";
+  for (title, functions) in sections {
+    for func_id in functions {
+      let func = match registry.functions.get(func_id) {
+        Some(func) if !func.is_member && !emitted.contains(func_id) => func,
+        _ => continue,
+      };
+
+      if title != previous_title {
+        write!(writer, "\n---")?;
+        write!(writer, "\n## {}\n", title)?;
+        previous_title = title;
+      }
+
+      write!(writer, "\n{}\n", render_member(ctx, func_id, func)?)?;
+      emitted.insert(func_id);
     }
   }
 
-  writer.flush()?;
+  write_index_page(ctx, Index::FUNCTIONS, &writer)
+}
+
+/// Emits the definition of a macro as C++ code.
+pub fn generate_define_signature(writer: &mut PageBuffer, define: &Define) -> EmitResult
+{
+  write!(writer, "#define {}", &define.name)?;
+
+  if define.is_function_like {
+    write!(writer, "({})", define.parameters.join(", "))?;
+  }
+
+  if !define.initializer.is_empty() {
+    write!(writer, " {}", &define.initializer)?;
+  }
+
+  write!(writer, "\n")?;
   Ok(())
 }
 
-fn generate_template_parameter_docs(writer: &mut BufWriter<&File>,
-                                    parameters: &HashMap<String, String>)
-  -> EmitResult
+fn emit_macro_index(ctx: &Context) -> EmitResult
 {
-  if !parameters.is_empty() {
-    write!(writer, "\n**Template Parameters**\n\n")?;
+  let registry = ctx.registry;
 
-    for (name, info) in parameters.iter() {
-      write!(writer, "* `{}` {}\n", name, if info.is_empty() { "N/A" } else { info })?;
+  info!("Generating macro index...");
+
+  let page = ctx.links.get_index(Index::MACROS).page;
+  let mut writer = PageBuffer::new();
+
+  write!(writer, "# Macros\n")?;
+  write!(writer, "\nHere is a list of all documented macros.\n\n")?;
+
+  let mut groups: HashMap<&RefID, &RefID> = HashMap::new();
+  for (group_id, group) in registry.compounds.iter().filter(|(_, c)| c.kind == GROUP) {
+    for define_id in &group.defines {
+      groups.insert(define_id, group_id);
     }
+  }
+
+  let mut defines: Vec<(&RefID, &Define)> = registry.defines
+                                                    .iter()
+                                                    .filter(|(_, d)| d.docs.is_documented())
+                                                    .collect();
+  defines.sort_by_key(|(_, define)| &define.name);
+
+  for (_, define) in &defines {
+    write!(writer, "* {}", ctx.links.local_link(&define.name, &define.name))?;
+
+    write_brief_suffix(&mut writer, &define.docs)?;
 
     write!(writer, "\n")?;
   }
 
-  Ok(())
+  for (define_id, define) in &defines {
+    write!(writer, "\n---")?;
+    write!(writer, "\n### {}\n", &define.name)?;
+
+    write!(writer, "\n```C++\n")?;
+    generate_define_signature(&mut writer, define)?;
+    write!(writer, "```\n")?;
+
+    for brief in &define.docs.brief {
+      write!(writer, "\n{}\n", brief)?;
+    }
+
+    for details in &define.docs.details {
+      write!(writer, "\n{}\n", details)?;
+    }
+
+    if let Some(group_id) = groups.get(define_id) {
+      let title = &registry.compounds.get(*group_id).unwrap().title;
+      write!(writer,
+             "\n*Defined in module {}.*\n",
+             ctx.links.link_to(title, group_id, &page).unwrap())?;
+    }
+  }
+
+  write_index_page(ctx, Index::MACROS, &writer)
 }
 
-fn generate_parameter_list(writer: &mut BufWriter<&File>,
-                           parameters: &Vec<String>,
-                           docs: &Comment)
-  -> EmitResult
+fn emit_enum_index(ctx: &Context) -> EmitResult
+{
+  let registry = ctx.registry;
+  let links = &ctx.links;
+
+  info!("Generating enum index...");
+
+  let page = links.get_index(Index::ENUMS).page;
+  let mut writer = PageBuffer::new();
+
+  write!(writer, "# Enums\n")?;
+  write!(writer, "\nHere is a list of all enums.\n\n")?;
+
+  let mut enums: Vec<(&RefID, &Enum)> = registry.enums
+                                                .iter()
+                                                .filter(|(_, e)| !e.name.contains('@'))
+                                                .collect();
+  enums.sort_by_key(|(_, e)| &e.qualified_name);
+
+  for (enum_id, enumeration) in &enums {
+    write!(writer, "* {}", links.link_to(&enumeration.qualified_name, enum_id, &page).unwrap())?;
+
+    if enumeration.is_scoped {
+      write!(writer, " (enum class)")?;
+    }
+
+    write_brief_suffix(&mut writer, &enumeration.docs)?;
+
+    write!(writer, "\n")?;
+  }
+
+  // Enums outside of groups are defined on this page
+  for (enum_id, enumeration) in &enums {
+    if links.get(enum_id).unwrap().page == page {
+      write!(writer, "\n---")?;
+      write!(writer, "\n## {}\n", &enumeration.qualified_name)?;
+
+      write!(writer, "\n```C++\n")?;
+      generate_enum_declaration(&mut writer, enumeration)?;
+      write!(writer, "```\n")?;
+    }
+  }
+
+  write_index_page(ctx, Index::ENUMS, &writer)
+}
+
+/// Represents an entry in the symbol index.
+struct Symbol<'a>
+{
+  id: &'a RefID,
+  name: &'a str,
+  qualified_name: &'a str,
+  kind: &'static str,
+  docs: &'a Comment,
+}
+
+fn get_symbol_letter(name: &str) -> char
+{
+  return match name.chars().next() {
+    Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+    _ => '#',
+  };
+}
+
+/// Collects the classes, functions, enums, macros, and variables, sorted by their names.
+fn collect_symbols(registry: &Registry) -> Vec<Symbol<'_>>
+{
+  let mut symbols = Vec::new();
+
+  for (id, compound) in &registry.compounds {
+    if let Some(class) = registry.classes.get(id) {
+      symbols.push(Symbol {
+        id,
+        name: &class.unqualified_name,
+        qualified_name: &compound.name,
+        kind: if class.is_struct { "struct" } else { "class" },
+        docs: &compound.docs,
+      });
+    }
+  }
+
+  for (id, func) in &registry.functions {
+    symbols.push(Symbol {
+      id,
+      name: &func.name,
+      qualified_name: &func.qualified_name,
+      kind: "function",
+      docs: &func.docs,
+    });
+  }
+
+  for (id, enumeration) in registry.enums.iter().filter(|(_, e)| !e.name.contains('@')) {
+    symbols.push(Symbol {
+      id,
+      name: &enumeration.name,
+      qualified_name: &enumeration.qualified_name,
+      kind: "enum",
+      docs: &enumeration.docs,
+    });
+  }
+
+  for (id, define) in &registry.defines {
+    symbols.push(Symbol {
+      id,
+      name: &define.name,
+      qualified_name: &define.name,
+      kind: "macro",
+      docs: &define.docs,
+    });
+  }
+
+  for (id, variable) in &registry.variables {
+    symbols.push(Symbol {
+      id,
+      name: &variable.name,
+      qualified_name: &variable.qualified_name,
+      kind: "variable",
+      docs: &variable.docs,
+    });
+  }
+
+  symbols.sort_by(|a, b| {
+    a.name.to_lowercase()
+     .cmp(&b.name.to_lowercase())
+     .then_with(|| a.qualified_name.cmp(b.qualified_name))
+  });
+
+  return symbols;
+}
+
+fn emit_symbol_index(ctx: &Context) -> EmitResult
 {
-  if !parameters.is_empty() {
-    write!(writer, "\n**Parameters**\n\n")?;
+  let registry = ctx.registry;
+  let links = &ctx.links;
 
-    for name in parameters {
-      write!(writer, "- `{}`", name)?;
+  info!("Generating symbol index...");
 
-      match docs.parameters.get(name) {
-        Some(desc) => write!(writer, " {}\n", desc)?,
-        None => write!(writer, " N/A\n")?
+  let page = links.get_index(Index::SYMBOLS).page;
+  let mut writer = PageBuffer::new();
+
+  let symbols = collect_symbols(registry);
+
+  if should_split_index(ctx, symbols.len()) {
+    let write_entry = |writer: &mut PageBuffer, symbol: &Symbol, page: &str| {
+      match links.link_to(symbol.name, symbol.id, page) {
+        Some(link) => write!(writer, "* {}", link)?,
+        None => write!(writer, "* {}", symbol.name)?,
       }
+      write!(writer, " — {} `{}`\n", symbol.kind, symbol.qualified_name)
+    };
+
+    return emit_split_index(ctx,
+                            Index::SYMBOLS,
+                            "Here is a list of all classes, functions, enums, macros, and \
+                             variables, by their initial letters.",
+                            &symbols,
+                            |symbol| get_symbol_letter(symbol.name),
+                            write_entry);
+  }
+
+  let mut letters: Vec<char> = symbols.iter().map(|s| get_symbol_letter(s.name)).collect();
+  letters.dedup();
+
+  write!(writer, "# Symbols\n")?;
+  write!(writer, "\nHere is a list of all classes, functions, enums, macros, and variables.\n\n")?;
+
+  let jump_links: Vec<String> =
+    letters.iter()
+           .map(|letter| links.local_link(&letter.to_string(), &letter.to_string()))
+           .collect();
+  write!(writer, "{}\n", jump_links.join(" | "))?;
+
+  let mut previous_letter = None;
+  for symbol in &symbols {
+    let letter = get_symbol_letter(symbol.name);
+
+    if previous_letter != Some(letter) {
+      write!(writer, "\n## {}\n\n", letter)?;
+      previous_letter = Some(letter);
     }
+
+    match links.link_to(symbol.name, symbol.id, &page) {
+      Some(link) => write!(writer, "* {}", link)?,
+      None => write!(writer, "* {}", symbol.name)?,
+    }
+
+    write!(writer, " — {} `{}`\n", symbol.kind, symbol.qualified_name)?;
   }
 
-  Ok(())
+  write_index_page(ctx, Index::SYMBOLS, &writer)
 }
 
-fn generate_function_signature(writer: &mut BufWriter<&File>, func: &Function)
+/// Emits a page that lists all deprecated entities along with their deprecation notes, if there
+/// are any.
+fn emit_deprecated_index(ctx: &Context) -> EmitResult
+{
+  let links = &ctx.links;
+
+  let symbols: Vec<Symbol> = collect_symbols(ctx.registry).into_iter()
+                                                          .filter(|s| s.docs.deprecated.is_some())
+                                                          .collect();
+  if symbols.is_empty() {
+    return Ok(());
+  }
+
+  info!("Generating deprecated index...");
+
+  let page = links.get_index(Index::DEPRECATED).page;
+  let mut writer = PageBuffer::new();
+
+  write!(writer, "# Deprecated\n")?;
+  write!(writer, "\nThese entities are deprecated, and may be removed in a future version.\n\n")?;
+
+  for symbol in &symbols {
+    match links.link_to(symbol.qualified_name, symbol.id, &page) {
+      Some(link) => write!(writer, "* {}", link)?,
+      None => write!(writer, "* {}", symbol.qualified_name)?,
+    }
+
+    write!(writer, " — {}", symbol.kind)?;
+
+    let note = symbol.docs.deprecated.as_deref().unwrap_or_default();
+    if !note.is_empty() {
+      write!(writer, ": {}", note)?;
+    }
+
+    write!(writer, "\n")?;
+  }
+
+  write_index_page(ctx, Index::DEPRECATED, &writer)
+}
+
+/// Emits a JSON map of every rendered entity to the page and anchor that it is rendered at, called
+/// symbols.json, so that other documentation can link to the generated pages.
+fn emit_symbol_map(ctx: &Context) -> EmitResult
+{
+  let registry = ctx.registry;
+  let links = &ctx.links;
+
+  info!("Generating symbol map...");
+
+  let mut symbols = collect_symbols(registry);
+
+  for (id, compound) in &registry.compounds {
+    let kind = match compound.kind {
+      GROUP => "group",
+      NAMESPACE => "namespace",
+      _ => continue,
+    };
+
+    symbols.push(Symbol {
+      id,
+      name: &compound.name,
+      qualified_name: &compound.name,
+      kind,
+      docs: &compound.docs,
+    });
+  }
+
+  symbols.sort_by(|a, b| a.qualified_name.cmp(b.qualified_name).then_with(|| a.id.cmp(b.id)));
+
+  let entries: Vec<serde_json::Value> =
+    symbols.iter()
+           .filter_map(|symbol| links.get(symbol.id).map(|link| (symbol, link)))
+           .map(|(symbol, link)| {
+             let mut entry = json!({
+               "id": symbol.id,
+               "name": symbol.qualified_name,
+               "kind": symbol.kind,
+               "page": link.page,
+             });
+
+             if let Some(anchor) = links.link_anchor(link) {
+               entry["anchor"] = json!(anchor);
+             }
+
+             entry
+           })
+           .collect();
+
+  let output = serde_json::to_string_pretty(&json!({ "symbols": entries }))?;
+  return stats::write_file(ctx.output_dir.join("symbols.json"), output + "\n");
+}
+
+/// Emits the declaration of a function as C++ code.
+pub fn generate_function_signature(writer: &mut PageBuffer, func: &Function)
   -> EmitResult
 {
   if !func.template_args.is_empty() {
@@ -188,60 +1349,215 @@ fn generate_function_signature(writer: &mut BufWriter<&File>, func: &Function)
     write!(writer, ">\n")?;
   }
 
-  write!(writer, "{}{}{}{}{}{};\n",
-         if func.is_static { "static " } else { "" },
-         if func.is_explicit { "explicit " } else { "" },
-         &func.return_type,
-         if func.return_type.is_empty() { "" } else { " " },
-         &func.name,
-         &func.args)?;
+  write!(writer, "{};\n", signature::format_function_signature(func))?;
 
   Ok(())
 }
 
-fn generate_function_definition(writer: &mut BufWriter<&File>, func: &Function)
-  -> EmitResult
+/// Renders the code emitted by a function, e.g. a declaration, without trailing whitespace.
+pub fn render_code(render: impl FnOnce(&mut PageBuffer) -> EmitResult) -> io::Result<String>
+{
+  let mut code = PageBuffer::new();
+  render(&mut code)?;
+
+  return Ok(String::from_utf8_lossy(&code).trim_end().to_owned());
+}
+
+/// Converts documented names, e.g. parameters, to template entries with "N/A" for missing docs.
+fn to_template_entries<'a, T: AsRef<str> + 'a>(names: impl Iterator<Item = &'a T>,
+                                               docs: &HashMap<String, String>)
+  -> Vec<serde_json::Value>
+{
+  return names.map(|name| {
+                let description = docs.get(name.as_ref()).map_or("", |s| s.as_str());
+                json!({
+                  "name": name.as_ref(),
+                  "description": if description.is_empty() { "N/A" } else { description },
+                })
+              })
+              .collect();
+}
+
+/// Returns the specifiers of a function that are shown as badges, e.g. "public" and "static".
+fn get_badges(func: &Function) -> Vec<String>
 {
-  write!(writer, "\n### **{}**\n", &func.qualified_name)?;
+  let mut badges = Vec::new();
+
+  if func.is_member {
+    badges.push(func.access.to_string());
+  }
 
-  write!(writer, "\n```C++\n")?;
-  generate_function_signature(writer, func)?;
-  write!(writer, "```\n")?;
+  if func.is_static {
+    badges.push(String::from("static"));
+  }
+
+  if func.is_virtual {
+    badges.push(String::from("virtual"));
+  }
 
-  generate_function_comment(writer, &func)?;
+  return badges;
+}
 
-  if !func.docs.see_also.is_empty() {
-    write!(writer, "\n**See Also**\n\n")?;
-    for see in &func.docs.see_also {
-      write!(writer, "* {}\n", see)?;
+/// Returns the examples of an entity, including the code of the example files it references.
+fn get_examples(registry: &Registry, docs: &Comment) -> Vec<String>
+{
+  let mut examples = docs.examples.clone();
+
+  for example_id in &docs.example_refs {
+    if let Some(example) = registry.compounds.get(example_id) {
+      let contents: Vec<&str> = example.docs
+                                       .brief
+                                       .iter()
+                                       .chain(&example.docs.details)
+                                       .map(|par| par.trim())
+                                       .filter(|par| !par.is_empty())
+                                       .collect();
+      if !contents.is_empty() {
+        examples.push(format!("From `{}`:\n\n{}", example.name, contents.join("\n\n")));
+      }
     }
   }
 
-  Ok(())
+  return examples;
 }
 
-fn generate_class_file(destination: &PathBuf,
-                       registry: &Registry,
-                       compound_id: &RefID,
-                       compound: &Compound) -> EmitResult
+/// Returns the heading of the section of a function, which is its qualified name unless the
+/// script changes it.
+fn get_member_heading<'a>(ctx: &'a Context, func_id: &RefID, func: &'a Function) -> &'a str
 {
-  // println!("Generating file {}", destination.display());
+  return ctx.headings.get(func_id).map_or(func.qualified_name.as_str(), String::as_str);
+}
 
-  let class = registry.classes.get(compound_id).unwrap();
+/// Returns the Markdown that the script appends to the page of a compound, if any.
+fn get_extra_sections(ctx: &Context, compound_id: &RefID, compound: &Compound)
+  -> io::Result<String>
+{
+  return match &ctx.options.script {
+    Some(script) => script.get_extra_sections(compound_id, compound),
+    None => Ok(String::new()),
+  };
+}
 
-  let file = File::create(destination)?;
-  let mut writer = BufWriter::new(&file);
-  write!(writer, "# {}\n", &compound.name)?;
+/// Renders the documentation of a function with the "member.md" template.
+fn render_member(ctx: &Context, func_id: &RefID, func: &Function) -> io::Result<String>
+{
+  trace!("Rendering function {}", func.name);
+  let minimal = ctx.options.minimal;
+
+  // Minimal pages only show the signatures, briefs, and deprecation notes of members
+  let brief_only = Comment {
+    brief: func.docs.brief.clone(),
+    deprecated: func.docs.deprecated.clone(),
+    ..Comment::new()
+  };
+  let docs = if minimal { &brief_only } else { &func.docs };
+
+  let mut context = tera::Context::new();
+  context.insert("qualified_name", &func.qualified_name);
+  context.insert("heading", get_member_heading(ctx, func_id, func));
+  context.insert("signature", &render_code(|code| generate_function_signature(code, func))?);
+  context.insert("types", &get_type_links(ctx, func_id, func));
+  context.insert("brief", &docs.brief);
+  context.insert("details", &docs.details);
+  context.insert("pre_conditions", &docs.pre_conditions);
+  context.insert("post_conditions", &docs.post_conditions);
+  context.insert("invariants", &docs.invariants);
+  context.insert("notes", &docs.notes);
+  context.insert("warnings", &docs.warnings);
+  context.insert("attentions", &docs.attentions);
+  context.insert("badges", &if ctx.options.badges { get_badges(func) } else { Vec::new() });
+  context.insert("deprecated", &docs.deprecated);
+  context.insert("access",
+                 &if func.is_member && !minimal { func.access.to_string() } else { String::new() });
+  context.insert("parameters",
+                 &if minimal {
+                   Vec::new()
+                 } else {
+                   to_template_entries(func.parameter_names.iter(), &docs.parameters)
+                 });
+  context.insert("template_parameters",
+                 &to_template_entries(docs.template_parameters.keys(), &docs.template_parameters));
+  context.insert("exceptions", &to_template_entries(docs.exceptions.keys(), &docs.exceptions));
+  context.insert("returns", &docs.returns);
+  context.insert("examples", &get_examples(ctx.registry, docs));
+  context.insert("see_also", &docs.see_also);
+
+  let member = ctx.options.templates.render("member.md", &context)?;
+  return Ok(member.trim_end().to_owned());
+}
 
-  for par in &compound.docs.brief {
-    write!(writer, "\n{}\n", par)?;
+/// Returns links to the documented entities that are referenced by the return, parameter, and
+/// template parameter types of a function, e.g. classes and enums.
+fn get_type_links(ctx: &Context, func_id: &RefID, func: &Function) -> Vec<String>
+{
+  let page = match ctx.links.get(func_id) {
+    Some(link) if ctx.options.type_links => &link.page,
+    _ => return Vec::new(),
+  };
+
+  let mut linked = HashSet::new();
+  let mut links = Vec::new();
+
+  for (name, id) in func.type_refs.iter().filter(|(_, id)| linked.insert(id)) {
+    // Types of the page itself, e.g. the class of a member function, aren't linked
+    if ctx.links.get(id).is_some_and(|link| link.page != *page) {
+      links.push(ctx.links.link_to(name, id, page).unwrap());
+    }
   }
 
-  if !compound.docs.details.is_empty() {
-    write!(writer, "\n[More...](#detailed-description)\n")?;
-  }
+  return links;
+}
 
-  write!(writer, "\n```C++\n")?;
+/// Returns a function signature on a single line, suitable for use in tables.
+fn get_function_signature_line(func: &Function) -> String
+{
+  let signature = format!("{}{}{}{}{}{}",
+                          if func.is_static { "static " } else { "" },
+                          if func.is_explicit { "explicit " } else { "" },
+                          &func.return_type,
+                          if func.return_type.is_empty() { "" } else { " " },
+                          &func.name,
+                          &func.args);
+  return signature.split_whitespace().collect::<Vec<&str>>().join(" ");
+}
+
+fn escape_table_cell(text: &str) -> String
+{
+  return text.replace('|', "\\|").replace('\n', " ");
+}
+
+/// Returns the rows of the summary table of the public member functions of a class, where the
+/// cells are escaped if the dialect supports tables.
+fn get_member_summary(ctx: &Context, compound: &Compound) -> Vec<serde_json::Value>
+{
+  return compound.functions
+                 .iter()
+                 .map(|id| (id, ctx.registry.functions.get(id).unwrap()))
+                 .filter(|(_, f)| f.access == AccessModifier::PUBLIC && !f.is_related)
+                 .map(|(id, func)| {
+                   let occurrence = ctx.links.get(id).map_or(0, |link| link.occurrence);
+                   let heading = get_member_heading(ctx, id, func);
+                   let link = ctx.links.numbered_local_link(&func.name, heading, occurrence);
+                   let brief = func.docs.brief.first().map_or("", |s| s.as_str());
+                   let signature = get_function_signature_line(func);
+
+                   // The summary is a list instead of a table in dialects without tables
+                   if ctx.options.dialect.supports_tables() {
+                     json!({
+                       "link": escape_table_cell(&link),
+                       "signature": escape_table_cell(&signature),
+                       "brief": escape_table_cell(brief),
+                     })
+                   } else {
+                     json!({ "link": link, "signature": signature, "brief": brief })
+                   }
+                 })
+                 .collect();
+}
+
+/// Emits the declaration of a class as C++ code.
+pub fn generate_class_declaration(writer: &mut PageBuffer, class: &Class) -> EmitResult
+{
   if !class.template_args.is_empty() {
     write!(writer, "template <")?;
     for arg in &class.template_args {
@@ -249,74 +1565,75 @@ fn generate_class_file(destination: &PathBuf,
     }
     write!(writer, ">\n")?;
   }
+
   write!(writer,
          "{} {};\n",
          if class.is_struct { "struct" } else { "class" },
          &class.unqualified_name)?;
-  write!(writer, "```\n")?;
-
-  // TODO typedefs
-
-  if !compound.functions.is_empty() {
-    write!(writer, "\n## API\n")?;
 
-    let count = compound.functions.len();
-    let mut index: usize = 0;
-
-    write!(writer, "\n```C++\n")?;
-    for func_id in &compound.functions {
-      let func = registry.functions.get(func_id).unwrap();
-      generate_function_signature(&mut writer, func)?;
-
-      index += 1;
-      if index != count {
-        write!(writer, "\n")?;
-      }
-    }
-    write!(writer, "```\n")?;
-  }
-
-  if !compound.docs.details.is_empty() {
-    write!(writer, "\n## Detailed Description\n")?;
-    for par in &compound.docs.details {
-      write!(writer, "\n{}\n", par)?;
-    }
-  }
+  Ok(())
+}
 
-  if !compound.docs.notes.is_empty() {
-    write!(writer, "\n")?;
-    for note in &compound.docs.notes {
-      write!(writer, "**Note**: {}\n", note)?;
-    }
-  }
+fn generate_class_file(ctx: &Context,
+                       compound_id: &RefID,
+                       compound: &Compound) -> EmitResult
+{
+  let registry = ctx.registry;
 
-  if !compound.docs.see_also.is_empty() {
-    write!(writer, "\n**See Also**\n")?;
-    for see in &compound.docs.see_also {
-      write!(writer, "- {}\n", see)?;
-    }
-  }
+  let class = registry.classes.get(compound_id).unwrap();
 
-  if !compound.functions.is_empty() {
-    write!(writer, "\n## Members\n")?;
+  // TODO typedefs
 
-    for func_id in &compound.functions {
-      let func = registry.functions.get(func_id).unwrap();
-      generate_function_definition(&mut writer, &func)?;
+  let mut signatures = Vec::new();
+  let mut members = Vec::new();
+  let mut static_members = Vec::new();
+  let mut related = Vec::new();
+
+  for func_id in &compound.functions {
+    let func = registry.functions.get(func_id).unwrap();
+
+    if func.is_related {
+      related.push(render_member(ctx, func_id, func)?);
+    } else if func.is_static {
+      signatures.push(render_code(|code| generate_function_signature(code, func))?);
+      static_members.push(render_member(ctx, func_id, func)?);
+    } else {
+      signatures.push(render_code(|code| generate_function_signature(code, func))?);
+      members.push(render_member(ctx, func_id, func)?);
     }
   }
 
-  writer.flush()?;
-  Ok(())
+  // Minimal pages leave out the detailed description and the member summary
+  let brief_only = Comment { brief: compound.docs.brief.clone(), ..Comment::new() };
+  let docs = if ctx.options.minimal { &brief_only } else { &compound.docs };
+  let summary = if ctx.options.minimal { Vec::new() } else { get_member_summary(ctx, compound) };
+
+  let mut context = tera::Context::new();
+  context.insert("breadcrumbs", &get_breadcrumbs(registry, &ctx.links, compound_id));
+  context.insert("name", &compound.name);
+  context.insert("title", backend::get_title(compound));
+  context.insert("deprecated", &compound.docs.deprecated);
+  context.insert("brief", &docs.brief);
+  context.insert("details", &docs.details);
+  context.insert("more_link", &ctx.links.local_link("More...", "Detailed Description"));
+  context.insert("declaration", &render_code(|code| generate_class_declaration(code, class))?);
+  context.insert("signatures", &signatures);
+  context.insert("notes", &docs.notes);
+  context.insert("see_also", &docs.see_also);
+  context.insert("summary", &summary);
+  context.insert("members", &members);
+  context.insert("static_members", &static_members);
+  context.insert("related", &related);
+  context.insert("extra_sections", &get_extra_sections(ctx, compound_id, compound)?);
+  context.insert("navigation", &get_page_navigation(ctx, compound_id));
+
+  let content = ctx.options.templates.render("class.md", &context)?;
+  write_compound_page(ctx, compound_id, backend::get_title(compound), content.into_bytes())
 }
 
-fn generate_enum_definition(writer: &mut BufWriter<&File>,
-                            enumeration: &Enum)
-  -> EmitResult
+/// Emits the definition of an enum, including its enumerators, as C++ code.
+pub fn generate_enum_declaration(writer: &mut PageBuffer, enumeration: &Enum) -> EmitResult
 {
-  write!(writer, "\n## {}\n", &enumeration.qualified_name)?;
-
-  write!(writer, "\n```C++\n")?;
   write!(writer, "enum{} {} \n{{\n",
          if enumeration.is_scoped { " class" } else { "" },
          &enumeration.name)?;
@@ -332,147 +1649,525 @@ fn generate_enum_definition(writer: &mut BufWriter<&File>,
   }
 
   write!(writer, "}};\n")?;
-  write!(writer, "```\n")?;
+  Ok(())
+}
 
-  // write!(writer, "\n| Enumerator | Description |\n")?;
-  // write!(writer, "|-----------:|:------------|\n")?;
-  // for value in &enumeration.values {
-  //   if !value.docs.brief.is_empty() {
-  //     write!(writer, "|`{}`|{}|\n", &value.name, &value.docs.brief.join(" "))?;
-  //   }
-  // }
+fn get_variable_badges(variable: &Variable) -> Vec<String>
+{
+  let mut badges = Vec::new();
 
-  Ok(())
+  if variable.is_static {
+    badges.push(String::from("static"));
+  }
+
+  if variable.is_constexpr {
+    badges.push(String::from("constexpr"));
+  }
+
+  return badges;
 }
 
-fn generate_group_file(destination: &PathBuf,
-                       registry: &Registry,
-                       compound: &Compound) -> EmitResult
+/// Returns the template entry of a variable on a group page.
+fn render_variable(ctx: &Context, variable: &Variable) -> serde_json::Value
 {
-  println!("Generating file {}", destination.display());
+  let docs = &variable.docs;
+  let minimal = ctx.options.minimal;
+
+  let badges = if ctx.options.badges { get_variable_badges(variable) } else { Vec::new() };
+  let empty = Vec::new();
+
+  return json!({
+    "qualified_name": &variable.qualified_name,
+    "brief": &docs.brief,
+    "details": if minimal { &empty } else { &docs.details },
+    "notes": if minimal { &empty } else { &docs.notes },
+    "warnings": if minimal { &empty } else { &docs.warnings },
+    "deprecated": &docs.deprecated,
+    "badges": badges,
+    "definition": &variable.definition,
+    "initializer": &variable.initializer,
+  });
+}
+
+/// Returns the template entry of a macro on a group page.
+fn render_define(ctx: &Context, define: &Define) -> io::Result<serde_json::Value>
+{
+  let docs = &define.docs;
+  let minimal = ctx.options.minimal;
+
+  let parameters = if minimal {
+    Vec::new()
+  } else {
+    to_template_entries(define.parameters.iter(), &docs.parameters)
+  };
+  let empty = Vec::new();
+
+  return Ok(json!({
+    "name": &define.name,
+    "signature": render_code(|code| generate_define_signature(code, define))?.trim_end(),
+    "brief": &docs.brief,
+    "details": if minimal { &empty } else { &docs.details },
+    "parameters": parameters,
+  }));
+}
 
-  let file = File::create(destination)?;
-  let mut writer = BufWriter::new(&file);
+/// Returns the rows of the enumerator table of an enum, which is empty if no enumerator is
+/// documented.
+fn get_enumerators(ctx: &Context, enumeration: &Enum) -> Vec<serde_json::Value>
+{
+  let is_documented =
+    |value: &EnumValue| !value.docs.brief.is_empty() || !value.docs.details.is_empty();
+  if !enumeration.values.iter().any(is_documented) {
+    return Vec::new();
+  }
 
-  write!(writer, "# {}\n", &compound.title)?;
+  return enumeration.values
+                    .iter()
+                    .map(|value| {
+                      let paragraphs: Vec<&str> = value.docs
+                                                       .brief
+                                                       .iter()
+                                                       .chain(&value.docs.details)
+                                                       .map(|par| par.trim())
+                                                       .filter(|par| !par.is_empty())
+                                                       .collect();
+                      let description = paragraphs.join(" ");
+
+                      // The enumerators are a list instead of a table in dialects without tables
+                      if ctx.options.dialect.supports_tables() {
+                        json!({
+                          "name": escape_table_cell(&value.name),
+                          "value": escape_table_cell(&value.initializer),
+                          "description": escape_table_cell(&description),
+                        })
+                      } else {
+                        json!({
+                          "name": value.name,
+                          "value": value.initializer,
+                          "description": description,
+                        })
+                      }
+                    })
+                    .collect();
+}
 
-  for par in &compound.docs.brief {
-    write!(writer, "\n{}\n", &par)?;
+fn generate_group_file(ctx: &Context,
+                       compound_id: &RefID,
+                       compound: &Compound) -> EmitResult
+{
+  let registry = ctx.registry;
+
+  let page = &ctx.links.get(compound_id).unwrap().page;
+
+  let groups: Vec<&String> = compound.groups
+                                     .iter()
+                                     .map(|id| &registry.compounds.get(id).unwrap().title)
+                                     .collect();
+
+  let classes: Vec<String> =
+    compound.classes
+            .iter()
+            .map(|class_id| {
+              let class = registry.classes.get(class_id).unwrap();
+              let text = format!("{} {}",
+                                 if class.is_struct { "struct" } else { "class" },
+                                 &class.unqualified_name);
+              ctx.links.link_to(&text, class_id, page).unwrap()
+            })
+            .collect();
+
+  let mut enums = Vec::new();
+  for enum_id in &compound.enums {
+    let enumeration = registry.enums.get(enum_id).unwrap();
+    enums.push(json!({
+      "qualified_name": &enumeration.qualified_name,
+      "declaration": render_code(|code| generate_enum_declaration(code, enumeration))?,
+      "enumerators": get_enumerators(ctx, enumeration),
+    }));
   }
 
-  for par in &compound.docs.details {
-    write!(writer, "\n{}\n", &par)?;
+  let mut functions = Vec::new();
+  for func_id in &compound.functions {
+    let func = registry.functions.get(func_id).unwrap();
+    if !func.is_member {
+      functions.push(render_member(ctx, func_id, func)?);
+    }
   }
 
-  if !compound.groups.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Groups\n\n")?;
+  let variables: Vec<serde_json::Value> =
+    compound.variables
+            .iter()
+            .map(|id| render_variable(ctx, registry.variables.get(id).unwrap()))
+            .collect();
 
-    for group_id in &compound.groups {
-      let group = registry.compounds.get(group_id).unwrap();
-      write!(writer, "- {}\n", &group.title)?;
-    }
+  let mut macros = Vec::new();
+  for define_id in &compound.defines {
+    macros.push(render_define(ctx, registry.defines.get(define_id).unwrap())?);
   }
 
-  if !compound.classes.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Classes\n\n")?;
+  let mut context = tera::Context::new();
+  context.insert("breadcrumbs", &get_breadcrumbs(registry, &ctx.links, compound_id));
+  context.insert("title", &compound.title);
+  context.insert("brief", &compound.docs.brief);
+  context.insert("details", &if ctx.options.minimal { &[] } else { &compound.docs.details[..] });
+  context.insert("groups", &groups);
+  context.insert("classes", &classes);
+  context.insert("enums", &enums);
+  context.insert("functions", &functions);
+  context.insert("variables", &variables);
+  context.insert("macros", &macros);
+  context.insert("extra_sections", &get_extra_sections(ctx, compound_id, compound)?);
+  context.insert("navigation", &get_page_navigation(ctx, compound_id));
+
+  let content = ctx.options.templates.render("group.md", &context)?;
+  write_compound_page(ctx, compound_id, &compound.title, content.into_bytes())
+}
 
-    for class_id in &compound.classes {
-      let class = registry.classes.get(class_id).unwrap();
-      let class_compound = registry.compounds.get(class_id).unwrap();
-      let filename = get_class_filename(&class_compound.name);
-      write!(writer,
-             "- [{} {}](../classes/{})\n",
-             if class.is_struct { "struct" } else { "class" },
-             &class.unqualified_name,
-             &filename)?;
-    }
+fn generate_namespace_file(ctx: &Context,
+                           compound_id: &RefID,
+                           compound: &Compound) -> EmitResult
+{
+  let registry = ctx.registry;
+
+  let page = &ctx.links.get(compound_id).unwrap().page;
+
+  let namespaces: Vec<String> =
+    compound.namespaces
+            .iter()
+            .filter_map(|id| registry.compounds.get(id).map(|c| (id, c)))
+            .filter(|(_, c)| !is_anonymous_namespace(c))
+            .map(|(id, namespace)| ctx.links.link_to(&namespace.name, id, page).unwrap())
+            .collect();
+
+  let classes: Vec<String> =
+    compound.classes
+            .iter()
+            .filter_map(|class_id| {
+              let class = registry.classes.get(class_id)?;
+              let text = format!("{} {}",
+                                 if class.is_struct { "struct" } else { "class" },
+                                 &class.unqualified_name);
+              ctx.links.link_to(&text, class_id, page)
+            })
+            .collect();
+
+  let mut context = tera::Context::new();
+  context.insert("breadcrumbs", &get_breadcrumbs(registry, &ctx.links, compound_id));
+  context.insert("name", &compound.name);
+  context.insert("title", backend::get_title(compound));
+  context.insert("brief", &compound.docs.brief);
+  context.insert("details", &if ctx.options.minimal { &[] } else { &compound.docs.details[..] });
+  context.insert("namespaces", &namespaces);
+  context.insert("classes", &classes);
+  context.insert("extra_sections", &get_extra_sections(ctx, compound_id, compound)?);
+  context.insert("navigation", &get_page_navigation(ctx, compound_id));
+
+  let content = ctx.options.templates.render("namespace.md", &context)?;
+  write_compound_page(ctx, compound_id, backend::get_title(compound), content.into_bytes())
+}
+
+fn sort_by_name<'a>(registry: &'a Registry, ids: &mut [&'a RefID])
+{
+  ids.sort_by_cached_key(|id| get_breadcrumb_label(registry, id).to_lowercase());
+}
+
+/// Appends a compound and its descendants to a list, in depth-first order.
+fn collect_tree_order<'a>(registry: &'a Registry,
+                          id: &'a RefID,
+                          get_children: fn(&Compound) -> &Vec<RefID>,
+                          result: &mut Vec<&'a RefID>)
+{
+  if result.contains(&id) {
+    return;  // Guards against malformed input with cyclic relations
   }
 
-  if !compound.enums.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Enums\n")?;
-    write!(writer, "\nThese are the enums associated with this group.\n")?;
+  result.push(id);
 
-    for enum_id in &compound.enums {
-      let enumeration = registry.enums.get(enum_id).unwrap();
-      generate_enum_definition(&mut writer, enumeration)?;
-    }
+  let mut children: Vec<&RefID> = get_children(registry.compounds.get(id).unwrap())
+                                    .iter()
+                                    .filter(|c| registry.compounds.contains_key(*c))
+                                    .collect();
+  sort_by_name(registry, &mut children);
+
+  for child_id in children {
+    collect_tree_order(registry, child_id, get_children, result);
   }
+}
 
-  if !compound.functions.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Functions\n")?;
-    write!(writer, "\nThese are the free functions associated with this group.\n")?;
-    for func_id in &compound.functions {
-      let func = registry.functions.get(func_id).unwrap();
-      if !func.is_member {
-        generate_function_definition(&mut writer, func)?;
+/// Orders compounds depth-first, starting with the ones without parents.
+fn sort_as_tree<'a>(registry: &'a Registry,
+                    ids: &[&'a RefID],
+                    get_children: fn(&Compound) -> &Vec<RefID>) -> Vec<&'a RefID>
+{
+  let nested: HashSet<&RefID> = ids.iter()
+                                   .map(|id| registry.compounds.get(*id).unwrap())
+                                   .flat_map(get_children)
+                                   .collect();
+
+  let mut result = Vec::new();
+  for id in ids.iter().filter(|id| !nested.contains(*id)) {
+    collect_tree_order(registry, id, get_children, &mut result);
+  }
+
+  return result;
+}
+
+/// Determines the previous and next pages of all compound pages.
+///
+/// Pages are only navigated within their category, i.e. among groups, classes, or namespaces.
+/// When using the group order, groups and namespaces are visited in the same depth-first order as
+/// in their indexes, and classes are ordered by their groups.
+fn collect_page_neighbors(registry: &Registry, order: PageOrder)
+  -> HashMap<RefID, (Option<RefID>, Option<RefID>)>
+{
+  let ids_of_kind = |kinds: &[CompoundKind]| -> Vec<&RefID> {
+    let mut ids: Vec<&RefID> = registry.compounds
+                                       .iter()
+                                       .filter(|(_, c)| kinds.contains(&c.kind))
+                                       .filter(|(_, c)| c.kind != NAMESPACE
+                                                        || !is_anonymous_namespace(c))
+                                       .map(|(id, _)| id)
+                                       .collect();
+    sort_by_name(registry, &mut ids);
+    ids
+  };
+
+  let mut groups = ids_of_kind(&[GROUP]);
+  let mut classes = ids_of_kind(&[CLASS, STRUCT]);
+  let mut namespaces = ids_of_kind(&[NAMESPACE]);
+
+  if order == PageOrder::GROUP {
+    groups = sort_as_tree(registry, &groups, |c| &c.groups);
+    namespaces = sort_as_tree(registry, &namespaces, |c| &c.namespaces);
+
+    let mut grouped_classes = Vec::new();
+    for group_id in &groups {
+      for (class_id, _) in registry.get_classes(group_id) {
+        if classes.contains(&class_id) && !grouped_classes.contains(&class_id) {
+          grouped_classes.push(class_id);
+        }
       }
     }
+
+    // Classes without groups are visited last
+    classes.retain(|id| !grouped_classes.contains(id));
+    grouped_classes.append(&mut classes);
+    classes = grouped_classes;
   }
 
-  if !compound.variables.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Variables\n")?;
-    write!(writer, "\nThese are the variables associated with this group.\n")?;
+  let mut neighbors = HashMap::new();
 
-    for variable_id in &compound.variables {
-      let variable = registry.variables.get(variable_id).unwrap();
+  for pages in [&groups, &classes, &namespaces] {
+    for (index, id) in pages.iter().enumerate() {
+      let previous = if index > 0 { Some(pages[index - 1].clone()) } else { None };
+      let next = pages.get(index + 1).map(|next| (*next).clone());
+      neighbors.insert((*id).clone(), (previous, next));
+    }
+  }
 
-      write!(writer, "\n### {}\n", &variable.qualified_name)?;
+  return neighbors;
+}
 
-      if !variable.docs.brief.is_empty() {
-        for brief in &variable.docs.brief {
-          write!(writer, "\n{}\n", brief)?;
-        }
-      }
+/// Returns links to the previous and next pages of a compound page, if there are any.
+fn get_page_navigation(ctx: &Context, compound_id: &RefID) -> String
+{
+  let mut parts = Vec::new();
 
-      write!(writer, "```C++\n")?;
-      write!(writer, "{};\n", &variable.definition)?;
-      write!(writer, "```\n")?;
+  if let Some((previous, next)) = ctx.neighbors.get(compound_id) {
+    let page = &ctx.links.get(compound_id).unwrap().page;
+
+    let to_link = |id: &RefID| -> String {
+      ctx.links.link_to(&get_breadcrumb_label(ctx.registry, id), id, page).unwrap()
+    };
+
+    if let Some(previous_id) = previous {
+      parts.push(format!("Previous: {}", to_link(previous_id)));
+    }
+
+    if let Some(next_id) = next {
+      parts.push(format!("Next: {}", to_link(next_id)));
     }
   }
 
-  if !compound.defines.is_empty() {
-    write!(writer, "\n---")?;
-    write!(writer, "\n## Macros\n\n")?;
+  return parts.join(" | ");
+}
 
-    for define_id in &compound.defines {
-      let define = registry.defines.get(define_id).unwrap();
-      write!(writer, "* {}\n", &define.name)?;
-    }
+/// Returns the options that determine which pages entities are rendered in, and how they are
+/// linked to.
+pub fn get_link_options(registry: &Registry, options: &GeneratorOptions) -> D2mResult<LinkOptions>
+{
+  let mut link_options = LinkOptions::new();
+  link_options.layout = options.layout;
+  link_options.style = if options.obsidian { LinkStyle::OBSIDIAN } else { LinkStyle::MARKDOWN };
+  link_options.slug_style = options.slug_style;
+  link_options.filename_scheme = options.filename_scheme.to_owned();
+  link_options.filename_case = options.filename_case;
+  link_options.link_base = options.link_base.to_owned();
+  link_options.page_paths = options.page_paths.clone();
+
+  // The headings are known up front, since links to functions refer to their headings
+  if let Some(script) = &options.script {
+    link_options.headings = script.get_member_headings(registry)?;
   }
 
-  writer.flush()?;
-  Ok(())
+  return Ok(link_options);
 }
 
-pub fn generate_markdown(output_dir: &PathBuf, registry: &Registry) -> EmitResult
+pub fn generate_markdown(output_dir: &Path,
+                         registry: &Registry,
+                         options: &GeneratorOptions) -> D2mResult<()>
 {
   let start_time = SystemTime::now();
-  println!("Generating Markdown output...");
+  info!("Generating Markdown output...");
+
+  let stage = profile::stage("Link resolution");
 
-  emit_module_index(output_dir, registry)?;
-  emit_class_index(output_dir, registry)?;
+  let link_options = get_link_options(registry, options)?;
+  let links = LinkResolver::new(registry, &link_options);
 
-  let group_dir = output_dir.join("groups");
-  let class_dir = output_dir.join("classes");
+  let neighbors = match options.page_navigation {
+    Some(order) => collect_page_neighbors(registry, order),
+    None => HashMap::new(),
+  };
+
+  let orphans = match options.orphans {
+    OrphanPolicy::IGNORE => Vec::new(),
+    _ => navigation::find_orphans(registry, &links),
+  };
+
+  for compound_id in &orphans {
+    let compound = registry.compounds.get(*compound_id).unwrap();
+    diagnostics::warn(WarningKind::ORPHANED_COMPOUND,
+                      format!("{} is not featured in any module or namespace", compound.name));
+  }
 
-  for (compound_id, compound) in &registry.compounds {
-    if compound.kind == GROUP {
-      let dst = group_dir.join(generate_group_filename(&compound.name));
-      generate_group_file(&dst, registry, compound)?;
-    } else if compound.kind == CLASS || compound.kind == STRUCT {
-      let dst = class_dir.join(get_class_filename(&compound.name));
-      generate_class_file(&dst, registry, compound_id, compound)?;
+  let miscellaneous = if options.orphans == OrphanPolicy::LIST { orphans } else { Vec::new() };
+  let navigation = navigation::build_navigation(registry, &links, false, &miscellaneous);
+  drop(stage);
+
+  let mut weights = HashMap::new();
+  if options.front_matter.is_some() {
+    let mut order = Vec::new();
+    navigation::collect_page_order(&navigation, &mut order);
+
+    for page in order {
+      let weight = weights.len() + 1;
+      weights.entry(page.to_owned()).or_insert(weight);
     }
   }
 
+  let miscellaneous = miscellaneous.into_iter().cloned().collect();
+  let ctx = Context {
+    registry,
+    options,
+    output_dir,
+    links,
+    neighbors,
+    weights,
+    miscellaneous,
+    headings: link_options.headings,
+    date: get_current_date(),
+    provenance: options.provenance.as_ref().map(|provenance| get_provenance(registry, provenance)),
+  };
+
+  if options.generates(PageKind::INDEXES) {
+    let _stage = profile::stage("Index pages");
+    emit_main_index(&ctx)?;
+    emit_module_index(&ctx)?;
+    emit_class_index(&ctx)?;
+    emit_namespace_index(&ctx)?;
+    emit_function_index(&ctx)?;
+    emit_macro_index(&ctx)?;
+    emit_enum_index(&ctx)?;
+    emit_symbol_index(&ctx)?;
+    emit_deprecated_index(&ctx)?;
+  }
+
+  if options.symbol_map && options.compound.is_none() {
+    let _stage = profile::stage("Symbol map");
+    emit_symbol_map(&ctx)?;
+  }
+
+  let has_page = |compound: &Compound| match compound.kind {
+    GROUP => options.generates(PageKind::GROUPS),
+    CLASS | STRUCT => options.generates(PageKind::CLASSES),
+    NAMESPACE => options.generates(PageKind::NAMESPACES) && !is_anonymous_namespace(compound),
+    _ => false,
+  };
+
+  let only_compound = options.compound.as_ref();
+  let pages: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(id, _)| only_compound.is_none_or(|compound_id| compound_id == *id))
+            .filter(|(_, compound)| has_page(compound))
+            .collect();
+
+  let stage = profile::stage("Compound pages");
+  progress::start("Generating", pages.len());
+
+  for (compound_id, compound) in pages {
+    let result = match compound.kind {
+      GROUP => generate_group_file(&ctx, compound_id, compound),
+      NAMESPACE => generate_namespace_file(&ctx, compound_id, compound),
+      _ => generate_class_file(&ctx, compound_id, compound),
+    };
+
+    result.inspect_err(|_| progress::finish())?;
+    progress::advance();
+  }
+
+  progress::finish();
+  drop(stage);
+
+  if options.compound.is_none() {
+    let _stage = profile::stage("Navigation files");
+    emit_navigation_files(&ctx, &navigation)?;
+  }
+
   let end_time = SystemTime::now();
-  println!("Generated Markdown files in {} ms",
-           end_time.duration_since(start_time).unwrap().as_millis());
+  info!("Generated Markdown files in {} ms",
+        end_time.duration_since(start_time).unwrap().as_millis());
 
   Ok(())
 }
+
+/// Emits the navigation files and summaries of the whole output, such as llms.txt.
+fn emit_navigation_files(ctx: &Context, navigation: &NavItem) -> D2mResult<()>
+{
+  let options = ctx.options;
+  let output_dir = ctx.output_dir;
+  let registry = ctx.registry;
+
+  if options.mkdocs_nav {
+    navigation::emit_mkdocs_nav(output_dir, navigation, &options.nav_prefix)?;
+  }
+
+  if options.docusaurus {
+    navigation::emit_docusaurus_sidebar(output_dir, navigation, &options.nav_prefix)?;
+  }
+
+  if options.layout == Layout::GITHUB_WIKI {
+    navigation::emit_wiki_sidebar(output_dir, navigation, &ctx.links)?;
+  }
+
+  if options.mdbook {
+    navigation::emit_mdbook_summary(output_dir, navigation)?;
+  }
+
+  if options.llms_txt {
+    llms::emit_llms_txt(output_dir, registry, &ctx.links)?;
+  }
+
+  if options.llms_full_txt {
+    llms::emit_llms_full_txt(output_dir, navigation)?;
+  }
+
+  if options.vitepress_sidebar {
+    let miscellaneous: Vec<&RefID> = ctx.miscellaneous.iter().collect();
+    let navigation = navigation::build_navigation(registry, &ctx.links, true, &miscellaneous);
+    navigation::emit_vitepress_sidebar(output_dir, &navigation, &options.nav_prefix)?;
+  }
+
+  return Ok(());
+}
@@ -0,0 +1,103 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// Whether the post-generation hook is run once, or once for every written file.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookMode
+{
+  #[default]
+  ONCE,
+  #[serde(rename = "per-file")]
+  PER_FILE,
+}
+
+/// An external command that is run after the output has been generated, see the "post-hook"
+/// configuration table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PostHook
+{
+  /// The program and its arguments, e.g. ["prettier", "--write"].
+  pub command: Vec<String>,
+  /// Whether the command is run once, or once for every file with the path of the file appended.
+  #[serde(default)]
+  pub mode: HookMode,
+}
+
+impl PostHook
+{
+  pub fn validate(&self) -> Result<(), String>
+  {
+    if self.command.is_empty() || self.command[0].is_empty() {
+      return Err(String::from("the post-hook command must name a program"));
+    }
+
+    return Ok(());
+  }
+}
+
+/// Runs a command with some JSON on stdin, failing unless the command succeeds.
+fn run_command(command: &[String], output_dir: &Path, input: &Value) -> D2mResult<()>
+{
+  let program = &command[0];
+  debug!("Running {}", command.join(" "));
+
+  let mut child = Command::new(program).args(&command[1..])
+                                       .env("D2M_OUTPUT_DIR", output_dir)
+                                       .stdin(Stdio::piped())
+                                       .spawn()
+                                       .map_err(|err| {
+                                         D2mError::HOOK(format!("could not start {}: {}",
+                                                                program, err))
+                                       })?;
+
+  // The command sees the end of its input once stdin is closed, which happens when it is dropped
+  if let Some(mut stdin) = child.stdin.take() {
+    let input = serde_json::to_string_pretty(input).map_err(io::Error::from)?;
+
+    // Commands that don't read their input may exit before it is written, which is fine
+    let _ = stdin.write_all(input.as_bytes()).and_then(|_| stdin.write_all(b"\n"));
+  }
+
+  let status = child.wait()?;
+  if !status.success() {
+    return Err(D2mError::HOOK(format!("{} failed with {}", command.join(" "), status)));
+  }
+
+  return Ok(());
+}
+
+/// Runs the post-generation hook, where the manifest of the written files is passed on stdin.
+///
+/// In the per-file mode, the command is run for each file, with the path of the file appended to
+/// the arguments and the manifest entry of the file passed on stdin instead. The path of the
+/// output directory is available in the D2M_OUTPUT_DIR environment variable.
+pub fn run_post_hook(hook: &PostHook, output_dir: &Path, manifest: &Value) -> D2mResult<()>
+{
+  info!("Running post-generation hook {}...", hook.command.join(" "));
+
+  if hook.mode == HookMode::ONCE {
+    return run_command(&hook.command, output_dir, manifest);
+  }
+
+  let files = manifest["files"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+  for entry in files {
+    if let Some(path) = entry["path"].as_str() {
+      let mut command = hook.command.clone();
+      command.push(output_dir.join(path).to_string_lossy().to_string());
+      run_command(&command, output_dir, entry)?;
+    }
+  }
+
+  return Ok(());
+}
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use lazy_static::lazy_static;
+use log::info;
+use regex::Regex;
+
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::links::{self, SlugStyle};
+
+lazy_static! {
+  static ref LINK_REGEX: Regex = Regex::new(r"\]\(([^)\s]+)\)").unwrap();
+  static ref CODE_SPAN_REGEX: Regex = Regex::new(r"`[^`]*`").unwrap();
+}
+
+/// The headings and links of a generated page.
+struct ScannedPage
+{
+  anchors: HashSet<String>,
+  links: Vec<String>,
+}
+
+/// Collects the paths of all Markdown files in a directory, relative to the output directory.
+pub fn collect_pages(dir: &Path, prefix: &str, pages: &mut Vec<String>) -> io::Result<()>
+{
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+    if path.is_dir() {
+      collect_pages(&path, &format!("{}{}/", prefix, name), pages)?;
+    } else if name.ends_with(".md") {
+      pages.push(format!("{}{}", prefix, name));
+    }
+  }
+
+  Ok(())
+}
+
+fn scan_page(content: &str, slug_style: SlugStyle) -> ScannedPage
+{
+  let mut page = ScannedPage { anchors: HashSet::new(), links: Vec::new() };
+  let mut occurrences: HashMap<String, usize> = HashMap::new();
+  let mut in_code_block = false;
+
+  for line in content.lines() {
+    if line.starts_with("```") {
+      in_code_block = !in_code_block;
+      continue;
+    } else if in_code_block {
+      continue;
+    }
+
+    if line.starts_with('#') {
+      let heading = line.trim_start_matches('#');
+      let anchor = links::slugify(heading, slug_style);
+
+      // Markdown renderers number the anchors of headings with the same text
+      let occurrence = occurrences.entry(anchor.to_owned()).or_default();
+      page.anchors.insert(links::number_anchor(anchor, *occurrence, slug_style));
+      *occurrence += 1;
+    }
+
+    let line = CODE_SPAN_REGEX.replace_all(line, "");
+    for captures in LINK_REGEX.captures_iter(&line) {
+      page.links.push(captures[1].to_owned());
+    }
+  }
+
+  return page;
+}
+
+/// Resolves a relative link target against the directory of a page, e.g. "../classes/foo.md" as
+/// seen from "groups/bar.md" resolves to "classes/foo.md".
+fn resolve_path(from_page: &str, target: &str) -> String
+{
+  let mut components: Vec<&str> = from_page.split('/').collect();
+  components.pop();
+
+  for component in target.split('/') {
+    match component {
+      "." | "" => (),
+      ".." => {
+        components.pop();
+      }
+      _ => components.push(component),
+    }
+  }
+
+  return components.join("/");
+}
+
+/// Verifies that all relative links in the generated Markdown files refer to existing pages and
+/// headings, reporting a warning for each broken link.
+///
+/// External links, i.e. links with a URL scheme or absolute paths, are not checked, except for
+/// links rooted at the link base, which are resolved against the output directory.
+pub fn check_links(output_dir: &Path, slug_style: SlugStyle, link_base: Option<&str>)
+  -> io::Result<()>
+{
+  let link_base = link_base.map(links::get_link_base);
+
+  let start_time = SystemTime::now();
+  info!("Checking links...");
+
+  let mut paths = Vec::new();
+  collect_pages(output_dir, "", &mut paths)?;
+
+  let mut pages = BTreeMap::new();
+  for path in paths {
+    let content = fs::read_to_string(output_dir.join(&path))?;
+    pages.insert(path, scan_page(&content, slug_style));
+  }
+
+  let mut broken_links = 0;
+
+  for (path, page) in &pages {
+    for link in &page.links {
+      // Links rooted at the link base are resolved from the output directory
+      let (from_page, relative_link) =
+        match link_base.as_ref().and_then(|base| link.strip_prefix(base.as_str())) {
+          Some(stripped) => ("", stripped),
+          None if link.contains("://") || link.starts_with("mailto:") => continue,
+          None if link.starts_with('/') => continue,
+          None => (path.as_str(), link.as_str()),
+        };
+
+      let (target, anchor) = match relative_link.split_once('#') {
+        Some((target, anchor)) => (target, Some(anchor)),
+        None => (relative_link, None),
+      };
+
+      let target_path = match target.is_empty() {
+        true => path.to_owned(),
+        false => resolve_path(from_page, target),
+      };
+
+      // Links to wiki pages omit the file extension
+      let target_page = pages.get(&target_path)
+                             .or_else(|| pages.get(&format!("{}.md", target_path)));
+
+      let is_valid = match (target_page, anchor) {
+        (Some(target_page), Some(anchor)) => target_page.anchors.contains(anchor),
+        (Some(_), None) => true,
+        (None, _) => output_dir.join(&target_path).exists() && anchor.is_none(),
+      };
+
+      if !is_valid {
+        broken_links += 1;
+        diagnostics::warn_in_file(WarningKind::BROKEN_LINK,
+                                  format!("broken link to '{}' in {}", link, path),
+                                  Some(output_dir.join(path)));
+      }
+    }
+  }
+
+  let end_time = SystemTime::now();
+  info!("Checked links of {} page(s) in {} ms, found {} broken link(s)",
+        pages.len(),
+        end_time.duration_since(start_time).unwrap().as_millis(),
+        broken_links);
+
+  Ok(())
+}
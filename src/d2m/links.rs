@@ -0,0 +1,899 @@
+use std::collections::{HashMap, HashSet};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+
+/// The layouts of the generated files.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum Layout
+{
+  /// Compound pages are placed in subdirectories based on their kinds.
+  DEFAULT,
+  /// All pages are placed directly in the output directory, where compound pages are prefixed
+  /// with their kinds, e.g. "class_foo.md", for wikis that don't support subdirectories.
+  FLAT,
+  /// All pages are placed directly in the output directory, and links omit file extensions.
+  GITHUB_WIKI,
+  /// Class and namespace pages are placed in directories that mirror the namespace hierarchy,
+  /// e.g. "foo/bar/class_baz.md" for "foo::bar::Baz".
+  BY_NAMESPACE,
+}
+
+/// How output file names are compared when checking that they are unique.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum FilenameCase
+{
+  /// Case-insensitive on Windows and macOS, and case-sensitive elsewhere.
+  AUTO,
+  /// File names that only differ in case are different files.
+  SENSITIVE,
+  /// File names that only differ in case are the same file, as on Windows and macOS by default.
+  INSENSITIVE,
+}
+
+impl FilenameCase
+{
+  pub fn is_case_insensitive(self) -> bool
+  {
+    return match self {
+      FilenameCase::AUTO => cfg!(any(windows, target_os = "macos")),
+      FilenameCase::SENSITIVE => false,
+      FilenameCase::INSENSITIVE => true,
+    };
+  }
+}
+
+/// Keeps track of the file names in use, so that files that would overwrite each other can be
+/// given numbered names instead.
+pub struct FileNames
+{
+  case_insensitive: bool,
+  used: HashSet<String>,
+}
+
+impl FileNames
+{
+  pub fn new(case: FilenameCase) -> Self
+  {
+    Self {
+      case_insensitive: case.is_case_insensitive(),
+      used: HashSet::new(),
+    }
+  }
+
+  fn key(&self, name: &str) -> String
+  {
+    return if self.case_insensitive { name.to_lowercase() } else { name.to_owned() };
+  }
+
+  /// Marks a file name as used, returns false if it was already in use.
+  pub fn reserve(&mut self, name: &str) -> bool
+  {
+    let key = self.key(name);
+    return self.used.insert(key);
+  }
+
+  /// Returns a file name that isn't in use, which is either the specified name or a numbered
+  /// variant of it, e.g. "foo_2.md" for "foo.md", and marks it as used.
+  pub fn claim(&mut self, name: &str) -> String
+  {
+    let mut unique_name = name.to_owned();
+    let mut number = 1;
+
+    while !self.reserve(&unique_name) {
+      number += 1;
+      unique_name = number_file(name, number);
+    }
+
+    return unique_name;
+  }
+}
+
+/// The syntax used for links between pages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LinkStyle
+{
+  /// Inline Markdown links with relative URLs, e.g. `[text](../classes/class_foo.md#section)`.
+  MARKDOWN,
+  /// Obsidian wiki links, e.g. `[[class_foo#Section|text]]`.
+  OBSIDIAN,
+}
+
+/// The index pages, which list the documented entities.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Index
+{
+  MAIN,
+  MODULES,
+  CLASSES,
+  NAMESPACES,
+  FUNCTIONS,
+  ENUMS,
+  MACROS,
+  SYMBOLS,
+  /// Only generated if there are deprecated entities.
+  DEPRECATED,
+}
+
+impl Index
+{
+  /// The indexes listed by the main index, in order.
+  pub const LISTED: [Index; 7] = [Index::MODULES,
+                                  Index::CLASSES,
+                                  Index::NAMESPACES,
+                                  Index::FUNCTIONS,
+                                  Index::ENUMS,
+                                  Index::MACROS,
+                                  Index::SYMBOLS];
+
+  pub fn title(self) -> &'static str
+  {
+    return match self {
+      Index::MAIN => "API",
+      Index::MODULES => "Modules",
+      Index::CLASSES => "Classes",
+      Index::NAMESPACES => "Namespaces",
+      Index::FUNCTIONS => "Functions",
+      Index::ENUMS => "Enums",
+      Index::MACROS => "Macros",
+      Index::SYMBOLS => "Symbols",
+      Index::DEPRECATED => "Deprecated",
+    };
+  }
+
+  pub fn description(self) -> &'static str
+  {
+    return match self {
+      Index::MAIN => "The indexes of the API documentation.",
+      Index::MODULES => "The API organized by topic.",
+      Index::CLASSES => "All classes and structs.",
+      Index::NAMESPACES => "All namespaces.",
+      Index::FUNCTIONS => "Free functions that are not part of any module.",
+      Index::ENUMS => "All enums.",
+      Index::MACROS => "All documented macros.",
+      Index::SYMBOLS => "Every symbol in alphabetical order.",
+      Index::DEPRECATED => "Entities that should no longer be used.",
+    };
+  }
+}
+
+/// The names of devices on Windows, which can't be used as file names regardless of extension.
+const RESERVED_FILENAMES: [&str; 22] = [
+  "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+  "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Makes a file name portable, by replacing characters that aren't allowed on some platforms,
+/// e.g. ':' and '*', with underscores, and by encoding other characters than ASCII by their code
+/// points, e.g. "u00e9" for 'é'. Names of Windows devices, e.g. "nul.md", get a trailing
+/// underscore.
+pub fn sanitize_filename(filename: &str) -> String
+{
+  let mut result = String::with_capacity(filename.len());
+
+  for c in filename.chars() {
+    if c.is_ascii_alphanumeric() || "._-".contains(c) {
+      result.push(c);
+    } else if c.is_ascii() {
+      result.push('_');
+    } else {
+      result += &format!("u{:04x}", c as u32);
+    }
+  }
+
+  // Trailing periods are removed by Windows
+  while result.ends_with('.') {
+    result.pop();
+    result.push('_');
+  }
+
+  let stem = result.split('.').next().unwrap().to_lowercase();
+  if RESERVED_FILENAMES.contains(&stem.as_str()) {
+    result.insert(stem.len(), '_');
+  }
+
+  return result;
+}
+
+pub fn group_filename(name: &str) -> String
+{
+  return sanitize_filename(&format!("group_{}.md", name.to_lowercase().replace(" ", "_")));
+}
+
+pub fn class_filename(name: &str) -> String
+{
+  return sanitize_filename(&format!("class_{}.md",
+                                    name.to_lowercase()
+                                        .replace("::", "_")
+                                        .replace("<", "_")
+                                        .replace(">", "_")
+                                        .replace(" ", "")));
+}
+
+pub fn namespace_filename(name: &str) -> String
+{
+  return sanitize_filename(&format!("namespace_{}.md", name.to_lowercase().replace("::", "_")));
+}
+
+/// Replaces characters that are not allowed in GitHub wiki page names.
+fn wiki_filename(filename: &str) -> String
+{
+  let filename: String =
+    filename.chars()
+            .map(|c| if c.is_alphanumeric() || "._-".contains(c) { c } else { '-' })
+            .collect();
+
+  return sanitize_filename(&filename);
+}
+
+/// The placeholders that can be used in filename schemes.
+pub const FILENAME_PLACEHOLDERS: [&str; 4] =
+  ["kind", "category", "name_slug", "qualified_name_slug"];
+
+/// Converts a name to lowercase, and replaces each run of characters other than letters, digits,
+/// and underscores with a hyphen, e.g. "foo-bar_t-int" for "Foo::bar_t<int>".
+fn filename_slug(name: &str) -> String
+{
+  let mut slug = String::with_capacity(name.len());
+
+  for c in name.chars() {
+    if c.is_alphanumeric() || c == '_' {
+      slug.extend(c.to_lowercase());
+    } else if !slug.is_empty() && !slug.ends_with('-') {
+      slug.push('-');
+    }
+  }
+
+  return slug.trim_end_matches('-').to_owned();
+}
+
+/// Checks that a filename scheme only uses known placeholders.
+pub fn validate_filename_scheme(scheme: &str) -> Result<(), String>
+{
+  lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{([^}]*)\}").unwrap();
+  }
+
+  for captures in PLACEHOLDER.captures_iter(scheme) {
+    let placeholder = captures.get(1).unwrap().as_str();
+    if !FILENAME_PLACEHOLDERS.contains(&placeholder) {
+      return Err(format!("unknown placeholder {{{}}} in filename scheme, expected one of {}",
+                         placeholder,
+                         FILENAME_PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")));
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns the path of a compound page according to a filename scheme.
+fn apply_filename_scheme(scheme: &str, category: &str, compound: &Compound) -> String
+{
+  let kind = match compound.kind {
+    STRUCT => "struct",
+    INTERFACE => "interface",
+    GROUP => "group",
+    NAMESPACE => "namespace",
+    _ => "class",
+  };
+
+  let name = compound.name.rsplit("::").next().unwrap();
+
+  let path = scheme.replace("{kind}", kind)
+                   .replace("{category}", category)
+                   .replace("{name_slug}", &filename_slug(name))
+                   .replace("{qualified_name_slug}", &filename_slug(&compound.name));
+
+  let path = if path.ends_with(".md") { path } else { path + ".md" };
+  return path.split('/').map(sanitize_filename).collect::<Vec<_>>().join("/");
+}
+
+/// Splits a qualified name into its enclosing scopes and its unqualified name, ignoring "::" in
+/// template arguments, e.g. (["foo", "bar"], "Baz<a::b>") for "foo::bar::Baz<a::b>".
+fn split_scope(qualified_name: &str) -> (Vec<&str>, &str)
+{
+  let mut scopes = Vec::new();
+
+  let mut depth = 0;
+  let mut start = 0;
+  let mut index = 0;
+  let bytes = qualified_name.as_bytes();
+
+  while index < bytes.len() {
+    match bytes[index] {
+      b'<' => depth += 1,
+      b'>' if depth > 0 => depth -= 1,
+      b':' if depth == 0 && bytes.get(index + 1) == Some(&b':') => {
+        scopes.push(&qualified_name[start..index]);
+        index += 1;
+        start = index + 1;
+      }
+      _ => {}
+    }
+
+    index += 1;
+  }
+
+  return (scopes, &qualified_name[start..]);
+}
+
+/// Returns the page path of a class or namespace in the namespace-mirroring layout, where a
+/// namespace page is placed in its own directory.
+fn get_scoped_page(compound: &Compound) -> String
+{
+  let (scopes, name) = split_scope(&compound.name);
+
+  let mut components: Vec<String> = scopes.iter()
+                                          .map(|scope| sanitize_filename(&scope.to_lowercase()))
+                                          .collect();
+  if compound.kind == NAMESPACE {
+    components.push(sanitize_filename(&name.to_lowercase()));
+    components.push(namespace_filename(name));
+  } else {
+    components.push(class_filename(name));
+  }
+
+  return components.join("/");
+}
+
+/// Returns the page path of a class, group, or namespace, if it has a page.
+fn get_compound_page(options: &LinkOptions, compound: &Compound) -> Option<String>
+{
+  let (directory, filename) = match compound.kind {
+    CLASS | STRUCT | INTERFACE => ("classes", class_filename(&compound.name)),
+    GROUP => ("groups", group_filename(&compound.name)),
+    NAMESPACE if !compound.name.contains('@') => {
+      ("namespaces", namespace_filename(&compound.name))
+    }
+    _ => return None,
+  };
+
+  let path = match &options.filename_scheme {
+    Some(scheme) => apply_filename_scheme(scheme, directory, compound),
+    None => format!("{}/{}", directory, filename),
+  };
+
+  return match options.layout {
+    Layout::DEFAULT => Some(path),
+    Layout::BY_NAMESPACE if options.filename_scheme.is_some() || compound.kind == GROUP => {
+      Some(path)
+    }
+    Layout::BY_NAMESPACE => Some(get_scoped_page(compound)),
+    Layout::FLAT if options.filename_scheme.is_some() => Some(path.replace('/', "_")),
+    Layout::FLAT => Some(filename),
+    Layout::GITHUB_WIKI if options.filename_scheme.is_some() => {
+      Some(wiki_filename(&path.replace('/', "-")))
+    }
+    Layout::GITHUB_WIKI => Some(wiki_filename(&filename)),
+  };
+}
+
+/// Appends a number to the stem of a file name, e.g. "classes/foo_2.md" for "classes/foo.md".
+fn number_file(path: &str, number: usize) -> String
+{
+  let filename_start = path.rfind('/').map_or(0, |index| index + 1);
+
+  return match path[filename_start..].rfind('.') {
+    Some(index) => {
+      let (stem, extension) = path.split_at(filename_start + index);
+      format!("{}_{}{}", stem, number, extension)
+    }
+    None => format!("{}_{}", path, number),
+  };
+}
+
+pub fn get_index_page(layout: Layout, index: Index) -> String
+{
+  return match (layout, index) {
+    (Layout::GITHUB_WIKI, Index::MAIN) => String::from("Home.md"),
+    (Layout::GITHUB_WIKI, _) => format!("{}.md", index.title()),
+    (_, Index::MAIN) => String::from("index.md"),
+    (_, _) => format!("{}.md", index.title().to_lowercase()),
+  };
+}
+
+/// Returns the page of a split index that lists the entries starting with a letter, e.g.
+/// "classes_a.md", where '#' denotes the entries that don't start with letters.
+pub fn get_index_part_page(layout: Layout, index: Index, letter: char) -> String
+{
+  let part = match letter {
+    '#' => String::from("Other"),
+    _ => letter.to_ascii_uppercase().to_string(),
+  };
+
+  return match layout {
+    Layout::GITHUB_WIKI => format!("{}-{}.md", index.title(), part),
+    _ => format!("{}_{}.md", index.title(), part).to_lowercase(),
+  };
+}
+
+/// Removes characters that have special meaning in Obsidian links from a heading.
+fn obsidian_heading(heading: &str) -> String
+{
+  return heading.replace(['#', '|', '^', '[', ']'], " ").trim().to_owned();
+}
+
+/// The conventions used by Markdown renderers to generate heading anchors.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum SlugStyle
+{
+  /// The text is converted to lowercase, punctuation is removed, and each space is replaced with
+  /// a hyphen.
+  GITHUB,
+  /// Like GitHub, but non-ASCII characters are removed, and runs of spaces and hyphens are
+  /// replaced with a single hyphen, as done by the Python-Markdown "toc" extension.
+  MKDOCS,
+  /// Docusaurus uses the same conventions as GitHub, through the "github-slugger" package.
+  DOCUSAURUS,
+}
+
+/// Returns the anchor that Markdown renderers generate for a heading with the specified text.
+pub fn slugify(heading: &str, style: SlugStyle) -> String
+{
+  let mut slug = String::with_capacity(heading.len());
+
+  match style {
+    SlugStyle::GITHUB | SlugStyle::DOCUSAURUS => {
+      for c in heading.trim().chars() {
+        if c.is_alphanumeric() || c == '_' || c == '-' {
+          slug.extend(c.to_lowercase());
+        } else if c == ' ' {
+          slug.push('-');
+        }
+      }
+    }
+    SlugStyle::MKDOCS => {
+      for c in heading.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+          slug.push(c.to_ascii_lowercase());
+        } else if (c == '-' || c.is_whitespace()) && !slug.ends_with('-') {
+          slug.push('-');
+        }
+      }
+    }
+  }
+
+  return slug;
+}
+
+/// Returns a link base that pages can be appended to, i.e. with a trailing slash.
+pub fn get_link_base(base: &str) -> String
+{
+  return if base.ends_with('/') { base.to_owned() } else { format!("{}/", base) };
+}
+
+/// Returns the anchor of a heading that is preceded by `occurrence` headings with the same anchor
+/// on its page, since Markdown renderers number such anchors to keep them unique, e.g. "foo-1"
+/// for the second "foo" heading on GitHub.
+pub fn number_anchor(anchor: String, occurrence: usize, style: SlugStyle) -> String
+{
+  return match (occurrence, style) {
+    (0, _) => anchor,
+    (_, SlugStyle::MKDOCS) => format!("{}_{}", anchor, occurrence),
+    (_, SlugStyle::GITHUB | SlugStyle::DOCUSAURUS) => format!("{}-{}", anchor, occurrence),
+  };
+}
+
+/// Represents the location of a rendered entity in the output directory.
+#[derive(Debug, Clone)]
+pub struct Link
+{
+  /// The page path, relative to the output directory.
+  pub page: String,
+  /// The text of the linked heading, if the link refers to a section.
+  pub heading: Option<String>,
+  /// The number of preceding headings on the page with the same anchor, e.g. for overloads.
+  pub occurrence: usize,
+}
+
+impl Link
+{
+  /// Creates a link to an entire page, where the path is relative to the output directory.
+  pub fn page(page: String) -> Self
+  {
+    Self { page, heading: None, occurrence: 0 }
+  }
+
+  fn section(page: String, heading: &str) -> Self
+  {
+    Self { page, heading: Some(heading.to_owned()), occurrence: 0 }
+  }
+
+  /// Returns the relative path to the linked page, as seen from the specified page.
+  ///
+  /// The path is empty if the link refers to the specified page.
+  fn relative_path(&self, from_page: &str) -> String
+  {
+    let from_dir = match from_page.rfind('/') {
+      Some(index) => &from_page[..index + 1],
+      None => "",
+    };
+
+    return if self.page == from_page {
+      String::new()
+    } else if let Some(stripped) = self.page.strip_prefix(from_dir) {
+      stripped.to_owned()
+    } else {
+      "../".repeat(from_dir.matches('/').count()) + self.page.as_str()
+    };
+  }
+}
+
+/// Options that control where pages are placed and how they are linked.
+pub struct LinkOptions
+{
+  pub layout: Layout,
+  pub style: LinkStyle,
+  /// The conventions used to compute heading anchors.
+  pub slug_style: SlugStyle,
+  /// A pattern for the paths of compound pages, e.g. "{kind}/{qualified_name_slug}.md".
+  pub filename_scheme: Option<String>,
+  /// How page paths are compared when checking that they are unique.
+  pub filename_case: FilenameCase,
+  /// A URL or path that links to other pages are rooted at, e.g. "/docs/api/", instead of
+  /// being relative to the linking page.
+  pub link_base: Option<String>,
+  /// Headings of function sections that replace their qualified names, e.g. from a script.
+  pub headings: HashMap<RefID, String>,
+  /// Paths of compound pages that replace the ones from the layout, e.g. from the configuration.
+  pub page_paths: HashMap<RefID, String>,
+}
+
+impl LinkOptions
+{
+  pub fn new() -> Self
+  {
+    Self {
+      layout: Layout::DEFAULT,
+      style: LinkStyle::MARKDOWN,
+      slug_style: SlugStyle::GITHUB,
+      filename_scheme: None,
+      filename_case: FilenameCase::AUTO,
+      link_base: None,
+      headings: HashMap::new(),
+      page_paths: HashMap::new(),
+    }
+  }
+}
+
+/// Keeps track of the pages and sections that entities are rendered in.
+pub struct LinkResolver
+{
+  layout: Layout,
+  style: LinkStyle,
+  slug_style: SlugStyle,
+  link_base: Option<String>,
+  links: HashMap<RefID, Link>,
+  parents: HashMap<RefID, RefID>,
+}
+
+impl LinkResolver
+{
+  pub fn new(registry: &Registry, options: &LinkOptions) -> Self
+  {
+    let layout = options.layout;
+    let mut links = HashMap::new();
+
+    let get_heading = |id: &RefID, func: &Function| -> String {
+      return options.headings.get(id).map_or(func.qualified_name.to_string(), String::clone);
+    };
+
+    // Pages that would overwrite other pages get numbered file names instead
+    let mut used_pages = FileNames::new(options.filename_case);
+    for index in Index::LISTED.iter().chain(&[Index::MAIN, Index::DEPRECATED]) {
+      used_pages.reserve(&get_index_page(layout, *index));
+    }
+
+    // Sorted so that the numbering is deterministic
+    let mut compound_ids: Vec<&RefID> = registry.compounds.keys().collect();
+    compound_ids.sort();
+
+    for compound_id in compound_ids {
+      let compound = registry.compounds.get(compound_id).unwrap();
+
+      if let Some(page) = get_compound_page(options, compound) {
+        let page = options.page_paths.get(compound_id).map_or(page, String::clone);
+        let unique_page = used_pages.claim(&page);
+
+        if unique_page != page {
+          diagnostics::warn(WarningKind::PAGE_COLLISION,
+                            format!("the page of {} would overwrite another page, using {} \
+                                     instead",
+                                    &compound.name,
+                                    &unique_page));
+        }
+
+        links.insert(compound_id.to_owned(), Link::page(unique_page));
+      }
+    }
+
+    // Free functions are rendered on group pages if possible, otherwise on the function index
+    for (function_id, func) in &registry.functions {
+      if !func.is_member {
+        links.insert(function_id.to_owned(),
+                     Link::section(get_index_page(layout, Index::FUNCTIONS),
+                                   &get_heading(function_id, func)));
+      }
+    }
+
+    // Enums are rendered on group pages if possible, otherwise on the enum index
+    for (enum_id, enumeration) in &registry.enums {
+      links.insert(enum_id.to_owned(),
+                   Link::section(get_index_page(layout, Index::ENUMS),
+                                 &enumeration.qualified_name));
+    }
+
+    for (define_id, define) in &registry.defines {
+      if define.docs.is_documented() {
+        links.insert(define_id.to_owned(),
+                     Link::section(get_index_page(layout, Index::MACROS), &define.name));
+      }
+    }
+
+    for (compound_id, compound) in &registry.compounds {
+      if !matches!(compound.kind, CLASS | STRUCT | INTERFACE | GROUP) {
+        continue;
+      }
+
+      let page = links.get(compound_id).unwrap().page.to_owned();
+
+      for function_id in &compound.functions {
+        if let Some(func) = registry.functions.get(function_id) {
+          // Group pages only feature free functions
+          if compound.kind != GROUP || !func.is_member {
+            links.insert(function_id.to_owned(),
+                         Link::section(page.to_owned(), &get_heading(function_id, func)));
+          }
+        }
+      }
+
+      if compound.kind == GROUP {
+        for enum_id in &compound.enums {
+          if let Some(enumeration) = registry.enums.get(enum_id) {
+            links.insert(enum_id.to_owned(),
+                         Link::section(page.to_owned(), &enumeration.qualified_name));
+          }
+        }
+
+        for variable_id in &compound.variables {
+          if let Some(variable) = registry.variables.get(variable_id) {
+            links.insert(variable_id.to_owned(),
+                         Link::section(page.to_owned(), &variable.qualified_name));
+          }
+        }
+
+        for define_id in &compound.defines {
+          if let Some(define) = registry.defines.get(define_id) {
+            links.insert(define_id.to_owned(), Link::section(page.to_owned(), &define.name));
+          }
+        }
+      }
+    }
+
+    Self::number_overloads(registry, layout, options.slug_style, &mut links);
+
+    return Self {
+      layout,
+      style: options.style,
+      slug_style: options.slug_style,
+      link_base: options.link_base.as_ref().map(|base| get_link_base(base)),
+      links,
+      parents: Self::collect_parents(registry),
+    };
+  }
+
+  /// Numbers the sections of functions that share headings with preceding functions on the same
+  /// page, e.g. overloads, in the order that they are rendered.
+  fn number_overloads(registry: &Registry,
+                      layout: Layout,
+                      slug_style: SlugStyle,
+                      links: &mut HashMap<RefID, Link>)
+  {
+    let mut compound_ids: Vec<&RefID> = registry.compounds.keys().collect();
+    compound_ids.sort();
+
+    // Class and group pages feature functions in the order of their compounds, whereas the
+    // function index lists them by namespace, and then by file for the global namespace
+    let mut sections: Vec<(String, Vec<&RefID>)> = Vec::new();
+
+    for compound_id in &compound_ids {
+      let compound = registry.compounds.get(*compound_id).unwrap();
+      if matches!(compound.kind, CLASS | STRUCT | INTERFACE | GROUP) {
+        if let Some(link) = links.get(*compound_id) {
+          let mut functions: Vec<&RefID> = compound.functions.iter().collect();
+
+          // Class pages feature instance functions, then static functions, then related functions
+          if compound.kind != GROUP {
+            functions.sort_by_key(|id| {
+              registry.functions.get(*id).map(|f| (f.is_related, f.is_static))
+            });
+          }
+
+          sections.push((link.page.to_owned(), functions));
+        }
+      }
+    }
+
+    let mut scopes: Vec<&Compound> = registry.compounds
+                                             .values()
+                                             .filter(|c| matches!(c.kind, NAMESPACE | FILE))
+                                             .collect();
+    scopes.sort_by_key(|c| (c.kind == FILE, &c.name));
+
+    let function_index = get_index_page(layout, Index::FUNCTIONS);
+    for scope in scopes {
+      sections.push((function_index.to_owned(), scope.functions.iter().collect()));
+    }
+
+    let mut numbered = HashSet::new();
+    let mut occurrences: HashMap<(String, String), usize> = HashMap::new();
+
+    for (page, function_ids) in sections {
+      for function_id in function_ids {
+        let link = match links.get_mut(function_id) {
+          Some(link) if link.page == page && numbered.insert(function_id) => link,
+          _ => continue,
+        };
+
+        if let Some(heading) = &link.heading {
+          let count = occurrences.entry((page.to_owned(), slugify(heading, slug_style)))
+                                 .or_default();
+          link.occurrence = *count;
+          *count += 1;
+        }
+      }
+    }
+  }
+
+  /// Determines the parent of each compound, used to produce breadcrumbs.
+  ///
+  /// The parent of a group is the group that contains it. Classes are primarily associated with
+  /// their groups, and otherwise with their namespaces. Namespaces belong to their outer
+  /// namespaces.
+  fn collect_parents(registry: &Registry) -> HashMap<RefID, RefID>
+  {
+    let mut parents = HashMap::new();
+
+    // Sorted to make the choice deterministic for classes featured in several groups
+    let mut compound_ids: Vec<&RefID> = registry.compounds.keys().collect();
+    compound_ids.sort();
+
+    for kind in [GROUP, NAMESPACE] {
+      for compound_id in &compound_ids {
+        let compound = registry.compounds.get(*compound_id).unwrap();
+        if compound.kind != kind {
+          continue;
+        }
+
+        let children = compound.groups.iter().chain(&compound.namespaces).chain(&compound.classes);
+        for child_id in children {
+          parents.entry(child_id.clone()).or_insert((*compound_id).clone());
+        }
+      }
+    }
+
+    return parents;
+  }
+
+  /// Returns the location of an entity, if it is rendered anywhere.
+  pub fn get(&self, id: &RefID) -> Option<&Link>
+  {
+    return self.links.get(id);
+  }
+
+  /// Returns the location of an index page.
+  pub fn get_index(&self, index: Index) -> Link
+  {
+    return Link::page(get_index_page(self.layout, index));
+  }
+
+  /// Returns a link to the page of a split index that lists the entries starting with a letter.
+  pub fn get_index_part(&self, index: Index, letter: char) -> Link
+  {
+    return Link::page(get_index_part_page(self.layout, index, letter));
+  }
+
+  /// Returns the anchor of a heading.
+  pub fn anchor(&self, heading: &str) -> String
+  {
+    return slugify(heading, self.slug_style);
+  }
+
+  /// Returns the anchor of the section that a link refers to, if any.
+  pub fn link_anchor(&self, link: &Link) -> Option<String>
+  {
+    let heading = link.heading.as_ref()?;
+    return Some(number_anchor(self.anchor(heading), link.occurrence, self.slug_style));
+  }
+
+  /// Returns the URL of a link, as seen from the specified page.
+  ///
+  /// Links to other pages are rooted at the link base, if there is one.
+  pub fn url_from(&self, link: &Link, from_page: &str) -> String
+  {
+    let mut url = match &self.link_base {
+      Some(base) if link.page != from_page => format!("{}{}", base, link.page),
+      _ => link.relative_path(from_page),
+    };
+
+    // Wiki pages are referenced by their names, i.e. without file extensions
+    if self.layout == Layout::GITHUB_WIKI {
+      url = url.strip_suffix(".md").map(str::to_owned).unwrap_or(url);
+    }
+
+    if let Some(anchor) = self.link_anchor(link) {
+      url.push('#');
+      url.push_str(&anchor);
+    }
+
+    return url;
+  }
+
+  /// Formats a link with the specified text, as seen from the specified page.
+  pub fn format_link(&self, text: &str, link: &Link, from_page: &str) -> String
+  {
+    return match self.style {
+      LinkStyle::MARKDOWN => format!("[{}]({})", text, self.url_from(link, from_page)),
+      LinkStyle::OBSIDIAN => {
+        // Obsidian resolves links by file name, regardless of the directory
+        let page = link.page.rsplit('/').next().unwrap();
+        let name = page.strip_suffix(".md").unwrap_or(page);
+
+        match &link.heading {
+          Some(heading) => format!("[[{}#{}|{}]]", name, obsidian_heading(heading), text),
+          None => format!("[[{}|{}]]", name, text),
+        }
+      }
+    };
+  }
+
+  /// Formats a link to a section on the same page.
+  pub fn local_link(&self, text: &str, heading: &str) -> String
+  {
+    return self.numbered_local_link(text, heading, 0);
+  }
+
+  /// Formats a link to a section on the same page, which is preceded by `occurrence` headings
+  /// with the same anchor. Obsidian links to the first heading with the same text.
+  pub fn numbered_local_link(&self, text: &str, heading: &str, occurrence: usize) -> String
+  {
+    return match self.style {
+      LinkStyle::MARKDOWN => {
+        let anchor = number_anchor(self.anchor(heading), occurrence, self.slug_style);
+        format!("[{}](#{})", text, anchor)
+      }
+      LinkStyle::OBSIDIAN => format!("[[#{}|{}]]", obsidian_heading(heading), text),
+    };
+  }
+
+  /// Formats a link to an entity, as seen from the specified page.
+  pub fn link_to(&self, text: &str, id: &RefID, from_page: &str) -> Option<String>
+  {
+    return self.get(id).map(|link| self.format_link(text, link, from_page));
+  }
+
+  /// Returns the chain of ancestors of a compound, starting with the outermost one.
+  pub fn get_ancestors(&self, id: &RefID) -> Vec<&RefID>
+  {
+    let mut ancestors = Vec::new();
+
+    let mut current = id;
+    while let Some(parent) = self.parents.get(current) {
+      // Guards against malformed input with cyclic relations
+      if parent == id || ancestors.contains(&parent) {
+        break;
+      }
+
+      ancestors.push(parent);
+      current = parent;
+    }
+
+    ancestors.reverse();
+    return ancestors;
+  }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use log::info;
+
+use crate::d2m::backend;
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+use crate::d2m::links::{Index, Link, LinkResolver};
+use crate::d2m::navigation::{self, NavItem};
+use crate::d2m::stats;
+
+/// The path that the links in llms.txt are relative to, i.e. the root of the output directory.
+const LLMS_PAGE: &str = "llms.txt";
+
+/// Returns the first brief paragraph of a comment on a single line.
+fn get_description(docs: &Comment) -> String
+{
+  let brief = docs.brief.first().map_or("", |s| s.as_str());
+  return brief.split_whitespace().collect::<Vec<&str>>().join(" ");
+}
+
+fn write_entry(output: &mut String,
+               links: &LinkResolver,
+               title: &str,
+               link: &Link,
+               description: &str)
+{
+  let url = links.url_from(link, LLMS_PAGE);
+
+  if description.is_empty() {
+    output.push_str(&format!("- [{}]({})\n", title, url));
+  } else {
+    output.push_str(&format!("- [{}]({}): {}\n", title, url, description));
+  }
+}
+
+/// Writes a section that links to all compounds of a kind that have pages, sorted by name.
+fn write_compound_section(output: &mut String,
+                          registry: &Registry,
+                          links: &LinkResolver,
+                          heading: &str,
+                          kinds: &[CompoundKind])
+{
+  let mut compounds: Vec<(String, &Link, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(_, compound)| kinds.contains(&compound.kind))
+            .filter_map(|(id, compound)| {
+              Some((backend::get_title(compound).to_owned(), links.get(id)?, compound))
+            })
+            .collect();
+
+  if compounds.is_empty() {
+    return;
+  }
+
+  compounds.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+  output.push_str(&format!("\n## {}\n\n", heading));
+  for (title, link, compound) in compounds {
+    write_entry(output, links, &title, link, &get_description(&compound.docs));
+  }
+}
+
+/// Emits an llms.txt file, which summarizes the structure of the API with links to its pages, so
+/// that tools based on language models can index the documentation.
+pub fn emit_llms_txt(output_dir: &Path, registry: &Registry, links: &LinkResolver)
+  -> io::Result<()>
+{
+  info!("Generating llms.txt...");
+
+  let mut output = format!("# {}\n\n", Index::MAIN.title());
+  output.push_str("> The API reference, with links to the pages of all modules, classes, and \
+                   namespaces.\n");
+
+  write_compound_section(&mut output, registry, links, "Modules", &[GROUP]);
+  write_compound_section(&mut output, registry, links, "Classes", &[CLASS, STRUCT, INTERFACE]);
+  write_compound_section(&mut output, registry, links, "Namespaces", &[NAMESPACE]);
+
+  let mut indexes = Index::LISTED.to_vec();
+  if registry.has_deprecated() {
+    indexes.push(Index::DEPRECATED);
+  }
+
+  // The indexes are mostly redundant with the sections above, so these may be skipped
+  output.push_str("\n## Optional\n\n");
+  for index in indexes {
+    write_entry(&mut output, links, index.title(), &links.get_index(index), index.description());
+  }
+
+  return stats::write_file(output_dir.join("llms.txt"), output);
+}
+
+/// Removes YAML or TOML front matter from the start of a page.
+fn strip_front_matter(content: &str) -> &str
+{
+  for delimiter in ["---", "+++"] {
+    if let Some(rest) = content.strip_prefix(delimiter).and_then(|c| c.strip_prefix('\n')) {
+      let end = format!("\n{}\n", delimiter);
+      if let Some(index) = rest.find(&end) {
+        return rest[index + end.len()..].trim_start();
+      }
+    }
+  }
+
+  return content;
+}
+
+/// Emits an llms-full.txt file, which contains the contents of all pages, in navigation order.
+pub fn emit_llms_full_txt(output_dir: &Path, root: &NavItem) -> io::Result<()>
+{
+  info!("Generating llms-full.txt...");
+
+  let mut order = Vec::new();
+  navigation::collect_page_order(root, &mut order);
+
+  let mut visited = HashSet::new();
+  let mut pages = Vec::new();
+
+  for page in order.into_iter().filter(|page| visited.insert(*page)) {
+    let content = fs::read_to_string(output_dir.join(page))?;
+    pages.push(strip_front_matter(&content).trim_end().to_owned());
+  }
+
+  return stats::write_file(output_dir.join("llms-full.txt"), pages.join("\n\n") + "\n");
+}
@@ -0,0 +1,133 @@
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::kv::{self, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::d2m::progress;
+
+/// The formats of the messages written to the console.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum LogFormat
+{
+  /// Plain text, where errors and warnings go to stderr and other messages to stdout.
+  TEXT,
+  /// One JSON object per line on stdout, with the level, message, and event details, e.g. the
+  /// "event" and "path" of parsed and written files, or the "kind" of warnings.
+  JSON,
+}
+
+lazy_static! {
+  static ref FORMAT: Mutex<LogFormat> = Mutex::new(LogFormat::TEXT);
+}
+
+/// Whether all messages go to stderr, e.g. when stdout is used for the generated output.
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Collects the key-value pairs of a log record as JSON fields.
+struct JsonFields(Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields
+{
+  fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error>
+  {
+    self.0.insert(key.to_string(), JsonValue::String(value.to_string()));
+    Ok(())
+  }
+}
+
+fn to_json(record: &Record) -> String
+{
+  let mut fields = JsonFields(Map::new());
+  fields.0.insert(String::from("level"), JsonValue::from(record.level().as_str().to_lowercase()));
+
+  // The fields are only informative, so a failure to collect them shouldn't lose the message
+  let _ = record.key_values().visit(&mut fields);
+
+  fields.0.insert(String::from("message"), JsonValue::from(record.args().to_string()));
+
+  return JsonValue::Object(fields.0).to_string();
+}
+
+/// Writes log messages to the console, where errors and warnings go to stderr in the text
+/// format, so that stdout only features the progress and summary of the run.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger
+{
+  fn enabled(&self, metadata: &Metadata) -> bool
+  {
+    return metadata.level() <= log::max_level();
+  }
+
+  fn log(&self, record: &Record)
+  {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+
+    let format = *FORMAT.lock().unwrap();
+    let mut out = get_output();
+
+    // Failing to write to the console is not worth aborting the run over
+    let _ = progress::suspend(|| match (format, record.level()) {
+      (LogFormat::JSON, _) => writeln!(out, "{}", to_json(record)),
+      (LogFormat::TEXT, Level::Error) => writeln!(io::stderr(), "Error: {}", record.args()),
+      (LogFormat::TEXT, Level::Warn) => writeln!(io::stderr(), "Warning: {}", record.args()),
+      (LogFormat::TEXT, _) => writeln!(out, "{}", record.args()),
+    });
+  }
+
+  fn flush(&self)
+  {
+    let _ = io::stdout().flush();
+  }
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+/// Installs the console logger, which shows informational messages until it is configured.
+pub fn init()
+{
+  if log::set_logger(&LOGGER).is_ok() {
+    log::set_max_level(LevelFilter::Info);
+  }
+}
+
+/// Sets the format and verbosity of the log, where quiet only shows errors, and each level of
+/// verbosity shows more details, i.e. per-file details at level one, and per-member details at
+/// level two. The JSON format always includes the per-file events.
+pub fn configure(format: LogFormat, quiet: bool, verbosity: usize)
+{
+  *FORMAT.lock().unwrap() = format;
+
+  let verbosity = if format == LogFormat::JSON { verbosity.max(1) } else { verbosity };
+
+  let level = match (quiet, verbosity) {
+    (true, _) => LevelFilter::Error,
+    (false, 0) => LevelFilter::Info,
+    (false, 1) => LevelFilter::Debug,
+    (false, _) => LevelFilter::Trace,
+  };
+
+  log::set_max_level(level);
+}
+
+/// Writes all messages to stderr, so that stdout can be used for the generated output.
+pub fn redirect_to_stderr()
+{
+  TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Returns the stream that informational messages are written to, which is stdout unless the
+/// messages are redirected to stderr.
+pub fn get_output() -> Box<dyn Write>
+{
+  return match TO_STDERR.load(Ordering::Relaxed) {
+    true => Box::new(io::stderr()),
+    false => Box::new(io::stdout()),
+  };
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use log::info;
+
+use crate::d2m::backend::{self, Callout, OutputBackend};
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+use crate::d2m::error::D2mResult;
+use crate::d2m::generator::{EmitResult, PageBuffer};
+use crate::d2m::links::{FileNames, FilenameCase, Link, LinkOptions, LinkResolver};
+use crate::d2m::stats;
+
+/// Returns the name of the man page of a compound, i.e. what is passed to `man`.
+fn get_page_name(compound: &Compound) -> &str
+{
+  return &compound.name;
+}
+
+/// Escapes text so that it isn't interpreted as roff requests or escape sequences.
+fn escape(text: &str) -> String
+{
+  let text = text.replace('\\', "\\e").replace('-', "\\-");
+
+  // Lines that start with a period or an apostrophe are control lines
+  if text.starts_with('.') || text.starts_with('\'') {
+    return format!("\\&{}", text);
+  }
+
+  return text;
+}
+
+/// Converts the Markdown produced for inline code spans in comments to bold text.
+fn convert_inline(text: &str) -> String
+{
+  let mut result = String::new();
+
+  for (index, part) in escape(text).split('`').enumerate() {
+    if index % 2 == 1 {
+      result += &format!("\\fB{}\\fR", part.trim());
+    } else {
+      result += part;
+    }
+  }
+
+  return result;
+}
+
+/// Emits a comment paragraph, where lines that start with "* " are list items.
+fn write_paragraph(writer: &mut PageBuffer, text: &str) -> EmitResult
+{
+  if text.trim().is_empty() {
+    return Ok(());
+  }
+
+  write!(writer, ".PP\n")?;
+
+  for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+    match line.strip_prefix("* ") {
+      Some(item) => write!(writer, ".IP \\(bu 2\n{}\n", convert_inline(item))?,
+      None => write!(writer, "{}\n", convert_inline(line))?,
+    }
+  }
+
+  Ok(())
+}
+
+struct ManBackend<'a>
+{
+  section: &'a str,
+  /// The page names of compounds whose default page names were already taken.
+  renamed: HashMap<String, String>,
+}
+
+impl OutputBackend for ManBackend<'_>
+{
+  /// Emits the title line and the NAME section, which features the brief description.
+  fn begin_page(&self, writer: &mut PageBuffer, title: &str, compound: Option<&Compound>)
+    -> EmitResult
+  {
+    let name = compound.map_or(title, get_page_name);
+    write!(writer, ".TH \"{}\" \"{}\"\n", escape(name), self.section)?;
+
+    write!(writer, ".SH NAME\n")?;
+    let summary = compound.and_then(|c| {
+      c.docs.brief.iter().map(|s| s.trim()).find(|s| !s.is_empty())
+    });
+    match summary {
+      Some(summary) => write!(writer, "{} \\- {}\n", escape(name), convert_inline(summary))?,
+      None if name != title => write!(writer, "{} \\- {}\n", escape(name), escape(title))?,
+      None => write!(writer, "{}\n", escape(name))?,
+    }
+
+    Ok(())
+  }
+
+  fn write_heading(&self, writer: &mut PageBuffer, level: usize, text: &str) -> EmitResult
+  {
+    match level {
+      1 => write!(writer, ".SH \"{}\"\n", escape(&text.to_uppercase()))?,
+      _ => write!(writer, ".SS \"{}\"\n", escape(text))?,
+    }
+
+    Ok(())
+  }
+
+  fn write_paragraph(&self, writer: &mut PageBuffer, text: &str) -> EmitResult
+  {
+    return write_paragraph(writer, text);
+  }
+
+  fn write_code_block(&self, writer: &mut PageBuffer, code: &[u8]) -> EmitResult
+  {
+    write!(writer, ".PP\n.nf\n")?;
+
+    for line in String::from_utf8_lossy(code).trim_end().lines() {
+      write!(writer, "{}\n", escape(line))?;
+    }
+
+    write!(writer, ".fi\n")?;
+
+    Ok(())
+  }
+
+  fn write_callout(&self, writer: &mut PageBuffer, kind: Callout, text: &str) -> EmitResult
+  {
+    let label = match kind {
+      Callout::NOTE => "Note",
+      Callout::WARNING => "Warning",
+    };
+
+    write!(writer, ".PP\n\\fB{}:\\fR\n", label)?;
+    write!(writer, ".RS\n")?;
+    write_paragraph(writer, text)?;
+    write!(writer, ".RE\n")?;
+
+    Ok(())
+  }
+
+  fn write_list(&self, writer: &mut PageBuffer, items: &[String]) -> EmitResult
+  {
+    write!(writer, "{}\n", items.join(",\n"))?;
+    Ok(())
+  }
+
+  fn write_definitions(&self,
+                       writer: &mut PageBuffer,
+                       label: &str,
+                       entries: &[(&str, &str)]) -> EmitResult
+  {
+    write!(writer, ".PP\n\\fB{}\\fR\n", escape(label))?;
+
+    for (name, desc) in entries {
+      write!(writer, ".TP\n\\fI{}\\fR\n{}\n", escape(name), convert_inline(desc))?;
+    }
+
+    Ok(())
+  }
+
+  fn write_field(&self, writer: &mut PageBuffer, label: &str, text: &str) -> EmitResult
+  {
+    write!(writer, ".PP\n\\fB{}:\\fR {}\n", escape(label), convert_inline(text))?;
+    Ok(())
+  }
+
+  /// Returns a reference to another man page, in the conventional "name(section)" form.
+  fn format_link(&self, _text: &str, target: &Compound, _link: &Link, _from_page: &str) -> String
+  {
+    let name = self.renamed.get(target.name.as_str()).map_or(get_page_name(target), String::as_str);
+    return format!("\\fB{}\\fR({})", escape(name), self.section);
+  }
+
+  /// Namespaces don't get pages, since they are mostly of interest for their classes.
+  fn has_page(&self, kind: CompoundKind) -> bool
+  {
+    return matches!(kind, GROUP | CLASS | STRUCT);
+  }
+}
+
+/// Generates a man page for each class, struct, and group, in a "man<section>" subdirectory of
+/// the output directory, so that the output directory can be added to `MANPATH`.
+///
+/// Pages are named after the qualified names of the classes, and the names of the groups.
+pub fn generate_man(output_dir: &Path,
+                    registry: &Registry,
+                    section: &str,
+                    filename_case: FilenameCase) -> D2mResult<()>
+{
+  let start_time = SystemTime::now();
+  info!("Generating man pages...");
+
+  // The section directory is named after the section number, e.g. "man3" for "3cpp"
+  let section_number: String = section.chars().take_while(|c| c.is_ascii_digit()).collect();
+  let directory = output_dir.join(format!("man{}", section_number));
+  fs::create_dir_all(&directory)?;
+
+  let links = LinkResolver::new(registry, &LinkOptions::new());
+  let mut backend = ManBackend { section, renamed: HashMap::new() };
+
+  let mut compounds: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(id, c)| backend.has_page(c.kind) && links.get(id).is_some())
+            .collect();
+  compounds.sort_by_key(|(id, c)| (&c.name, *id));
+
+  // Pages that would overwrite other pages get numbered names instead, e.g. "foo_2"
+  let mut files = FileNames::new(filename_case);
+  let mut pages = Vec::new();
+
+  for (compound_id, compound) in compounds {
+    let name = get_page_name(compound);
+    let file = files.claim(&format!("{}.{}", name, section));
+    let unique_name = file.strip_suffix(&format!(".{}", section)).unwrap().to_owned();
+
+    if unique_name != name {
+      diagnostics::warn(WarningKind::PAGE_COLLISION,
+                        format!("the man page of {} would overwrite another page, using {} \
+                                 instead",
+                                &compound.name,
+                                &file));
+      backend.renamed.insert(compound.name.to_string(), unique_name);
+    }
+
+    pages.push((compound_id, compound, file));
+  }
+
+  for (compound_id, compound, file) in pages {
+    let path = directory.join(file);
+
+    let content = backend::render_compound_page(&backend, registry, &links, compound_id, compound)?;
+    stats::write_file(path, content)?;
+  }
+
+  let end_time = SystemTime::now();
+  info!("Generated man pages in {} ms",
+        end_time.duration_since(start_time).unwrap().as_millis());
+
+  Ok(())
+}
@@ -1,3 +1,41 @@
+pub mod aliases;
+pub mod annotations;
+pub mod archive;
+pub mod autolinks;
+pub mod backend;
+pub mod cache;
+pub mod callouts;
+pub mod compare;
+pub mod config;
+pub mod confluence;
+pub mod diagnostics;
+pub mod dialect;
+pub mod docfx;
+pub mod doxyfile;
 pub mod doxygen;
+pub mod error;
+pub mod filter;
 pub mod generator;
+pub mod hook;
+pub mod linkcheck;
+pub mod links;
+pub mod llms;
+pub mod logging;
+pub mod man;
+pub mod navigation;
+pub mod org;
+pub mod overrides;
 pub mod parser;
+pub mod profile;
+pub mod progress;
+pub mod replacements;
+pub mod sarif;
+pub mod script;
+pub mod signature;
+pub mod sitemap;
+pub mod stats;
+pub mod symbol;
+pub mod tagfile;
+pub mod templates;
+pub mod watch;
+pub mod xml;
@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use log::info;
+use serde_json::{json, Value};
+
+use crate::d2m::backend;
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+use crate::d2m::links::{Index, Link, LinkResolver};
+use crate::d2m::stats;
+
+/// What to do with orphaned compounds, i.e. compounds that aren't featured in the module or
+/// namespace trees.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum OrphanPolicy
+{
+  /// Orphaned compounds are only listed in the flat indexes, e.g. the class index.
+  IGNORE,
+  /// Each orphaned compound is reported as a warning.
+  WARN,
+  /// Orphaned compounds are reported and listed in a "Miscellaneous" section of the module index.
+  LIST,
+}
+
+/// Represents an entry in the navigation structure of the generated pages.
+#[derive(Debug)]
+pub struct NavItem
+{
+  pub title: String,
+  /// The page path, relative to the output directory.
+  pub page: Option<String>,
+  pub children: Vec<NavItem>,
+}
+
+impl NavItem
+{
+  fn page(title: &str, page: &str) -> Self
+  {
+    Self {
+      title: title.to_owned(),
+      page: Some(page.to_owned()),
+      children: Vec::new(),
+    }
+  }
+}
+
+fn create_compound_item(registry: &Registry,
+                        links: &LinkResolver,
+                        compound_id: &RefID,
+                        group_classes: bool,
+                        visited: &mut HashSet<RefID>) -> Option<NavItem>
+{
+  let compound = registry.compounds.get(compound_id)?;
+  let link = links.get(compound_id)?;
+
+  // Guards against malformed input with cyclic relations
+  if !visited.insert(compound_id.to_owned()) {
+    return None;
+  }
+
+  let (title, children) = match compound.kind {
+    GROUP => (compound.title.to_owned(), Some(&compound.groups)),
+    NAMESPACE => (compound.name.split("::").last().unwrap().to_owned(), Some(&compound.namespaces)),
+    _ => (compound.name.to_string(), None),
+  };
+
+  // Titles from the configuration replace the names of classes and namespaces
+  let title = if compound.title.is_empty() { title } else { compound.title.to_owned() };
+
+  let mut item = NavItem {
+    title,
+    page: Some(link.page.to_owned()),
+    children: Vec::new(),
+  };
+
+  for child_id in children.into_iter().flatten() {
+    if let Some(child) = create_compound_item(registry, links, child_id, group_classes, visited) {
+      item.children.push(child);
+    }
+  }
+
+  item.children.sort_by_key(|c| c.title.to_lowercase());
+
+  // Classes are listed after the subgroups
+  if group_classes && compound.kind == GROUP {
+    let mut classes = create_class_items(registry, links, &compound.classes);
+    item.children.append(&mut classes);
+  }
+
+  return Some(item);
+}
+
+/// Creates sorted navigation items for the classes and structs among the specified compounds.
+fn create_class_items<'a>(registry: &Registry,
+                          links: &LinkResolver,
+                          ids: impl IntoIterator<Item = &'a RefID>) -> Vec<NavItem>
+{
+  let mut items = Vec::new();
+
+  for compound_id in ids {
+    if let (Some(compound), Some(link)) = (registry.compounds.get(compound_id),
+                                           links.get(compound_id)) {
+      if compound.kind == CLASS || compound.kind == STRUCT {
+        items.push(NavItem::page(backend::get_title(compound), &link.page));
+      }
+    }
+  }
+
+  items.sort_by_key(|c| c.title.to_lowercase());
+  return items;
+}
+
+/// Creates the navigation items for the top-level compounds of the specified kind.
+fn create_compound_tree(registry: &Registry,
+                        links: &LinkResolver,
+                        kind: CompoundKind,
+                        group_classes: bool) -> Vec<NavItem>
+{
+  let nested: HashSet<&RefID> = registry.compounds
+                                        .values()
+                                        .filter(|c| c.kind == kind)
+                                        .flat_map(|c| c.groups.iter().chain(&c.namespaces))
+                                        .collect();
+
+  let mut visited = HashSet::new();
+  let mut items: Vec<NavItem> = registry.compounds
+                                        .iter()
+                                        .filter(|(id, c)| c.kind == kind && !nested.contains(id))
+                                        .filter_map(|(id, _)| {
+                                          create_compound_item(registry,
+                                                               links,
+                                                               id,
+                                                               group_classes,
+                                                               &mut visited)
+                                        })
+                                        .collect();
+
+  items.sort_by_key(|c| c.title.to_lowercase());
+  return items;
+}
+
+/// Returns the compounds with pages that aren't featured in the module or namespace trees, or on
+/// the pages of featured compounds, sorted by name. For example, a class that is neither in a
+/// group nor in a namespace is only listed in the class index.
+pub fn find_orphans<'a>(registry: &'a Registry, links: &LinkResolver) -> Vec<&'a RefID>
+{
+  let mut trees = create_compound_tree(registry, links, GROUP, true);
+  trees.append(&mut create_compound_tree(registry, links, NAMESPACE, false));
+
+  let mut pages = Vec::new();
+  for item in &trees {
+    collect_page_order(item, &mut pages);
+  }
+
+  let is_featured = |id: &RefID| links.get(id).is_some_and(|l| pages.contains(&l.page.as_str()));
+  let mut featured: HashSet<&RefID> =
+    registry.compounds.keys().filter(|id| is_featured(id)).collect();
+
+  // Nested classes and the classes in namespaces are listed on the pages of their parents
+  let mut pending: Vec<&RefID> = featured.iter().copied().collect();
+  while let Some(compound_id) = pending.pop() {
+    for class_id in &registry.compounds.get(compound_id).unwrap().classes {
+      if registry.compounds.contains_key(class_id) && featured.insert(class_id) {
+        pending.push(class_id);
+      }
+    }
+  }
+
+  let mut orphans: Vec<&RefID> =
+    registry.compounds
+            .iter()
+            .filter(|(_, c)| matches!(c.kind, GROUP | CLASS | STRUCT | NAMESPACE))
+            .filter(|(id, _)| links.get(id).is_some() && !featured.contains(id))
+            .map(|(id, _)| id)
+            .collect();
+  orphans.sort_by_key(|id| &registry.compounds.get(*id).unwrap().name);
+
+  return orphans;
+}
+
+/// Builds the navigation structure of the generated pages, rooted at the main index.
+///
+/// Classes are always listed in the class index. If `group_classes` is true, they are also
+/// listed beneath the groups that they belong to. The `miscellaneous` compounds are listed in a
+/// section at the end of the module tree.
+pub fn build_navigation(registry: &Registry,
+                        links: &LinkResolver,
+                        group_classes: bool,
+                        miscellaneous: &[&RefID]) -> NavItem
+{
+  let index_item = |index: Index| NavItem::page(index.title(), &links.get_index(index).page);
+
+  let mut modules = index_item(Index::MODULES);
+  modules.children = create_compound_tree(registry, links, GROUP, group_classes);
+
+  if !miscellaneous.is_empty() {
+    modules.children.push(NavItem {
+      title: "Miscellaneous".to_owned(),
+      page: None,
+      children: miscellaneous.iter()
+                             .filter_map(|id| Some((registry.compounds.get(*id)?, links.get(id)?)))
+                             .map(|(compound, link)| {
+                               NavItem::page(backend::get_title(compound), &link.page)
+                             })
+                             .collect(),
+    });
+  }
+
+  let mut classes = index_item(Index::CLASSES);
+  classes.children = create_class_items(registry, links, registry.compounds.keys());
+
+  let mut namespaces = index_item(Index::NAMESPACES);
+  namespaces.children = create_compound_tree(registry, links, NAMESPACE, false);
+
+  let mut root = index_item(Index::MAIN);
+  root.children = vec![modules,
+                       classes,
+                       namespaces,
+                       index_item(Index::FUNCTIONS),
+                       index_item(Index::ENUMS),
+                       index_item(Index::MACROS),
+                       index_item(Index::SYMBOLS)];
+
+  if registry.has_deprecated() {
+    root.children.push(index_item(Index::DEPRECATED));
+  }
+
+  return root;
+}
+
+/// Appends the pages of a navigation structure to a list, in depth-first order.
+pub fn collect_page_order<'a>(item: &'a NavItem, result: &mut Vec<&'a str>)
+{
+  if let Some(page) = &item.page {
+    result.push(page);
+  }
+
+  for child in &item.children {
+    collect_page_order(child, result);
+  }
+}
+
+pub fn quote_yaml(text: &str) -> String
+{
+  return format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+fn write_mkdocs_item(output: &mut String, item: &NavItem, prefix: &str, depth: usize)
+{
+  let indent = "  ".repeat(depth);
+
+  if item.children.is_empty() {
+    if let Some(page) = &item.page {
+      output.push_str(&format!("{}- {}: {}\n",
+                               indent,
+                               quote_yaml(&item.title),
+                               quote_yaml(&format!("{}{}", prefix, page))));
+    }
+  } else {
+    output.push_str(&format!("{}- {}:\n", indent, quote_yaml(&item.title)));
+
+    // A section page is listed as an untitled first child, which is what MkDocs themes use for
+    // section index pages
+    if let Some(page) = &item.page {
+      output.push_str(&format!("{}  - {}\n", indent, quote_yaml(&format!("{}{}", prefix, page))));
+    }
+
+    for child in &item.children {
+      write_mkdocs_item(output, child, prefix, depth + 1);
+    }
+  }
+}
+
+/// Writes the navigation structure as a "nav" section for an MkDocs configuration file.
+pub fn emit_mkdocs_nav(output_dir: &Path, root: &NavItem, prefix: &str) -> io::Result<()>
+{
+  info!("Generating MkDocs navigation...");
+
+  let mut output = String::from("nav:\n");
+  write_mkdocs_item(&mut output, root, prefix, 1);
+
+  return stats::write_file(output_dir.join("mkdocs-nav.yml"), output);
+}
+
+fn create_docusaurus_item(item: &NavItem, prefix: &str) -> Option<Value>
+{
+  // Docusaurus identifies documents by their path, without the file extension
+  let id = item.page
+               .as_ref()
+               .map(|page| format!("{}{}", prefix, page.strip_suffix(".md").unwrap_or(page)));
+
+  if item.children.is_empty() {
+    return id.map(|id| json!({ "type": "doc", "id": id, "label": item.title }));
+  }
+
+  let children: Vec<Value> = item.children
+                                 .iter()
+                                 .filter_map(|child| create_docusaurus_item(child, prefix))
+                                 .collect();
+
+  let mut category = json!({ "type": "category", "label": item.title, "items": children });
+  if let Some(id) = id {
+    category["link"] = json!({ "type": "doc", "id": id });
+  }
+
+  return Some(category);
+}
+
+/// Writes the navigation structure as a Docusaurus sidebar, i.e. a JSON array of sidebar items.
+pub fn emit_docusaurus_sidebar(output_dir: &Path, root: &NavItem, prefix: &str)
+  -> io::Result<()>
+{
+  info!("Generating Docusaurus sidebar...");
+
+  // The main index is listed first, followed by the indexes as top-level categories
+  let mut items = vec![json!({
+    "type": "doc",
+    "id": format!("{}index", prefix),
+    "label": root.title,
+  })];
+
+  items.extend(root.children.iter().filter_map(|child| create_docusaurus_item(child, prefix)));
+
+  let output = serde_json::to_string_pretty(&items)?;
+  return stats::write_file(output_dir.join("sidebar.json"), output + "\n");
+}
+
+fn create_vitepress_item(item: &NavItem, prefix: &str, depth: usize) -> Value
+{
+  let mut result = json!({ "text": item.title });
+
+  // VitePress links omit the file extension, and index pages are linked via their directory
+  if let Some(page) = &item.page {
+    let stem = page.strip_suffix(".md").unwrap_or(page);
+    let stem = stem.strip_suffix("index").unwrap_or(stem);
+    result["link"] = json!(format!("/{}{}", prefix, stem));
+  }
+
+  if !item.children.is_empty() {
+    result["collapsed"] = json!(depth > 0);
+    result["items"] = item.children
+                          .iter()
+                          .map(|child| create_vitepress_item(child, prefix, depth + 1))
+                          .collect();
+  }
+
+  return result;
+}
+
+/// Writes the navigation structure as a VitePress sidebar, i.e. a JSON array of sidebar items.
+pub fn emit_vitepress_sidebar(output_dir: &Path, root: &NavItem, prefix: &str) -> io::Result<()>
+{
+  info!("Generating VitePress sidebar...");
+
+  // The indexes are listed as expanded top-level sections, whereas nested sections are collapsed
+  let mut items = vec![json!({ "text": root.title, "link": format!("/{}", prefix) })];
+  items.extend(root.children.iter().map(|child| create_vitepress_item(child, prefix, 0)));
+
+  let output = serde_json::to_string_pretty(&items)?;
+  return stats::write_file(output_dir.join("vitepress-sidebar.json"), output + "\n");
+}
+
+fn escape_mdbook_title(title: &str) -> String
+{
+  return title.replace('[', "\\[").replace(']', "\\]");
+}
+
+fn write_mdbook_item(output: &mut String, item: &NavItem, depth: usize)
+{
+  let title = escape_mdbook_title(&item.title);
+
+  // Entries without pages are rendered as draft chapters
+  output.push_str(&format!("{}- [{}]({})\n",
+                           "  ".repeat(depth),
+                           title,
+                           item.page.as_deref().unwrap_or("")));
+
+  for child in &item.children {
+    write_mdbook_item(output, child, depth + 1);
+  }
+}
+
+/// Writes the navigation structure as an mdBook summary, along with a minimal book configuration.
+///
+/// The output directory is used as the source directory of the book, so an existing "book.toml"
+/// file is left as is.
+pub fn emit_mdbook_summary(output_dir: &Path, root: &NavItem) -> io::Result<()>
+{
+  info!("Generating mdBook summary...");
+
+  let mut output = String::from("# Summary\n\n");
+
+  // The main index is a prefix chapter, which is not numbered
+  if let Some(page) = &root.page {
+    output.push_str(&format!("[{}]({})\n\n", escape_mdbook_title(&root.title), page));
+  }
+
+  for child in &root.children {
+    write_mdbook_item(&mut output, child, 0);
+  }
+
+  stats::write_file(output_dir.join("SUMMARY.md"), output)?;
+
+  let book_file = output_dir.join("book.toml");
+  if !book_file.exists() {
+    let title = toml::Value::from(root.title.as_str());
+    stats::write_file(book_file, format!("[book]\ntitle = {}\nsrc = \".\"\n", title))?;
+  }
+
+  Ok(())
+}
+
+fn write_wiki_item(output: &mut String, item: &NavItem, links: &LinkResolver, depth: usize)
+{
+  let indent = "  ".repeat(depth);
+
+  match &item.page {
+    Some(page) => {
+      let url = links.url_from(&Link::page(page.to_owned()), "");
+      output.push_str(&format!("{}* [{}]({})\n", indent, item.title, url));
+    }
+    None => output.push_str(&format!("{}* {}\n", indent, item.title)),
+  }
+
+  for child in &item.children {
+    write_wiki_item(output, child, links, depth + 1);
+  }
+}
+
+/// Writes the navigation structure as a GitHub wiki sidebar, called "_Sidebar.md".
+pub fn emit_wiki_sidebar(output_dir: &Path, root: &NavItem, links: &LinkResolver)
+  -> io::Result<()>
+{
+  info!("Generating wiki sidebar...");
+
+  let mut output = String::new();
+  write_wiki_item(&mut output, root, links, 0);
+
+  return stats::write_file(output_dir.join("_Sidebar.md"), output);
+}
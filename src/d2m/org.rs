@@ -0,0 +1,189 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use log::info;
+
+use crate::d2m::backend::{self, Callout, OutputBackend};
+use crate::d2m::doxygen::*;
+use crate::d2m::error::D2mResult;
+use crate::d2m::generator::{EmitResult, PageBuffer};
+use crate::d2m::links::{Link, LinkOptions, LinkResolver};
+use crate::d2m::stats;
+
+/// Returns the path of the Org file of a page, relative to the output directory.
+fn get_org_file(page: &str) -> String
+{
+  return format!("{}.org", page.strip_suffix(".md").unwrap_or(page));
+}
+
+/// Converts the Markdown produced for inline code spans and lists in comments to Org markup.
+fn convert_text(text: &str) -> String
+{
+  let mut result = String::new();
+
+  for line in text.lines() {
+    // Lines that start with asterisks are headings in Org
+    let line = match line.trim_start().strip_prefix("* ") {
+      Some(item) => format!("- {}", item),
+      None => line.to_owned(),
+    };
+
+    for (index, part) in line.split('`').enumerate() {
+      if index % 2 == 1 {
+        result += &format!("~{}~", part.trim());
+      } else {
+        result += part;
+      }
+    }
+
+    result.push('\n');
+  }
+
+  return result;
+}
+
+struct OrgBackend<'a>
+{
+  links: &'a LinkResolver,
+}
+
+impl OutputBackend for OrgBackend<'_>
+{
+  fn begin_page(&self, writer: &mut PageBuffer, title: &str, _compound: Option<&Compound>)
+    -> EmitResult
+  {
+    write!(writer, "#+TITLE: {}\n", title)?;
+    Ok(())
+  }
+
+  fn write_heading(&self, writer: &mut PageBuffer, level: usize, text: &str) -> EmitResult
+  {
+    write!(writer, "\n{} {}\n", "*".repeat(level), text)?;
+    Ok(())
+  }
+
+  fn write_paragraph(&self, writer: &mut PageBuffer, text: &str) -> EmitResult
+  {
+    write!(writer, "\n{}", convert_text(text))?;
+    Ok(())
+  }
+
+  fn write_code_block(&self, writer: &mut PageBuffer, code: &[u8]) -> EmitResult
+  {
+    write!(writer, "\n#+BEGIN_SRC cpp\n")?;
+    writer.extend_from_slice(code);
+    write!(writer, "#+END_SRC\n")?;
+
+    Ok(())
+  }
+
+  fn write_callout(&self, writer: &mut PageBuffer, kind: Callout, text: &str) -> EmitResult
+  {
+    let block = match kind {
+      Callout::NOTE => "NOTE",
+      Callout::WARNING => "WARNING",
+    };
+
+    write!(writer, "\n#+BEGIN_{}\n{}#+END_{}\n", block, convert_text(text), block)?;
+    Ok(())
+  }
+
+  fn write_list(&self, writer: &mut PageBuffer, items: &[String]) -> EmitResult
+  {
+    write!(writer, "\n")?;
+
+    for item in items {
+      write!(writer, "- {}\n", item)?;
+    }
+
+    Ok(())
+  }
+
+  fn write_definitions(&self,
+                       writer: &mut PageBuffer,
+                       label: &str,
+                       entries: &[(&str, &str)]) -> EmitResult
+  {
+    write!(writer, "\n*{}*\n\n", label)?;
+
+    for (name, desc) in entries {
+      write!(writer, "- ={}= {}", name, convert_text(desc))?;
+    }
+
+    Ok(())
+  }
+
+  fn write_field(&self, writer: &mut PageBuffer, label: &str, text: &str) -> EmitResult
+  {
+    write!(writer, "\n*{}:* {}", label, convert_text(text))?;
+    Ok(())
+  }
+
+  fn format_link(&self, text: &str, _target: &Compound, link: &Link, from_page: &str) -> String
+  {
+    let page = Link::page(link.page.to_owned());
+
+    let mut target = if link.page == from_page {
+      String::new()
+    } else {
+      format!("file:{}", get_org_file(&self.links.url_from(&page, from_page)))
+    };
+
+    if let Some(heading) = &link.heading {
+      if !target.is_empty() {
+        target += "::";
+      }
+
+      target += &format!("*{}", heading);
+    }
+
+    return format!("[[{}][{}]]", target, text);
+  }
+}
+
+fn write_file(output_dir: &Path, file: &str, content: &[u8]) -> EmitResult
+{
+  let path = output_dir.join(file);
+  if let Some(directory) = path.parent() {
+    fs::create_dir_all(directory)?;
+  }
+
+  return stats::write_file(path, content);
+}
+
+/// Generates an Org file for each group, class, and namespace, along with an index file.
+pub fn generate_org(output_dir: &Path, registry: &Registry) -> D2mResult<()>
+{
+  let start_time = SystemTime::now();
+  info!("Generating Org output...");
+
+  let links = LinkResolver::new(registry, &LinkOptions::new());
+  let backend = OrgBackend { links: &links };
+
+  let mut compounds: Vec<(&RefID, &Compound)> =
+    registry.compounds
+            .iter()
+            .filter(|(id, c)| backend.has_page(c.kind) && links.get(id).is_some())
+            .collect();
+  compounds.sort_by_key(|(_, c)| backend::get_title(c));
+
+  let index_page = "index.md";
+  let ids: Vec<RefID> = compounds.iter().map(|(id, _)| (*id).clone()).collect();
+  let index = backend::render_index_page(&backend, registry, &links, index_page, "API", &ids)?;
+  write_file(output_dir, &get_org_file(index_page), &index)?;
+
+  for (compound_id, compound) in &compounds {
+    let file = get_org_file(&links.get(compound_id).unwrap().page);
+
+    let content = backend::render_compound_page(&backend, registry, &links, compound_id, compound)?;
+    write_file(output_dir, &file, &content)?;
+  }
+
+  let end_time = SystemTime::now();
+  info!("Generated Org files in {} ms",
+        end_time.duration_since(start_time).unwrap().as_millis());
+
+  Ok(())
+}
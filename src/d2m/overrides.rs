@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path};
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::d2m::diagnostics;
+use crate::d2m::doxygen::CompoundKind::*;
+use crate::d2m::doxygen::*;
+
+/// Attributes of a compound that replace the ones from the XML files, see the "compounds"
+/// configuration tables.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CompoundOverride
+{
+  /// The title of the page, e.g. "Window" instead of "cen::basic_window".
+  pub title: Option<String>,
+  /// The path of the page, relative to the output directory, e.g. "classes/window.md".
+  pub filename: Option<String>,
+  /// The name of the group that the compound is featured in, instead of its groups in the XML.
+  pub group: Option<String>,
+  /// Whether to leave out the compound, along with its members.
+  pub exclude: bool,
+}
+
+impl CompoundOverride
+{
+  pub fn validate(&self, name: &str) -> Result<(), String>
+  {
+    if let Some(filename) = &self.filename {
+      let is_relative = Path::new(filename).components().all(|c| matches!(c, Component::Normal(_)));
+
+      if filename.is_empty() || !is_relative {
+        return Err(format!("the filename of '{}' must be a path within the output directory",
+                           name));
+      }
+    }
+
+    return Ok(());
+  }
+}
+
+/// Returns the list of a group that features compounds of the specified kind, if any.
+fn get_group_list(group: &mut Compound, kind: CompoundKind) -> Option<&mut Vec<RefID>>
+{
+  return match kind {
+    CLASS | STRUCT | INTERFACE | CONCEPT => Some(&mut group.classes),
+    NAMESPACE => Some(&mut group.namespaces),
+    GROUP => Some(&mut group.groups),
+    _ => None,
+  };
+}
+
+/// Moves a compound to a group, removing it from the other groups.
+fn place_in_group(registry: &mut Registry, id: RefID, kind: CompoundKind, group_name: &str)
+{
+  let group_id = registry.compounds
+                         .iter()
+                         .find(|(_, c)| c.kind == GROUP && c.name == group_name)
+                         .map(|(group_id, _)| group_id.clone());
+
+  let group_id = match group_id {
+    Some(group_id) if group_id != id => group_id,
+    _ => {
+      warn!("Cannot place {} in unknown group '{}'", id, group_name);
+      return;
+    }
+  };
+
+  for group in registry.compounds.values_mut().filter(|c| c.kind == GROUP) {
+    if let Some(list) = get_group_list(group, kind) {
+      list.retain(|existing| *existing != id);
+    }
+  }
+
+  match get_group_list(registry.compounds.get_mut(&group_id).unwrap(), kind) {
+    Some(list) => list.push(id),
+    None => warn!("Cannot place {} in a group, since it is a {}", id, kind.name()),
+  }
+}
+
+/// Applies the overrides of compounds, which are looked up by qualified name, or by name for
+/// groups. Returns the paths of the pages that replace the ones from the layout.
+pub fn apply_overrides(registry: &mut Registry, overrides: &BTreeMap<String, CompoundOverride>)
+  -> HashMap<RefID, String>
+{
+  let mut page_paths = HashMap::new();
+  let mut excluded = HashSet::new();
+
+  for (name, entry) in overrides {
+    let compound = registry.compounds
+                           .iter_mut()
+                           .filter(|(_, c)| !matches!(c.kind, FILE | DIRECTORY))
+                           .find(|(_, c)| c.name == name.as_str());
+
+    let (id, compound) = match compound {
+      Some((id, compound)) => (id.clone(), compound),
+      None => {
+        warn!("Cannot override unknown compound '{}'", name);
+        continue;
+      }
+    };
+
+    if entry.exclude {
+      excluded.insert(id);
+      continue;
+    }
+
+    if let Some(title) = &entry.title {
+      compound.title = title.to_owned();
+    }
+
+    if let Some(filename) = &entry.filename {
+      page_paths.insert(id.clone(), filename.replace('\\', "/"));
+    }
+
+    if let Some(group) = &entry.group {
+      let kind = compound.kind;
+      place_in_group(registry, id, kind, group);
+    }
+  }
+
+  if !excluded.is_empty() {
+    info!("Excluding {} compound(s) as configured", excluded.len());
+
+    let orphans = registry.remove_compounds(&excluded);
+    diagnostics::exclude(excluded.into_iter().chain(orphans));
+  }
+
+  return page_paths;
+}
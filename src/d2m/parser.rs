@@ -1,23 +1,61 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::iter;
-use std::path::PathBuf;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
+use log::{debug, error, info, trace};
 use minidom::Element;
 use minidom::NSChoice::Any as AnyNS;
 use lazy_static::lazy_static;
+use rayon::prelude::*;
 use regex::Regex;
 
+use crate::d2m::cache::{self, Cache};
+use crate::d2m::diagnostics::{self, WarningKind};
 use crate::d2m::doxygen::CompoundKind::*;
 use crate::d2m::doxygen::*;
+use crate::d2m::error::{D2mError, D2mResult};
+use crate::d2m::filter::FileFilter;
+use crate::d2m::profile;
+use crate::d2m::progress;
+use crate::d2m::tagfile;
+use crate::d2m::xml;
+
+/// Returns the value of an attribute that Doxygen always emits.
+fn get_attribute<'a>(elem: &'a Element, attribute: &'static str) -> D2mResult<&'a str>
+{
+  return elem.attr(attribute).ok_or_else(|| D2mError::MISSING_ATTRIBUTE {
+                                 element: elem.name().to_owned(),
+                                 attribute,
+                               });
+}
 
-fn parse_xml_file(path: &PathBuf) -> Element
+/// Parses the value of an attribute that Doxygen always emits, e.g. an access modifier.
+fn parse_attribute<T: FromStr>(elem: &Element, attribute: &'static str) -> D2mResult<T>
 {
-  let raw_contents = fs::read_to_string(&path).unwrap();
-  let root_element: minidom::Element = raw_contents.parse().unwrap();
-  return root_element;
+  let value = get_attribute(elem, attribute)?;
+  return T::from_str(value).map_err(|_| D2mError::INVALID_ATTRIBUTE {
+                              element: elem.name().to_owned(),
+                              attribute,
+                              value: value.to_owned(),
+                            });
+}
+
+/// Indicates whether a "yes" or "no" attribute that Doxygen always emits is set.
+fn is_set(elem: &Element, attribute: &'static str) -> D2mResult<bool>
+{
+  return Ok(get_attribute(elem, attribute)? == "yes");
+}
+
+/// Returns a child element that Doxygen always emits.
+fn get_child<'a>(elem: &'a Element, child: &'static str) -> D2mResult<&'a Element>
+{
+  return elem.get_child(child, AnyNS).ok_or_else(|| D2mError::MISSING_CHILD {
+                                         element: elem.name().to_owned(),
+                                         child,
+                                       });
 }
 
 fn parse_text(root: &Element) -> String
@@ -37,13 +75,23 @@ fn parse_text(root: &Element) -> String
         "itemizedlist" => content += format!("\n{}", parse_text(elem)).as_str(),
         "listitem" => content += format!("* {}\n", parse_text(elem)).as_str(),
         "ref" => {
-          // TODO use
-          let _referenced_id = elem.attr("refid").unwrap();
-          let _referenced_kind = elem.attr("kindref").unwrap();
-          // content += format!("[{}](foo.md)", parse_text(elem)).as_str();
-          content += format!("{}", parse_text(elem)).as_str();
+          let referenced_id = elem.attr("refid").unwrap_or_default();
+
+          // References to entities in tag files are linked to the external documentation
+          match elem.attr("external").and_then(|tag| tagfile::resolve(tag, referenced_id)) {
+            Some(url) => content += format!("[{}]({})", parse_text(elem), url).as_str(),
+            None => content += parse_text(elem).as_str(),
+          }
+
+          if elem.attr("external").is_none() && !referenced_id.is_empty() {
+            diagnostics::reference(referenced_id);
+          }
         }
-        _ => ()
+        "programlisting" => content += format!("\n```C++\n{}\n```\n", parse_code(elem)).as_str(),
+        // These are handled separately by parse_comment
+        "parameterlist" | "simplesect" => (),
+        "xrefsect" if is_deprecated_section(elem) => (),
+        tag => diagnostics::ignore("text element with tag", tag),
       }
     }
   }
@@ -51,27 +99,85 @@ fn parse_text(root: &Element) -> String
   return content;
 }
 
-fn parse_parameter_list(elem: &Element) -> HashMap<String, String>
+/// Returns the code of a program listing, e.g. from "\code" or an example file.
+fn parse_code(listing: &Element) -> String
+{
+  let lines: Vec<String> = listing.children()
+                                  .filter(|e| e.is("codeline", AnyNS))
+                                  .map(parse_code_line)
+                                  .collect();
+  return lines.join("\n");
+}
+
+fn parse_code_line(elem: &Element) -> String
+{
+  let mut line = String::new();
+
+  for node in elem.nodes() {
+    if let Some(text) = node.as_text() {
+      line += text;
+    } else if let Some(child) = node.as_element() {
+      match child.name() {
+        "sp" => line.push(' '),
+        _ => line += parse_code_line(child).as_str(),
+      }
+    }
+  }
+
+  return line;
+}
+
+/// Indicates whether a paragraph section stems from a "\par Example" command.
+fn is_example_section(elem: &Element) -> bool
+{
+  return elem.get_child("title", AnyNS)
+             .is_some_and(|title| parse_text(title).to_lowercase().starts_with("example"));
+}
+
+/// Collects the example files that are referenced in an element and its descendants.
+fn collect_example_refs(elem: &Element, refs: &mut Vec<RefID>)
+{
+  for child in elem.children() {
+    let example = child.attr("refid").filter(|id| id.ends_with("-example"));
+
+    match example {
+      Some(id) if child.is("ref", AnyNS) => {
+        if !refs.iter().any(|existing| existing == id) {
+          refs.push(RefID::from(id));
+        }
+      }
+      _ => collect_example_refs(child, refs),
+    }
+  }
+}
+
+/// Indicates whether a cross-reference section stems from a "\deprecated" command.
+fn is_deprecated_section(elem: &Element) -> bool
+{
+  return elem.attr("id").is_some_and(|id| id.starts_with("deprecated"));
+}
+
+fn parse_parameter_list(elem: &Element) -> D2mResult<HashMap<String, String>>
 {
   assert_eq!(elem.name(), "parameterlist");
   let mut entries = HashMap::new();
 
   for item in elem.children().filter(|e| e.is("parameteritem", AnyNS)) {
-    let list = item.get_child("parameternamelist", AnyNS).unwrap();
+    let list = get_child(item, "parameternamelist")?;
 
-    let name_elem = list.get_child("parametername", AnyNS).unwrap();
+    let name_elem = get_child(list, "parametername")?;
     let name = parse_text(name_elem);
 
-    let desc_elem = item.get_child("parameterdescription", AnyNS).unwrap();
+    let desc_elem = get_child(item, "parameterdescription")?;
     let desc = parse_text(desc_elem);
 
     entries.insert(name, desc);
   }
 
-  return entries;
+  return Ok(entries);
 }
 
-fn parse_comment(elem: &Element) -> Comment
+fn parse_comment(elem: &Element) -> D2mResult<Comment>
 {
   let mut comment = Comment::new();
 
@@ -87,27 +193,29 @@ fn parse_comment(elem: &Element) -> Comment
         "para" => {
           // The parse_text function ignores parameter lists and sections
           comment.details.push(parse_text(child));
+          collect_example_refs(child, &mut comment.example_refs);
 
           if let Some(parameter_list) = child.get_child("parameterlist", AnyNS) {
-            match parameter_list.attr("kind").unwrap() {
-              "param" => {
-                assert!(comment.parameters.is_empty());
-                comment.parameters = parse_parameter_list(parameter_list);
-              }
-              "exception" => {
-                assert!(comment.exceptions.is_empty());
-                comment.exceptions = parse_parameter_list(parameter_list);
-              }
+            // Lists of the same kind may be split across several paragraphs
+            match get_attribute(parameter_list, "kind")? {
+              "param" => comment.parameters.extend(parse_parameter_list(parameter_list)?),
+              "exception" => comment.exceptions.extend(parse_parameter_list(parameter_list)?),
               "templateparam" => {
-                assert!(comment.template_parameters.is_empty());
-                comment.template_parameters = parse_parameter_list(parameter_list);
+                comment.template_parameters.extend(parse_parameter_list(parameter_list)?);
               }
-              kind => println!("Ignoring parameter list of type '{}'", kind)
+              kind => diagnostics::ignore("parameter list of type", kind),
+            }
+          }
+
+          for section in child.children().filter(|c| c.is("xrefsect", AnyNS)) {
+            if is_deprecated_section(section) {
+              let note = section.get_child("xrefdescription", AnyNS).map(parse_text);
+              comment.deprecated = Some(note.unwrap_or_default());
             }
           }
 
           for simple_section in child.children().filter(|c| c.is("simplesect", AnyNS)) {
-            match simple_section.attr("kind").unwrap() {
+            match get_attribute(simple_section, "kind")? {
               "return" => {
                 if let Some(para) = simple_section.get_child("para", AnyNS) {
                   comment.returns = parse_text(para);
@@ -128,6 +236,16 @@ fn parse_comment(elem: &Element) -> Comment
                   comment.warnings.push(parse_text(para));
                 }
               }
+              "attention" => {
+                if let Some(para) = simple_section.get_child("para", AnyNS) {
+                  comment.attentions.push(parse_text(para));
+                }
+              }
+              "invariant" => {
+                if let Some(para) = simple_section.get_child("para", AnyNS) {
+                  comment.invariants.push(parse_text(para));
+                }
+              }
               "pre" => {
                 if let Some(para) = simple_section.get_child("para", AnyNS) {
                   comment.pre_conditions.push(parse_text(para));
@@ -138,28 +256,61 @@ fn parse_comment(elem: &Element) -> Comment
                   comment.post_conditions.push(parse_text(para));
                 }
               }
-              kind => println!("Ignoring simple section of type '{}'", kind),
+              "par" if is_example_section(simple_section) => {
+                let paragraphs: Vec<String> = simple_section.children()
+                                                            .filter(|c| c.is("para", AnyNS))
+                                                            .map(parse_text)
+                                                            .collect();
+                comment.examples.push(paragraphs.join("\n\n").trim().to_owned());
+              }
+              kind => diagnostics::ignore("simple section of type", kind),
             }
           }
         }
-        tag => println!("Ignoring child of detailed description with tag '{}'", tag)
+        tag => diagnostics::ignore("child of detailed description with tag", tag),
       }
     }
   }
 
-  return comment;
+  return Ok(comment);
 }
 
-fn parse_template_args(elem: &Element) -> Vec<String>
+/// Returns the text of a type, including the names that Doxygen wraps in references, e.g.
+/// "const point &" for "const <ref>point</ref> &". The referenced entities are added to `refs`,
+/// except for entities in tag files.
+fn parse_type(elem: &Element, refs: &mut Vec<(Symbol, RefID)>) -> String
+{
+  let mut text = String::new();
+
+  for node in elem.nodes() {
+    if let Some(content) = node.as_text() {
+      text += content;
+    } else if let Some(child) = node.as_element() {
+      let content = parse_type(child, refs);
+
+      if child.name() == "ref" && child.attr("external").is_none() {
+        if let Some(referenced_id) = child.attr("refid") {
+          refs.push((Symbol::from(content.as_str()), RefID::from(referenced_id)));
+        }
+      }
+
+      text += &content;
+    }
+  }
+
+  return text;
+}
+
+fn parse_template_args(elem: &Element, refs: &mut Vec<(Symbol, RefID)>) -> D2mResult<Vec<Symbol>>
 {
   let mut args = Vec::new();
 
   for param in elem.children().filter(|e| e.is("param", AnyNS)) {
-    let type_elem = param.get_child("type", AnyNS).unwrap();
-    args.push(type_elem.text());
+    let type_elem = get_child(param, "type")?;
+    args.push(Symbol::from(parse_type(type_elem, refs)));
   }
 
-  return args;
+  return Ok(args);
 }
 
 fn remove_redundant_const_from_function_parameters(func: &mut Function)
@@ -168,33 +319,27 @@ fn remove_redundant_const_from_function_parameters(func: &mut Function)
     return;  // No need to process functions with zero arguments
   }
 
-  let uses_trailing_return = func.return_type == "auto";
+  // Functions with deduced return types are also declared as "auto", but without an arrow
+  let trailing_return = match func.args.split_once("->") {
+    Some(split) if func.return_type == "auto" => Some(split),
+    _ => None,
+  };
 
-  let (head, tail) = if uses_trailing_return {
-    let mut separated = func.args.split("->");
-    (separated.next().unwrap().to_owned(), separated.next().unwrap().to_owned())
-  } else {
-    (func.args.to_owned(), String::new())
+  let uses_trailing_return = trailing_return.is_some();
+  let (head, tail) = match trailing_return {
+    Some((head, tail)) => (head.to_owned(), tail.to_owned()),
+    None => (func.args.to_owned(), String::new()),
   };
 
   let mut new_args = String::with_capacity(func.args.len());
   let mut first = true;
 
-  let align_offset = func.return_type.len()
-      + func.name.len()
-      + if func.is_static { 7 } else { 0 }
-      + if func.is_explicit { 9 } else { 0 }
-      + if func.return_type.is_empty() { 0 } else { 1 };
-
+  // The parameters are laid out when the signature is generated, see signature.rs
   for arg in head.split(",").filter(|s| !s.is_empty()) {
     let is_pointer = arg.contains("*") || arg.contains("&");
 
     if !first {
       new_args += ",";
-      if !arg.contains("<") && !arg.contains(">") {
-        new_args += "\n";
-        new_args += iter::repeat(" ").take(align_offset).collect::<String>().as_str();
-      }
     }
 
     if !is_pointer && arg.contains("const") {
@@ -225,34 +370,51 @@ fn simplify_function_noexcept_specifier(func: &mut Function)
   }
 }
 
-fn parse_function_definition(elem: &Element, func: &mut Function)
+/// Returns the source location of an entity, which Doxygen omits for some compounds.
+fn parse_location(elem: &Element) -> Option<Location>
+{
+  let location = elem.get_child("location", AnyNS)?;
+
+  return Some(Location {
+    file: location.attr("file")?.to_owned(),
+    line: location.attr("line").and_then(|line| line.parse().ok()),
+  });
+}
+
+fn parse_function_definition(elem: &Element, func: &mut Function) -> D2mResult<()>
 {
-  func.access = AccessModifier::from_str(elem.attr("prot").unwrap()).unwrap();
+  func.access = parse_attribute(elem, "prot")?;
 
-  func.is_static = elem.attr("static").unwrap() == "yes";
-  func.is_const = elem.attr("const").unwrap() == "yes";
-  func.is_explicit = elem.attr("explicit").unwrap() == "yes";
-  func.is_inline = elem.attr("inline").unwrap() == "yes";
-  func.is_virtual = elem.attr("virt").unwrap() != "non-virtual";
+  func.is_static = is_set(elem, "static")?;
+  func.is_const = is_set(elem, "const")?;
+  func.is_explicit = is_set(elem, "explicit")?;
+  func.is_inline = is_set(elem, "inline")?;
+  func.is_virtual = get_attribute(elem, "virt")? != "non-virtual";
   func.is_noexcept = elem.attr("const").unwrap_or("no") == "yes";
 
-  func.name = elem.get_child("name", AnyNS).unwrap().text();
-  func.definition = elem.get_child("definition", AnyNS).unwrap().text();
-  func.return_type = elem.get_child("type", AnyNS).unwrap().text();
-  func.args = elem.get_child("argsstring", AnyNS).unwrap().text();
+  func.name = Symbol::from(get_child(elem, "name")?.text());
+  func.definition = get_child(elem, "definition")?.text();
+  func.type_refs.clear();
+  func.return_type = Symbol::from(parse_type(get_child(elem, "type")?, &mut func.type_refs));
+  func.args = get_child(elem, "argsstring")?.text();
 
   if let Some(qname) = elem.get_child("qualifiedname", AnyNS) {
-    func.qualified_name = qname.text();
+    func.qualified_name = Symbol::from(qname.text());
   }
 
   if let Some(args) = elem.get_child("templateparamlist", AnyNS) {
-    func.template_args = parse_template_args(args);
+    func.template_args = parse_template_args(args, &mut func.type_refs)?;
   }
 
   // Parse parameter names, even if they may be undocumented
   for child in elem.children().filter(|e| e.is("param", AnyNS)) {
+    // The argument string is plain text, so references are only found in the parameter types
+    if let Some(type_elem) = child.get_child("type", AnyNS) {
+      parse_type(type_elem, &mut func.type_refs);
+    }
+
     if let Some(decl_name) = child.get_child("declname", AnyNS) {
-      let name = decl_name.text();
+      let name = Symbol::from(decl_name.text());
 
       // Information is occasionally duplicated, such as in namespace and group files
       if !func.parameter_names.contains(&name) {
@@ -261,162 +423,311 @@ fn parse_function_definition(elem: &Element, func: &mut Function)
     }
   }
 
-  func.docs = parse_comment(&elem);
+  func.docs = parse_comment(elem)?;
+  func.location = parse_location(elem);
+
+  // Functions can also be deprecated with the attribute, which is part of the definition
+  if func.docs.deprecated.is_none() && func.definition.contains("[[deprecated") {
+    func.docs.deprecated = Some(String::new());
+  }
 
   remove_redundant_const_from_function_parameters(func);
   simplify_function_noexcept_specifier(func);
+
+  return Ok(());
 }
 
-fn parse_variable_definition(elem: &Element, var: &mut Variable)
+fn parse_variable_definition(elem: &Element, var: &mut Variable) -> D2mResult<()>
 {
-  var.access = AccessModifier::from_str(elem.attr("prot").unwrap()).unwrap();
+  var.access = parse_attribute(elem, "prot")?;
 
-  var.is_static = elem.attr("static").unwrap() == "yes";
-  var.is_mutable = elem.attr("mutable").unwrap() == "yes";
+  var.is_static = is_set(elem, "static")?;
+  var.is_mutable = is_set(elem, "mutable")?;
   var.is_constexpr = elem.attr("constexpr").unwrap_or("no") == "yes";
 
-  var.name = elem.get_child("name", AnyNS).unwrap().text();
-  var.qualified_name = elem.get_child("qualifiedname", AnyNS).unwrap().text();
-  var.definition = elem.get_child("definition", AnyNS).unwrap().text();
+  var.name = Symbol::from(get_child(elem, "name")?.text());
+  var.qualified_name = Symbol::from(get_child(elem, "qualifiedname")?.text());
+  var.definition = get_child(elem, "definition")?.text();
+
+  if let Some(initializer) = elem.get_child("initializer", AnyNS) {
+    var.initializer = initializer.text().trim_start_matches('=').trim().to_owned();
+  }
+
+  var.docs = parse_comment(elem)?;
+  var.location = parse_location(elem);
 
-  var.docs = parse_comment(elem);
+  return Ok(());
 }
 
-fn parse_enum_definition(elem: &Element, e: &mut Enum)
+fn parse_enum_definition(elem: &Element, e: &mut Enum) -> D2mResult<()>
 {
-  e.name = elem.get_child("name", AnyNS).unwrap().text();
-  e.qualified_name = elem.get_child("qualifiedname", AnyNS).unwrap().text();
-  e.is_scoped = elem.attr("strong").unwrap() == "yes";
+  e.name = Symbol::from(get_child(elem, "name")?.text());
+  e.qualified_name = Symbol::from(get_child(elem, "qualifiedname")?.text());
+  e.is_scoped = is_set(elem, "strong")?;
 
-  e.docs = parse_comment(elem);
+  e.docs = parse_comment(elem)?;
+  e.location = parse_location(elem);
+
+  // Enums may be defined in several compounds, e.g. both a namespace and a group
+  e.values.clear();
 
   for value_elem in elem.children().filter(|c| c.is("enumvalue", AnyNS)) {
     let mut value = EnumValue::new();
 
-    value.name = value_elem.get_child("name", AnyNS).unwrap().text();
+    value.name = Symbol::from(get_child(value_elem, "name")?.text());
 
     if let Some(initializer) = value_elem.get_child("initializer", AnyNS) {
       value.initializer = initializer.text().replace("= ", "");
     }
 
-    value.docs = parse_comment(value_elem);
+    value.docs = parse_comment(value_elem)?;
 
     e.values.push(value);
   }
+
+  return Ok(());
 }
 
-fn parse_compound_definition(element: &Element, registry: &mut Registry)
+fn parse_define_definition(elem: &Element, define: &mut Define) -> D2mResult<()>
 {
-  let kind = element.attr("kind").unwrap();
+  define.name = Symbol::from(get_child(elem, "name")?.text());
 
-  if kind == "file" || kind == "namespace" {
-    return;
+  // Function-like macros feature param elements, even if they have no parameters
+  define.parameters.clear();
+  for param in elem.children().filter(|e| e.is("param", AnyNS)) {
+    define.is_function_like = true;
+
+    if let Some(name) = param.get_child("defname", AnyNS) {
+      define.parameters.push(name.text());
+    }
   }
 
-  let compound_id = element.attr("id").unwrap();
-  let mut compound = registry.compounds.get_mut(compound_id).unwrap();
+  if let Some(initializer) = elem.get_child("initializer", AnyNS) {
+    define.initializer = initializer.text();
+  }
+
+  define.docs = parse_comment(elem)?;
+  define.location = parse_location(elem);
+
+  return Ok(());
+}
 
-  compound.docs = parse_comment(element);
+/// Returns a declared entity, which is expected to be declared before it is defined.
+fn get_declared<'a, T>(entities: &'a mut HashMap<RefID, T>, id: &str) -> D2mResult<&'a mut T>
+{
+  let id = RefID::from(id);
+  return entities.get_mut(&id).ok_or(D2mError::UNDECLARED(id));
+}
+
+fn parse_compound_definition(element: &Element, registry: &mut Registry) -> D2mResult<()>
+{
+  let compound_id = get_attribute(element, "id")?;
+  let compound = get_declared(&mut registry.compounds, compound_id)?;
+
+  compound.docs = parse_comment(element)?;
+  compound.location = parse_location(element);
 
   for elem in element.children() {
     match elem.name() {
       "title" => compound.title = parse_text(elem),
       "innergroup" => {
         if let Some(id) = elem.attr("refid") {
-          compound.groups.push(id.to_owned());
+          compound.groups.push(RefID::from(id));
         }
       }
       "innerclass" => {
         if let Some(id) = elem.attr("refid") {
-          compound.classes.push(id.to_owned());
+          compound.classes.push(RefID::from(id));
         }
       }
       "innernamespace" => {
         if let Some(id) = elem.attr("refid") {
-          compound.namespaces.push(id.to_owned());
+          compound.namespaces.push(RefID::from(id));
         }
       }
       "sectiondef" => {
+        // Functions that are related to classes are listed in separate sections of the classes
+        let is_related = elem.attr("kind") == Some("related");
+
         for member in elem.children().filter(|e| e.is("memberdef", AnyNS)) {
-          let member_id: RefID = member.attr("id").unwrap().to_owned();
+          let member_id = get_attribute(member, "id")?;
+          let kind = get_attribute(member, "kind")?;
+          trace!("Parsing {} {}", kind, member_id);
 
-          match member.attr("kind").unwrap() {
+          match kind {
             "function" => {
-              let mut func = registry.functions.get_mut(&member_id).unwrap();
-              parse_function_definition(member, &mut func);
+              let func = get_declared(&mut registry.functions, member_id)?;
+              parse_function_definition(member, func)?;
+
+              if is_related {
+                func.is_member = false;
+                func.is_related = true;
+              }
             }
             "variable" => {
-              let mut var = registry.variables.get_mut(&member_id).unwrap();
-              parse_variable_definition(member, &mut var);
+              let var = get_declared(&mut registry.variables, member_id)?;
+              parse_variable_definition(member, var)?;
             }
             "enum" => {
-              let mut e = registry.enums.get_mut(&member_id).unwrap();
-              parse_enum_definition(member, &mut e);
+              let e = get_declared(&mut registry.enums, member_id)?;
+              parse_enum_definition(member, e)?;
+            }
+            "define" => {
+              let define = get_declared(&mut registry.defines, member_id)?;
+              parse_define_definition(member, define)?;
             }
             _ => ()
           }
         }
       }
       "templateparamlist" => {
-        if let Some(class) = registry.classes.get_mut(compound_id) {
-          class.template_args = parse_template_args(elem);
+        if let Some(class) = registry.classes.get_mut(&RefID::from(compound_id)) {
+          class.template_args = parse_template_args(elem, &mut Vec::new())?;
         }
       }
       _ => ()
     }
   }
+
+  return Ok(());
+}
+
+fn copy_declaration<T: Clone>(from: &HashMap<RefID, T>, to: &mut HashMap<RefID, T>, id: &str)
+{
+  let id = RefID::from(id);
+
+  if let Some(entity) = from.get(&id) {
+    to.insert(id, entity.clone());
+  }
 }
 
-fn parse_generic_file(file_path: &PathBuf, registry: &mut Registry)
+/// Copies the declarations of a compound and its members from the index, so that the definitions
+/// can be parsed into a separate registry.
+fn copy_declarations(index: &Registry, registry: &mut Registry, element: &Element)
+  -> D2mResult<()>
 {
-  if file_path.is_file()
-      && file_path.extension().unwrap() == "xml"
-      && file_path.file_name().unwrap() != "index.xml" {
-    println!("Parsing file {}", file_path.display());
+  let compound_id = get_attribute(element, "id")?;
+
+  copy_declaration(&index.compounds, &mut registry.compounds, compound_id);
+  copy_declaration(&index.classes, &mut registry.classes, compound_id);
 
-    let root_element = parse_xml_file(&file_path);
-    for elem in root_element.children().filter(|e| e.is("compounddef", AnyNS)) {
-      parse_compound_definition(elem, registry);
+  for section in element.children().filter(|e| e.is("sectiondef", AnyNS)) {
+    for member in section.children().filter(|e| e.is("memberdef", AnyNS)) {
+      let member_id = get_attribute(member, "id")?;
+
+      match get_attribute(member, "kind")? {
+        "function" => copy_declaration(&index.functions, &mut registry.functions, member_id),
+        "variable" => copy_declaration(&index.variables, &mut registry.variables, member_id),
+        "enum" => copy_declaration(&index.enums, &mut registry.enums, member_id),
+        "define" => copy_declaration(&index.defines, &mut registry.defines, member_id),
+        _ => (),
+      }
     }
   }
+
+  return Ok(());
+}
+
+fn parse_compound_definitions(root_element: &Element,
+                              index: Option<&Registry>,
+                              registry: &mut Registry) -> D2mResult<()>
+{
+  for elem in root_element.children().filter(|e| e.is("compounddef", AnyNS)) {
+    match index {
+      Some(index) => copy_declarations(index, registry, elem)?,
+      None => parse_compound_declaration_from_definition(registry, elem)?,
+    }
+
+    parse_compound_definition(elem, registry)?;
+  }
+
+  return Ok(());
+}
+
+/// Indicates whether a file in the input directory is expected to define compounds.
+fn is_compound_file(path: &Path) -> bool
+{
+  return path.is_file()
+         && path.extension().unwrap_or_default() == "xml"
+         && path.file_name().unwrap_or_default() != "index.xml";
 }
 
-fn parse_member_declaration(registry: &mut Registry, element: &Element, parent_id: &RefID)
+/// Parses the definitions in a compound file into a separate registry, which is merged with the
+/// others afterwards. The declarations are taken from the index, if there is one.
+fn parse_compound_file(file_path: &Path, index: Option<&Registry>) -> D2mResult<Registry>
 {
-  let parent = registry.compounds.get_mut(parent_id).unwrap();
-  let member_id = element.attr("refid").unwrap().to_owned();
+  let path = file_path.display();
+  debug!(event = "parse_file", path:% = path; "Parsing file {}", path);
+
+  let root_element = xml::read_element(file_path)?;
+  return parse_compound_element(&root_element, file_path, index);
+}
+
+fn parse_compound_element(root: &Element, file_path: &Path, index: Option<&Registry>)
+  -> D2mResult<Registry>
+{
+  let mut registry = Registry::new();
+  registry.doxygen_version = root.attr("version").unwrap_or_default().to_owned();
 
-  match element.attr("kind").unwrap() {
+  diagnostics::set_current_file(Some(file_path));
+  let result = parse_compound_definitions(root, index, &mut registry);
+  diagnostics::set_current_file(None);
+
+  result.map_err(|err| err.in_file(file_path.to_path_buf()))?;
+
+  return Ok(registry);
+}
+
+/// Parses the XML of a single compound from a reader, e.g. stdin, where the path is only used to
+/// describe the source in errors. The compound is parsed without an index, so references to other
+/// compounds can't be resolved.
+pub fn parse_compound<R: BufRead>(reader: R, path: &Path) -> D2mResult<Registry>
+{
+  let root_element = xml::read_element_from(reader, path)?;
+  return parse_compound_element(&root_element, path, None);
+}
+
+fn parse_member_declaration(registry: &mut Registry,
+                            parent_id: &RefID,
+                            member_id: &str,
+                            kind: &str) -> D2mResult<()>
+{
+  let parent = get_declared(&mut registry.compounds, parent_id)?;
+  let member_id = RefID::from(member_id);
+
+  // Members may be declared by several compounds, e.g. both a namespace and a group, so we
+  // make sure not to overwrite members that have already been parsed
+  match kind {
     "define" => {
-      registry.defines.insert(member_id.to_owned(), Define::new());
-      parent.defines.push(member_id.to_owned());
+      registry.defines.entry(member_id.clone()).or_insert_with(Define::new);
+      parent.defines.push(member_id);
     }
-    "friend" => {}
-    "typedef" => {}
     "variable" => {
-      registry.variables.insert(member_id.to_owned(), Variable::new());
-      parent.variables.push(member_id.to_owned());
+      registry.variables.entry(member_id.clone()).or_insert_with(Variable::new);
+      parent.variables.push(member_id);
     }
     "function" => {
-      registry.functions.insert(member_id.to_owned(),
-                                Function::new(parent.kind == CLASS || parent.kind == STRUCT));
-      parent.functions.push(member_id.to_owned());
+      let is_member = parent.kind == CLASS || parent.kind == STRUCT;
+      registry.functions.entry(member_id.clone()).or_insert_with(|| Function::new(is_member));
+      parent.functions.push(member_id);
     }
     "enum" => {
-      registry.enums.insert(member_id.to_owned(), Enum::new());
-      parent.enums.push(member_id.to_owned());
+      registry.enums.entry(member_id.clone()).or_insert_with(Enum::new);
+      parent.enums.push(member_id);
     }
     "enumvalue" => {
-      registry.enum_values.insert(member_id.to_owned(), EnumValue::new());
-      parent.enum_values.push(member_id.to_owned());
+      registry.enum_values.entry(member_id.clone()).or_insert_with(EnumValue::new);
+      parent.enum_values.push(member_id);
     }
-    kind => println!("Ignoring member declaration of type '{}'", kind),
+    kind => diagnostics::ignore("member declaration of type", kind),
   };
+
+  return Ok(());
 }
 
 fn parse_class_declaration(registry: &mut Registry,
                            ref_id: &RefID,
-                           name: &String,
+                           name: &str,
                            clazz: Class)
 {
   registry.classes.insert(ref_id.to_owned(), clazz);
@@ -425,62 +736,327 @@ fn parse_class_declaration(registry: &mut Registry,
   class.unqualified_name = name.split("::").last().unwrap().to_owned();
 }
 
-fn parse_compound_declaration(registry: &mut Registry, element: &Element)
+fn declare_compound(registry: &mut Registry, compound_id: &RefID, kind: &str, name: String)
 {
-  let compound_id = element.attr("refid").unwrap().to_owned();
+  // Compounds such as unions are declared, but not otherwise parsed
+  let kind_value = CompoundKind::from_str(kind).unwrap_or(UNKNOWN);
+
+  match kind_value {
+    CLASS => parse_class_declaration(registry, compound_id, &name, Class::new()),
+    STRUCT => parse_class_declaration(registry, compound_id, &name, Class::new_struct()),
+    INTERFACE => parse_class_declaration(registry, compound_id, &name, Class::new_interface()),
+    GROUP | NAMESPACE | EXAMPLE => (),
+    _ => diagnostics::ignore("compound declaration of kind", kind),
+  }
+
+  registry.add_compound(compound_id.to_owned(), kind_value, name);
+}
+
+fn parse_compound_declaration(registry: &mut Registry, element: &Element) -> D2mResult<()>
+{
+  let compound_id = RefID::from(get_attribute(element, "refid")?);
 
   let name = match element.get_child("name", AnyNS) {
     Some(name) => name.text(),
     None => String::from("?")
   };
 
-  let kind = CompoundKind::from_str(element.attr("kind").unwrap()).unwrap();
+  declare_compound(registry, &compound_id, get_attribute(element, "kind")?, name);
 
-  match kind {
-    CLASS => parse_class_declaration(registry, &compound_id, &name, Class::new()),
-    STRUCT => parse_class_declaration(registry, &compound_id, &name, Class::new_struct()),
-    INTERFACE => parse_class_declaration(registry, &compound_id, &name, Class::new_interface()),
-    k => println!("Ignoring {:?} in compound declaration", k),
+  for member in element.children().filter(|e| e.is("member", AnyNS)) {
+    parse_member_declaration(registry,
+                             &compound_id,
+                             get_attribute(member, "refid")?,
+                             get_attribute(member, "kind")?)?;
   }
 
-  registry.add_compound(compound_id.to_owned(), kind, name);
+  return Ok(());
+}
 
-  for member in element.children().filter(|e| e.is("member", AnyNS)) {
-    parse_member_declaration(registry, member, &compound_id);
+/// Declares a compound and its members based on its definition, used when there is no index.
+fn parse_compound_declaration_from_definition(registry: &mut Registry, element: &Element)
+  -> D2mResult<()>
+{
+  let compound_id = RefID::from(get_attribute(element, "id")?);
+
+  let name = match element.get_child("compoundname", AnyNS) {
+    Some(name) => name.text(),
+    None => String::from("?")
+  };
+
+  declare_compound(registry, &compound_id, get_attribute(element, "kind")?, name);
+
+  for section in element.children().filter(|e| e.is("sectiondef", AnyNS)) {
+    for member in section.children().filter(|e| e.is("memberdef", AnyNS)) {
+      parse_member_declaration(registry,
+                               &compound_id,
+                               get_attribute(member, "id")?,
+                               get_attribute(member, "kind")?)?;
+
+      for value in member.children().filter(|e| e.is("enumvalue", AnyNS)) {
+        parse_member_declaration(registry,
+                                 &compound_id,
+                                 get_attribute(value, "id")?,
+                                 "enumvalue")?;
+      }
+    }
   }
+
+  return Ok(());
 }
 
-fn parse_index_file(input_dir: &PathBuf) -> Registry
+fn parse_index_file(index_file: &Path) -> D2mResult<Registry>
 {
   let mut registry = Registry::new();
 
-  let index_file = input_dir.join("index.xml");
-  let root_element = parse_xml_file(&index_file);
+  let root_element = xml::read_element(index_file)?;
+  registry.doxygen_version = root_element.attr("version").unwrap_or_default().to_owned();
+
+  diagnostics::set_current_file(Some(index_file));
+  let result = root_element.children()
+                           .filter(|e| e.is("compound", AnyNS))
+                           .try_for_each(|decl| parse_compound_declaration(&mut registry, decl));
+  diagnostics::set_current_file(None);
 
-  for decl in root_element.children().filter(|e| e.is("compound", AnyNS)) {
-    parse_compound_declaration(&mut registry, decl);
+  result.map_err(|err| err.in_file(index_file.to_path_buf()))?;
+
+  return Ok(registry);
+}
+
+/// Parses the index file in a directory, which declares all compounds and their members.
+pub fn parse_index(input_dir: &Path) -> D2mResult<Registry>
+{
+  return parse_index_file(&input_dir.join("index.xml"));
+}
+
+/// Parses a compound file, unless its contents are cached and it hasn't changed since then.
+///
+/// The checksum of the file is returned if it was parsed and should be added to the cache.
+fn parse_or_load_compound_file(path: &Path, index: Option<&Registry>, cache: Option<&Cache>)
+  -> D2mResult<(Registry, Option<String>)>
+{
+  let cache = match cache {
+    Some(cache) => cache,
+    None => return Ok((parse_compound_file(path, index)?, None)),
+  };
+
+  let sha256 = cache::hash_file(path).map_err(|source| D2mError::READ {
+                                       path: path.to_path_buf(),
+                                       source,
+                                     })?;
+
+  if let Some(file) = cache.get(&path.file_name().unwrap_or_default().to_string_lossy(), &sha256)
+  {
+    file.replay_diagnostics(path);
+    return Ok((file.registry.clone(), None));
   }
 
-  return registry;
+  return Ok((parse_compound_file(path, index)?, Some(sha256)));
 }
 
-pub fn parse_xml(input_dir: &PathBuf) -> Registry
+/// Parses all XML files in the input directory.
+///
+/// By default, the first file that fails to parse aborts the whole run. When `keep_going` is
+/// enabled, broken files are skipped and returned alongside the registry instead. Note that the
+/// index file, if present, is always required to be valid.
+///
+/// If there is no index file, the compounds are instead declared based on the definitions found
+/// in the individual compound files. However, the order of the declarations may then differ
+/// compared to the index.
+///
+/// If a cache is provided, compound files that haven't changed since they were cached are not
+/// parsed again, and the cache is updated with the files that were parsed.
+///
+/// The compound files are parsed in parallel, by as many threads as there are CPUs unless
+/// `RAYON_NUM_THREADS` says otherwise, and merged in the order of their paths.
+pub fn parse_xml(input_dir: &Path,
+                 filter: &FileFilter,
+                 keep_going: bool,
+                 mut cache: Option<&mut Cache>)
+  -> D2mResult<(Registry, Vec<D2mError>)>
 {
   let start_time = SystemTime::now();
-  println!("Parsing XML input...");
+  info!("Parsing XML input...");
 
-  let mut registry = parse_index_file(input_dir);
+  let index_file = input_dir.join("index.xml");
+  let has_index = index_file.exists();
 
-  for e in fs::read_dir(input_dir).unwrap() {
+  let mut registry = if has_index {
+    let _stage = profile::stage("Index parsing");
+    parse_index_file(&index_file)?
+  } else {
+    diagnostics::warn(WarningKind::MISSING_INDEX,
+                      String::from("found no index file, compounds will be discovered by \
+                                    scanning the input files instead, which means that the \
+                                    declaration order may differ"));
+    Registry::new()
+  };
+
+  let mut failures = Vec::new();
+
+  let mut paths = Vec::new();
+  let entries = fs::read_dir(input_dir).map_err(|source| D2mError::READ {
+                                          path: input_dir.to_path_buf(),
+                                          source,
+                                        })?;
+
+  for e in entries {
     match e {
-      Ok(entry) => parse_generic_file(&entry.path(), &mut registry),
-      Err(err) => println!("Error encountered when iterating input directory: {}", err),
+      Ok(entry) => paths.push(entry.path()),
+      Err(err) => error!("failed to iterate input directory: {}", err),
+    }
+  }
+
+  paths.retain(|path| is_compound_file(path));
+
+  let (mut paths, filtered): (Vec<_>, Vec<_>) =
+    paths.into_iter().partition(|path| filter.is_included(path));
+
+  // Sorted to make the declaration order deterministic when there is no index
+  paths.sort();
+
+  if !filtered.is_empty() {
+    info!("Skipping {} filtered input file(s)", filtered.len());
+  }
+
+  if let Some(cache) = cache.as_deref_mut() {
+    let index_hash = match has_index {
+      true => Some(cache::hash_file(&index_file).map_err(|source| D2mError::READ {
+                                                     path: index_file.to_owned(),
+                                                     source,
+                                                   })?),
+      false => None,
+    };
+
+    cache.set_index(index_hash);
+  }
+
+  let stage = profile::stage("File parsing");
+  progress::start("Parsing", paths.len());
+
+  let index = Some(&registry).filter(|_| has_index);
+  let cached = cache.as_deref();
+  let results: Vec<_> =
+    paths.par_iter()
+         .map(|path: &PathBuf| {
+           let file_start_time = SystemTime::now();
+           let result = parse_or_load_compound_file(path, index, cached);
+           progress::advance();
+
+           let thread = rayon::current_thread_index().unwrap_or_default();
+           (thread, file_start_time.elapsed().unwrap_or_default(), result)
+         })
+         .collect();
+
+  progress::finish();
+
+  let mut thread_times: BTreeMap<usize, (usize, Duration)> = BTreeMap::new();
+  let mut file_diagnostics = match cache {
+    Some(_) => diagnostics::get_file_diagnostics(),
+    None => HashMap::new(),
+  };
+
+  for (path, (thread, duration, result)) in paths.iter().zip(results) {
+    let (files, total) = thread_times.entry(thread).or_default();
+    *files += 1;
+    *total += duration;
+    profile::record_file(path, duration);
+
+    match result {
+      Ok((partial, sha256)) => {
+        if let (Some(cache), Some(sha256)) = (cache.as_deref_mut(), sha256) {
+          let diagnostics = file_diagnostics.remove(path).unwrap_or_default();
+          cache.insert(path, sha256, &partial, diagnostics);
+        }
+
+        registry.merge(partial);
+      }
+      Err(failure) => {
+        if !keep_going {
+          return Err(failure);
+        }
+
+        diagnostics::warn_in_file(WarningKind::SKIPPED_FILE,
+                                  format!("skipping file that could not be parsed: {}", failure),
+                                  failure.get_path().map(Path::to_path_buf));
+        failures.push(failure);
+      }
     }
   }
 
+  drop(stage);
+
+  // The compounds of filtered files are removed, since they may be declared by the index or listed
+  // by other compounds, and references to them are rendered as plain text without warnings
+  if !filtered.is_empty() && !filter.keep_skipped {
+    let ids: HashSet<RefID> =
+      filtered.iter()
+              .map(|path| RefID::intern(&path.file_stem().unwrap_or_default().to_string_lossy()))
+              .collect();
+
+    let members = registry.remove_compounds(&ids);
+    diagnostics::exclude(ids.into_iter().chain(members));
+  }
+
+  if let Some(cache) = cache {
+    let names: HashSet<String> =
+      paths.iter()
+           .map(|path| path.file_name().unwrap_or_default().to_string_lossy().to_string())
+           .collect();
+
+    cache.retain_files(&names);
+  }
+
+  for (thread, (files, total)) in &thread_times {
+    debug!("Thread {} parsed {} file(s) in {} ms", thread, files, total.as_millis());
+  }
+
   let end_time = SystemTime::now();
-  println!("Parsed XML files in {} ms",
-           end_time.duration_since(start_time).unwrap().as_millis());
+  info!("Parsed XML files in {} ms using {} thread(s)",
+        end_time.duration_since(start_time).unwrap().as_millis(),
+        thread_times.len());
+
+  return Ok((registry, failures));
+}
 
-  return registry;
+#[cfg(test)]
+mod tests
+{
+  use super::*;
+
+  const CLASS_XML: &str = r#"<?xml version='1.0' encoding='UTF-8' standalone='no'?>
+<doxygen version="1.9.3">
+  <compounddef id="classcen_1_1basic__window" kind="class" language="C++" prot="public">
+    <compoundname>cen::basic_window</compoundname>
+    <sectiondef kind="public-static-func">
+      <memberdef kind="function" id="classcen_1_1basic__window_1a2" prot="public" static="yes"
+                 const="no" explicit="no" inline="no" virt="non-virtual">
+        <type><ref refid="classcen_1_1basic__window" kindref="compound">basic_window</ref></type>
+        <definition>static basic_window cen::basic_window::make</definition>
+        <argsstring>(const point &amp;size)</argsstring>
+        <name>make</name>
+        <qualifiedname>cen::basic_window::make</qualifiedname>
+        <param>
+          <type>const <ref refid="structcen_1_1point" kindref="compound">point</ref> &amp;</type>
+          <declname>size</declname>
+        </param>
+        <briefdescription></briefdescription>
+        <detaileddescription></detaileddescription>
+      </memberdef>
+    </sectiondef>
+  </compounddef>
+</doxygen>
+"#;
+
+  #[test]
+  fn parses_referenced_types()
+  {
+    let registry = parse_compound(CLASS_XML.as_bytes(), Path::new("class.xml")).unwrap();
+    let func = registry.functions.get(&RefID::from("classcen_1_1basic__window_1a2")).unwrap();
+
+    assert_eq!(func.return_type, "basic_window");
+    assert_eq!(func.type_refs,
+               vec![(Symbol::from("basic_window"), RefID::from("classcen_1_1basic__window")),
+                    (Symbol::from("point"), RefID::from("structcen_1_1point"))]);
+  }
 }
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use lazy_static::lazy_static;
+use log::info;
+
+/// A stage of a run, which may be nested in another stage, e.g. link resolution in the
+/// generation of the Markdown output.
+struct StageTime
+{
+  name: String,
+  depth: usize,
+  duration: Duration,
+}
+
+struct Profile
+{
+  stages: Vec<StageTime>,
+  depth: usize,
+  files: Vec<(PathBuf, Duration)>,
+}
+
+lazy_static! {
+  static ref PROFILE: Mutex<Profile> = Mutex::new(Profile {
+    stages: Vec::new(),
+    depth: 0,
+    files: Vec::new(),
+  });
+}
+
+/// Measures the time of a stage until it is dropped, where stages that are started meanwhile are
+/// nested in it.
+#[must_use = "the stage ends when it is dropped"]
+pub struct Stage
+{
+  index: usize,
+  start_time: SystemTime,
+}
+
+impl Drop for Stage
+{
+  fn drop(&mut self)
+  {
+    let mut profile = PROFILE.lock().unwrap();
+    profile.stages[self.index].duration = self.start_time.elapsed().unwrap_or_default();
+    profile.depth -= 1;
+  }
+}
+
+/// Starts measuring the time of a stage, e.g. "Index parsing".
+pub fn stage(name: &str) -> Stage
+{
+  let mut profile = PROFILE.lock().unwrap();
+  let depth = profile.depth;
+
+  profile.stages.push(StageTime {
+    name: name.to_owned(),
+    depth,
+    duration: Duration::ZERO,
+  });
+  profile.depth += 1;
+
+  return Stage {
+    index: profile.stages.len() - 1,
+    start_time: SystemTime::now(),
+  };
+}
+
+/// Records the time it took to parse an input file.
+pub fn record_file(path: &Path, duration: Duration)
+{
+  PROFILE.lock().unwrap().files.push((path.to_path_buf(), duration));
+}
+
+/// Forgets the recorded times, e.g. before converting the input again.
+pub fn reset()
+{
+  let mut profile = PROFILE.lock().unwrap();
+  profile.stages.clear();
+  profile.files.clear();
+}
+
+/// Prints the time of each stage, with nested stages indented below their parents, and the input
+/// files that took the longest to parse.
+pub fn print_report(slowest_files: usize)
+{
+  let profile = PROFILE.lock().unwrap();
+
+  info!("\nProfile:");
+
+  if profile.stages.is_empty() {
+    info!("  No stages");
+  }
+
+  let total: Duration = profile.stages.iter().filter(|s| s.depth == 0).map(|s| s.duration).sum();
+  let width = profile.stages.iter().map(|s| s.name.len() + 2 * s.depth).max().unwrap_or(0);
+
+  for stage in &profile.stages {
+    let percentage = match total.is_zero() {
+      true => 0.0,
+      false => 100.0 * stage.duration.as_secs_f64() / total.as_secs_f64(),
+    };
+
+    info!("  {:width$}  {:>9.1} ms  {:>5.1}%",
+          format!("{}{}", "  ".repeat(stage.depth), stage.name),
+          stage.duration.as_secs_f64() * 1000.0,
+          percentage);
+  }
+
+  let mut files: Vec<&(PathBuf, Duration)> = profile.files.iter().collect();
+  files.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+  files.truncate(slowest_files);
+
+  if files.is_empty() {
+    return;
+  }
+
+  info!("\nSlowest input files:");
+
+  // The files are all in the input directory, so their names are enough to tell them apart
+  let names: Vec<(String, &Duration)> =
+    files.iter()
+         .map(|(path, duration)| {
+           (path.file_name().unwrap_or_default().to_string_lossy().to_string(), duration)
+         })
+         .collect();
+
+  let width = names.iter().map(|(name, _)| name.len()).max().unwrap();
+  for (name, duration) in names {
+    info!("  {:width$}  {:>9.1} ms", name, duration.as_secs_f64() * 1000.0);
+  }
+}
@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use lazy_static::lazy_static;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+  static ref BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+}
+
+/// Enables progress bars, which should only be shown when stdout is a terminal.
+pub fn set_enabled(enabled: bool)
+{
+  ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Shows a progress bar for a stage of the run, e.g. "Parsing", with a known number of steps.
+pub fn start(stage: &'static str, total: usize)
+{
+  if !ENABLED.load(Ordering::Relaxed) {
+    return;
+  }
+
+  let style = ProgressStyle::with_template("{msg:>10} [{bar:40}] {pos}/{len}")
+    .unwrap()
+    .progress_chars("=> ");
+
+  let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout());
+  bar.set_style(style);
+  bar.set_message(stage);
+
+  *BAR.lock().unwrap() = Some(bar);
+}
+
+/// Advances the progress bar of the current stage by one step.
+pub fn advance()
+{
+  if let Some(bar) = BAR.lock().unwrap().as_ref() {
+    bar.inc(1);
+  }
+}
+
+/// Removes the progress bar of the current stage.
+pub fn finish()
+{
+  if let Some(bar) = BAR.lock().unwrap().take() {
+    bar.finish_and_clear();
+  }
+}
+
+/// Runs a function that writes to the console, hiding the progress bar while it runs.
+pub fn suspend<R>(function: impl FnOnce() -> R) -> R
+{
+  return match BAR.lock().unwrap().as_ref() {
+    Some(bar) => bar.suspend(function),
+    None => function(),
+  };
+}
@@ -0,0 +1,70 @@
+use std::mem;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::d2m::generator::PageBuffer;
+
+/// A rule that rewrites text in pages, e.g. ticket numbers into links, see the "replacements"
+/// configuration tables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplacementRule
+{
+  /// A regular expression, e.g. "\\b(PROJ-[0-9]+)\\b".
+  pub pattern: String,
+  /// The text that replaces each match, where "$1" or "${name}" refer to capture groups.
+  pub replacement: String,
+}
+
+/// Compiles the patterns of replacement rules.
+pub fn compile_rules(rules: &[ReplacementRule]) -> Result<Vec<(Regex, String)>, String>
+{
+  let mut compiled = Vec::with_capacity(rules.len());
+
+  for rule in rules {
+    match Regex::new(&rule.pattern) {
+      Ok(regex) => compiled.push((regex, rule.replacement.to_owned())),
+      Err(err) => return Err(format!("invalid replacement pattern '{}': {}", rule.pattern, err)),
+    }
+  }
+
+  return Ok(compiled);
+}
+
+/// Applies the rules in order to the text of a Markdown page outside of code blocks, where
+/// patterns may match across lines within the same stretch of text.
+pub fn apply(rules: &[(Regex, String)], content: &[u8]) -> PageBuffer
+{
+  let text = String::from_utf8_lossy(content);
+
+  let mut output = String::with_capacity(text.len());
+  let mut prose = String::new();
+  let mut in_code_block = false;
+
+  let replace = |prose: &mut String, output: &mut String| {
+    let mut replaced = mem::take(prose);
+    for (regex, replacement) in rules {
+      replaced = regex.replace_all(&replaced, replacement.as_str()).into_owned();
+    }
+    *output += &replaced;
+  };
+
+  for line in text.split_inclusive('\n') {
+    if line.starts_with("```") {
+      if !in_code_block {
+        replace(&mut prose, &mut output);
+      }
+
+      in_code_block = !in_code_block;
+      output += line;
+    } else if in_code_block {
+      output += line;
+    } else {
+      prose += line;
+    }
+  }
+
+  replace(&mut prose, &mut output);
+  return output.into_bytes();
+}
@@ -0,0 +1,192 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::d2m::annotations;
+use crate::d2m::diagnostics::{Ignored, Warning, WarningKind};
+use crate::d2m::doxygen::{AccessModifier, Location, Registry};
+use crate::d2m::error::D2mError;
+
+const SCHEMA_URL: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+const UNDOCUMENTED_RULE: &str = "undocumented_member";
+const IGNORED_RULE: &str = "ignored_construct";
+const PARSE_FAILURE_RULE: &str = "parse_failure";
+
+/// The kinds of warnings, which are reported with their names as rule IDs.
+const WARNING_KINDS: [WarningKind; 6] = [WarningKind::MISSING_INDEX,
+                                         WarningKind::SKIPPED_FILE,
+                                         WarningKind::PAGE_COLLISION,
+                                         WarningKind::BROKEN_REFERENCE,
+                                         WarningKind::BROKEN_LINK,
+                                         WarningKind::ORPHANED_COMPOUND];
+
+/// Returns the URI of a file, which is relative to the working directory if possible.
+fn get_uri(file: &Path) -> String
+{
+  let path = annotations::get_display_path(file);
+  let uri = path.replace(' ', "%20");
+
+  return match Path::new(&path).is_absolute() {
+    true if uri.starts_with('/') => format!("file://{}", uri),
+    true => format!("file:///{}", uri),
+    false => uri,
+  };
+}
+
+fn get_physical_location(file: &Path, line: Option<usize>) -> Value
+{
+  let mut location = json!({ "artifactLocation": { "uri": get_uri(file) } });
+
+  if let Some(line) = line.filter(|line| *line > 0) {
+    location["region"] = json!({ "startLine": line });
+  }
+
+  return json!({ "physicalLocation": location });
+}
+
+fn get_result(rule: &str, level: &str, message: &str, locations: Vec<Value>) -> Value
+{
+  return json!({
+    "ruleId": rule,
+    "level": level,
+    "message": { "text": message },
+    "locations": locations,
+  });
+}
+
+fn get_rules() -> Vec<Value>
+{
+  let mut rules = vec![json!({
+                         "id": UNDOCUMENTED_RULE,
+                         "shortDescription": { "text": "Undocumented public members" },
+                       }),
+                       json!({
+                         "id": IGNORED_RULE,
+                         "shortDescription": { "text": "Ignored constructs" },
+                       }),
+                       json!({
+                         "id": PARSE_FAILURE_RULE,
+                         "shortDescription": { "text": "Files that failed to parse" },
+                       })];
+
+  for kind in WARNING_KINDS {
+    rules.push(json!({ "id": kind.name(), "shortDescription": { "text": kind.description() } }));
+  }
+
+  return rules;
+}
+
+/// Returns a result for each public function, variable, enum, and macro without a brief or
+/// detailed description, sorted by name.
+fn get_undocumented_members(registry: &Registry) -> Vec<Value>
+{
+  let functions = registry.functions.values().map(|f| {
+    ("function", f.qualified_name.as_str(), f.access, &f.docs, f.location.as_ref())
+  });
+
+  let variables = registry.variables.values().map(|v| {
+    ("variable", v.qualified_name.as_str(), v.access, &v.docs, v.location.as_ref())
+  });
+
+  let enums = registry.enums.values().map(|e| {
+    ("enum", e.qualified_name.as_str(), AccessModifier::PUBLIC, &e.docs, e.location.as_ref())
+  });
+
+  let defines = registry.defines.values().map(|d| {
+    ("macro", d.name.as_str(), AccessModifier::PUBLIC, &d.docs, d.location.as_ref())
+  });
+
+  let mut members: Vec<(&str, &str, Option<&Location>)> =
+    functions.chain(variables)
+             .chain(enums)
+             .chain(defines)
+             .filter(|(_, _, access, docs, _)| {
+               *access == AccessModifier::PUBLIC && !docs.is_documented()
+             })
+             .map(|(kind, name, _, _, location)| (kind, name, location))
+             .collect();
+
+  members.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+  return members.into_iter()
+                .map(|(kind, name, location)| {
+                  let locations = location.map(|location| {
+                                            get_physical_location(Path::new(&location.file),
+                                                                  location.line)
+                                          })
+                                          .into_iter()
+                                          .collect();
+
+                  let message = format!("{} '{}' is not documented", kind, name);
+                  let mut result = get_result(UNDOCUMENTED_RULE, "note", &message, locations);
+
+                  // Identifies the member across runs, even if its location changes
+                  result["partialFingerprints"] = json!({ "qualifiedName": name });
+                  result
+                })
+                .collect();
+}
+
+/// Writes a SARIF log with the undocumented public members, files that failed to parse,
+/// warnings, and ignored constructs, for code scanning tools such as GitHub code scanning.
+pub fn write_sarif(path: &Path,
+                   registry: &Registry,
+                   failures: &[D2mError],
+                   warnings: &[Warning],
+                   ignored: &[Ignored])
+  -> io::Result<()>
+{
+  let mut results = get_undocumented_members(registry);
+
+  for failure in failures {
+    let locations = failure.get_path()
+                           .map(|file| get_physical_location(file, None))
+                           .into_iter()
+                           .collect();
+
+    results.push(get_result(PARSE_FAILURE_RULE, "error", &failure.to_string(), locations));
+  }
+
+  for warning in warnings {
+    let locations = warning.file
+                           .as_ref()
+                           .map(|file| get_physical_location(file, None))
+                           .into_iter()
+                           .collect();
+
+    results.push(get_result(warning.kind.name(), "warning", &warning.message, locations));
+  }
+
+  for entry in ignored {
+    let locations = entry.file
+                         .as_ref()
+                         .map(|file| get_physical_location(file, None))
+                         .into_iter()
+                         .collect();
+
+    let message = format!("ignored {} '{}'", entry.context, entry.name);
+    results.push(get_result(IGNORED_RULE, "note", &message, locations));
+  }
+
+  let log = json!({
+    "$schema": SCHEMA_URL,
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": env!("CARGO_PKG_NAME"),
+          "version": env!("CARGO_PKG_VERSION"),
+          "informationUri": "https://github.com/albin-johansson/doxy-to-md",
+          "rules": get_rules(),
+        }
+      },
+      "results": results,
+    }]
+  });
+
+  let output = serde_json::to_string_pretty(&log)?;
+  return fs::write(path, output + "\n");
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info};
+use rhai::{Dynamic, Engine, FuncArgs, Map, Scope, AST};
+
+use crate::d2m::diagnostics;
+use crate::d2m::doxygen::*;
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// The function that returns the heading of a function section, e.g. to drop namespaces.
+const MEMBER_HEADING: &str = "member_heading";
+
+/// The function that indicates whether a member is left out of the output.
+const SKIP_MEMBER: &str = "skip_member";
+
+/// The function that returns Markdown that is appended to a class, group, or namespace page.
+const EXTRA_SECTIONS: &str = "extra_sections";
+
+/// A Rhai script with functions that customize rendering decisions, where every function is
+/// optional, see the "Scripting" section of the README.
+pub struct Script
+{
+  path: PathBuf,
+  engine: Engine,
+  ast: AST,
+}
+
+/// Returns the description of a member or compound that is passed to script functions.
+fn to_map(id: &RefID, kind: &str, name: &str, qualified_name: &str, docs: &Comment) -> Map
+{
+  let mut map = Map::new();
+  map.insert("refid".into(), Dynamic::from(id.to_string()));
+  map.insert("kind".into(), Dynamic::from(kind.to_owned()));
+  map.insert("name".into(), Dynamic::from(name.to_owned()));
+  map.insert("qualified_name".into(), Dynamic::from(qualified_name.to_owned()));
+  map.insert("brief".into(), Dynamic::from(docs.brief.first().cloned().unwrap_or_default()));
+  map.insert("deprecated".into(), Dynamic::from(docs.deprecated.is_some()));
+  return map;
+}
+
+fn get_function_map(id: &RefID, func: &Function) -> Map
+{
+  let mut map = to_map(id, "function", &func.name, &func.qualified_name, &func.docs);
+  map.insert("access".into(), Dynamic::from(func.access.to_string()));
+  map.insert("signature".into(), Dynamic::from(func.definition.to_owned() + func.args.as_str()));
+  map.insert("is_static".into(), Dynamic::from(func.is_static));
+  map.insert("is_member".into(), Dynamic::from(func.is_member));
+  return map;
+}
+
+fn get_compound_map(id: &RefID, compound: &Compound) -> Map
+{
+  let mut map = to_map(id, compound.kind.name(), &compound.name, &compound.name, &compound.docs);
+  map.insert("title".into(), Dynamic::from(compound.title.to_owned()));
+  return map;
+}
+
+impl Script
+{
+  pub fn load(path: &Path) -> D2mResult<Self>
+  {
+    let source = fs::read_to_string(path).map_err(|source| D2mError::READ {
+                                            path: path.to_path_buf(),
+                                            source,
+                                          })?;
+
+    let engine = Engine::new();
+    let ast = engine.compile(&source).map_err(|err| D2mError::SCRIPT {
+                                         path: path.to_path_buf(),
+                                         reason: err.to_string(),
+                                       })?;
+
+    return Ok(Self { path: path.to_path_buf(), engine, ast });
+  }
+
+  /// Indicates whether the script defines a function that takes a single argument.
+  fn defines(&self, name: &str) -> bool
+  {
+    return self.ast.iter_functions().any(|func| func.name == name && func.params.len() == 1);
+  }
+
+  fn call<T: Clone + 'static>(&self, name: &str, args: impl FuncArgs) -> D2mResult<T>
+  {
+    return self.engine
+               .call_fn(&mut Scope::new(), &self.ast, name, args)
+               .map_err(|err| D2mError::SCRIPT {
+                          path: self.path.to_path_buf(),
+                          reason: format!("{} failed: {}", name, err),
+                        });
+  }
+
+  /// Returns the headings of the functions that the script changes, which replace their
+  /// qualified names both in the pages and in the links to them.
+  pub fn get_member_headings(&self, registry: &Registry) -> D2mResult<HashMap<RefID, String>>
+  {
+    let mut headings = HashMap::new();
+
+    if !self.defines(MEMBER_HEADING) {
+      return Ok(headings);
+    }
+
+    for (id, func) in &registry.functions {
+      let heading: String = self.call(MEMBER_HEADING, (get_function_map(id, func),))?;
+
+      if heading != func.qualified_name.as_str() {
+        headings.insert(id.clone(), heading);
+      }
+    }
+
+    return Ok(headings);
+  }
+
+  /// Returns the Markdown that is appended to the page of a compound, which may be empty.
+  pub fn get_extra_sections(&self, id: &RefID, compound: &Compound) -> io::Result<String>
+  {
+    if !self.defines(EXTRA_SECTIONS) {
+      return Ok(String::new());
+    }
+
+    // Pages are rendered like templates, which report their errors as I/O errors
+    let sections: String =
+      self.call(EXTRA_SECTIONS, (get_compound_map(id, compound),)).map_err(io::Error::other)?;
+    return Ok(sections.trim().to_owned());
+  }
+
+  /// Removes the members that the script skips, e.g. functions with a project-specific prefix.
+  pub fn skip_members(&self, registry: &mut Registry) -> D2mResult<()>
+  {
+    if !self.defines(SKIP_MEMBER) {
+      return Ok(());
+    }
+
+    let functions = registry.functions.iter().map(|(id, func)| (id, get_function_map(id, func)));
+
+    let enums = registry.enums.iter().map(|(id, enumeration)| {
+      (id, to_map(id, "enum", &enumeration.name, &enumeration.qualified_name, &enumeration.docs))
+    });
+
+    let variables = registry.variables.iter().map(|(id, var)| {
+      let mut map = to_map(id, "variable", &var.name, &var.qualified_name, &var.docs);
+      map.insert("access".into(), Dynamic::from(var.access.to_string()));
+      (id, map)
+    });
+
+    let defines = registry.defines.iter().map(|(id, define)| {
+      (id, to_map(id, "define", &define.name, &define.name, &define.docs))
+    });
+
+    let mut members = HashSet::new();
+
+    for (id, map) in functions.chain(enums).chain(variables).chain(defines) {
+      if self.call::<bool>(SKIP_MEMBER, (map,))? {
+        debug!("Skipping {} as requested by the script", id);
+        members.insert(id.clone());
+      }
+    }
+
+    if members.is_empty() {
+      return Ok(());
+    }
+
+    info!("Skipping {} member(s) as requested by the script", members.len());
+
+    registry.remove_members(&members);
+    diagnostics::exclude(members);
+
+    return Ok(());
+  }
+}
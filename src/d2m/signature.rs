@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::d2m::doxygen::Function;
+
+/// The number of spaces that wrapped parameters are indented by, if they aren't aligned.
+const PARAMETER_INDENT: usize = 4;
+
+/// How function signatures are laid out in code blocks, see the "signatures" configuration table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SignatureFormat
+{
+  /// Whether signatures are always written on a single line.
+  pub single_line: bool,
+  /// The width above which the parameters of a signature are written on separate lines, if
+  /// absent, functions with several parameters always get one parameter per line.
+  pub max_width: Option<usize>,
+  /// Whether wrapped parameters are aligned, otherwise they are indented by four spaces.
+  pub align: bool,
+  /// Whether wrapped parameters are aligned to the function name instead of the first parameter.
+  pub align_to_name: bool,
+}
+
+impl Default for SignatureFormat
+{
+  fn default() -> Self
+  {
+    Self {
+      single_line: false,
+      max_width: None,
+      align: true,
+      align_to_name: false,
+    }
+  }
+}
+
+lazy_static! {
+  static ref FORMAT: Mutex<SignatureFormat> = Mutex::new(SignatureFormat::default());
+}
+
+/// Sets the format used for all function signatures in the generated output.
+pub fn set_format(format: SignatureFormat)
+{
+  *FORMAT.lock().unwrap() = format;
+}
+
+/// Splits a Doxygen argument string, e.g. "(int a, int b) const", into its parameters and the
+/// trailing specifiers, ignoring commas in nested brackets such as template arguments.
+fn split_parameters(args: &str) -> Option<(Vec<String>, &str)>
+{
+  let inner = args.strip_prefix('(')?;
+
+  let mut parameters = Vec::new();
+  let mut depth = 0;
+  let mut start = 0;
+
+  for (index, c) in inner.char_indices() {
+    match c {
+      '(' | '<' | '[' | '{' => depth += 1,
+      ')' if depth == 0 => {
+        let last = inner[start..index].trim();
+        if !last.is_empty() {
+          parameters.push(last.to_owned());
+        }
+
+        return Some((parameters, &inner[index + 1..]));
+      }
+      ')' | '>' | ']' | '}' => depth -= 1,
+      ',' if depth == 0 => {
+        parameters.push(inner[start..index].trim().to_owned());
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+
+  return None;
+}
+
+/// Returns the signature of a function, without template parameters, laid out according to the
+/// current signature format.
+pub fn format_function_signature(func: &Function) -> String
+{
+  let format = FORMAT.lock().unwrap();
+
+  let prefix = format!("{}{}{}{}",
+                       if func.is_static { "static " } else { "" },
+                       if func.is_explicit { "explicit " } else { "" },
+                       &func.return_type,
+                       if func.return_type.is_empty() { "" } else { " " });
+
+  let (parameters, suffix) = match split_parameters(&func.args) {
+    Some(split) => split,
+    None => return format!("{}{}{}", prefix, &func.name, &func.args),
+  };
+
+  let single_line = format!("{}{}({}){}", prefix, &func.name, parameters.join(", "), suffix);
+
+  let fits = match format.max_width {
+    Some(width) => single_line.chars().count() <= width,
+    None => false,
+  };
+
+  if format.single_line || parameters.len() < 2 || fits {
+    return single_line;
+  }
+
+  let indent = if !format.align {
+    PARAMETER_INDENT
+  } else if format.align_to_name {
+    prefix.chars().count()
+  } else {
+    prefix.chars().count() + func.name.chars().count() + 1
+  };
+
+  let separator = format!(",\n{}", " ".repeat(indent));
+  return format!("{}{}({}){}", prefix, &func.name, parameters.join(&separator), suffix);
+}
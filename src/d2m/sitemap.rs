@@ -0,0 +1,71 @@
+use std::io;
+use std::path::Path;
+
+use log::info;
+
+use crate::d2m::generator;
+use crate::d2m::linkcheck;
+use crate::d2m::stats;
+
+/// How the URLs of pages are derived from their paths, which depends on the site generator.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+pub enum SiteUrlStyle
+{
+  /// Pages are served as HTML files, e.g. "classes/class_foo.html".
+  HTML,
+  /// Pages are served as directories, e.g. "classes/class_foo/", as by MkDocs and Hugo.
+  DIRECTORY,
+  /// Pages are served without extensions, e.g. "classes/class_foo", as by Docusaurus.
+  EXTENSIONLESS,
+}
+
+/// Returns the URL of a page, relative to the site URL.
+fn get_page_url(page: &str, style: SiteUrlStyle) -> String
+{
+  let stem = page.strip_suffix(".md").unwrap_or(page);
+  let is_index = stem == "index" || stem.ends_with("/index");
+
+  return match style {
+    SiteUrlStyle::HTML => format!("{}.html", stem),
+    // Index pages are served as the directories that they are located in
+    _ if is_index => stem[..stem.len() - "index".len()].to_owned(),
+    SiteUrlStyle::DIRECTORY => format!("{}/", stem),
+    SiteUrlStyle::EXTENSIONLESS => stem.to_owned(),
+  };
+}
+
+fn escape_xml(text: &str) -> String
+{
+  return text.replace('&', "&amp;")
+             .replace('<', "&lt;")
+             .replace('>', "&gt;")
+             .replace('"', "&quot;")
+             .replace('\'', "&apos;");
+}
+
+/// Emits a sitemap.xml file that lists the URLs of all Markdown pages in the output directory.
+pub fn emit_sitemap(output_dir: &Path, site_url: &str, style: SiteUrlStyle) -> io::Result<()>
+{
+  info!("Generating sitemap...");
+
+  let mut pages = Vec::new();
+  linkcheck::collect_pages(output_dir, "", &mut pages)?;
+  pages.sort();
+
+  let base = if site_url.ends_with('/') { site_url.to_owned() } else { format!("{}/", site_url) };
+  let date = generator::get_current_date();
+
+  let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  output.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+  for page in &pages {
+    let url = format!("{}{}", base, get_page_url(page, style));
+    output.push_str(&format!("  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+                             escape_xml(&url),
+                             date));
+  }
+
+  output.push_str("</urlset>\n");
+
+  return stats::write_file(output_dir.join("sitemap.xml"), output);
+}
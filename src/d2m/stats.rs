@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::{debug, info};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::d2m::diagnostics::{self, WarningKind};
+use crate::d2m::doxygen::*;
+use crate::d2m::links::{FileNames, FilenameCase};
+
+static FILES_WRITTEN: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+  static ref WRITTEN_PATHS: Mutex<FileNames> = Mutex::new(FileNames::new(FilenameCase::AUTO));
+  static ref MANIFEST: Mutex<BTreeMap<PathBuf, ManifestEntry>> = Mutex::new(BTreeMap::new());
+  static ref PREVIOUS_OUTPUTS: Mutex<HashMap<PathBuf, String>> = Mutex::new(HashMap::new());
+}
+
+/// The entity that an output file documents, listed in the manifest.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSource
+{
+  /// The ID of the documented compound, which is absent for index pages.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub refid: Option<RefID>,
+  /// The kind of the documented entity, e.g. "class", or "index" for index pages.
+  pub kind: String,
+  pub title: String,
+}
+
+/// An entry in the manifest of written files.
+#[derive(Debug, Serialize)]
+struct ManifestEntry
+{
+  path: String,
+  #[serde(flatten)]
+  source: Option<FileSource>,
+  sha256: String,
+}
+
+/// Forgets the files written so far, e.g. before converting the input again.
+pub fn reset()
+{
+  FILES_WRITTEN.store(0, Ordering::Relaxed);
+  MANIFEST.lock().unwrap().clear();
+}
+
+/// Sets how the paths of written files are compared when checking for collisions.
+pub fn set_filename_case(case: FilenameCase)
+{
+  *WRITTEN_PATHS.lock().unwrap() = FileNames::new(case);
+}
+
+/// Writes an output file, keeping track of the number of written files.
+///
+/// Writing the same file twice in a run means that two pages were given the same path, which
+/// is reported as a page collision, since the first page is lost.
+pub fn write_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()>
+{
+  return write_source_file(path, contents, None);
+}
+
+/// Writes an output file that documents an entity, which is recorded in the manifest.
+pub fn write_source_file(path: impl AsRef<Path>,
+                         contents: impl AsRef<[u8]>,
+                         source: Option<FileSource>) -> io::Result<()>
+{
+  let path = path.as_ref();
+  let contents = contents.as_ref();
+
+  if !WRITTEN_PATHS.lock().unwrap().reserve(&path.to_string_lossy()) {
+    diagnostics::warn(WarningKind::PAGE_COLLISION,
+                      format!("{} was written more than once, overwriting its previous contents",
+                              path.display()));
+  }
+
+  let sha256 = format!("{:x}", Sha256::digest(contents));
+  let is_unchanged = PREVIOUS_OUTPUTS.lock().unwrap().get(path) == Some(&sha256) && path.exists();
+
+  if is_unchanged {
+    debug!("Skipping unchanged file {}", path.display());
+  } else {
+    debug!(event = "write_file", path:% = path.display(); "Generating file {}", path.display());
+
+    fs::write(path, contents)?;
+    FILES_WRITTEN.fetch_add(1, Ordering::Relaxed);
+  }
+
+  let entry = ManifestEntry {
+    path: path.to_string_lossy().to_string(),
+    source,
+    sha256,
+  };
+  MANIFEST.lock().unwrap().insert(path.to_path_buf(), entry);
+
+  Ok(())
+}
+
+/// Sets the checksums of the files written by a previous run, by path relative to the output
+/// directory. Files with the same contents aren't written again, to avoid touching them.
+pub fn set_previous_outputs(output_dir: &Path, outputs: &BTreeMap<String, String>)
+{
+  *PREVIOUS_OUTPUTS.lock().unwrap() =
+    outputs.iter().map(|(path, sha256)| (output_dir.join(path), sha256.to_owned())).collect();
+}
+
+/// Returns the checksums of the files written so far, by path relative to the output directory.
+pub fn get_outputs(output_dir: &Path) -> BTreeMap<String, String>
+{
+  return MANIFEST.lock()
+                 .unwrap()
+                 .iter()
+                 .map(|(path, entry)| {
+                   let relative = path.strip_prefix(output_dir).unwrap_or(path);
+                   (relative.to_string_lossy().replace('\\', "/"), entry.sha256.to_owned())
+                 })
+                 .collect();
+}
+
+/// Returns the manifest of the files written so far, which lists the entities that they document
+/// and their checksums, with paths relative to the output directory.
+pub fn get_manifest(output_dir: &Path) -> serde_json::Value
+{
+  let manifest = MANIFEST.lock().unwrap();
+
+  let files: Vec<ManifestEntry> =
+    manifest.iter()
+            .map(|(path, entry)| {
+              let relative = path.strip_prefix(output_dir).unwrap_or(path);
+              ManifestEntry {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                source: entry.source.clone(),
+                sha256: entry.sha256.to_owned(),
+              }
+            })
+            .collect();
+
+  return serde_json::json!({ "files": files });
+}
+
+/// Writes a manifest.json file that lists every file written so far.
+pub fn write_manifest(output_dir: &Path) -> io::Result<()>
+{
+  let output = serde_json::to_string_pretty(&get_manifest(output_dir))?;
+  return write_file(output_dir.join("manifest.json"), output + "\n");
+}
+
+/// Returns the plural of a kind of compound, e.g. "classes" for "class".
+fn pluralize(kind: &str) -> String
+{
+  if kind.ends_with("ss") {
+    return format!("{}es", kind);
+  }
+
+  return match kind.strip_suffix('y') {
+    Some(stem) => format!("{}ies", stem),
+    None => format!("{}s", kind),
+  };
+}
+
+/// A summary of a run, printed at the end and optionally written as JSON.
+#[derive(Debug, Serialize)]
+pub struct Statistics
+{
+  /// The number of compounds of each kind, e.g. "class".
+  pub compounds: BTreeMap<String, usize>,
+  pub functions: usize,
+  pub enums: usize,
+  pub variables: usize,
+  pub macros: usize,
+  pub files_written: usize,
+  pub failed_files: usize,
+  pub ignored_constructs: usize,
+  /// The number of references in comments to entities that are missing from the input.
+  pub broken_references: usize,
+  pub elapsed_ms: u128,
+}
+
+impl Statistics
+{
+  /// Gathers the statistics of the run, where broken references are counted based on the reported
+  /// warnings.
+  pub fn collect(registry: &Registry, failed_files: usize, elapsed_ms: u128) -> Self
+  {
+    let mut compounds = BTreeMap::new();
+    for compound in registry.compounds.values() {
+      *compounds.entry(format!("{:?}", compound.kind).to_lowercase()).or_default() += 1;
+    }
+
+    Self {
+      compounds,
+      functions: registry.functions.len(),
+      enums: registry.enums.len(),
+      variables: registry.variables.len(),
+      macros: registry.defines.len(),
+      files_written: FILES_WRITTEN.load(Ordering::Relaxed),
+      failed_files,
+      ignored_constructs: diagnostics::ignored_count(),
+      broken_references: diagnostics::warning_count(WarningKind::BROKEN_REFERENCE),
+      elapsed_ms,
+    }
+  }
+
+  pub fn print(&self)
+  {
+    info!("\nSummary:");
+
+    let compounds: Vec<String> = self.compounds
+                                     .iter()
+                                     .map(|(kind, count)| match count {
+                                       1 => format!("1 {}", kind),
+                                       _ => format!("{} {}", count, pluralize(kind)),
+                                     })
+                                     .collect();
+    if compounds.is_empty() {
+      info!("  Compounds: none");
+    } else {
+      info!("  Compounds: {}", compounds.join(", "));
+    }
+
+    info!("  Members: {} function(s), {} enum(s), {} variable(s), {} macro(s)",
+          self.functions,
+          self.enums,
+          self.variables,
+          self.macros);
+    info!("  Files written: {}", self.files_written);
+    info!("  Files that failed to parse: {}", self.failed_files);
+    info!("  Ignored constructs: {}", self.ignored_constructs);
+    info!("  Broken references: {}", self.broken_references);
+    info!("  Total time: {} ms", self.elapsed_ms);
+  }
+
+  pub fn write_json(&self, path: &Path) -> io::Result<()>
+  {
+    let output = serde_json::to_string_pretty(self)?;
+    return fs::write(path, output + "\n");
+  }
+}
@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{self, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, RwLock, Weak};
+use std::{ptr, slice, str};
+
+use lazy_static::lazy_static;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+/// The size of the chunks that interned strings are stored in, in bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An interned string, such as a reference ID or a qualified name.
+///
+/// Symbols are cheap to clone, and are compared and hashed by their IDs rather than their contents.
+/// The interned strings are owned by an interner that is shared by all live symbols, which is
+/// freed once the last of them is dropped, e.g. along with the registry they are part of.
+#[derive(Clone)]
+pub struct Symbol
+{
+  id: u32,
+  /// The interned string, which lives in the arena of the interner as long as the symbol does.
+  name: &'static str,
+  /// The interner that owns the string, which is only absent for the empty string.
+  _interner: Option<Arc<RwLock<Interner>>>,
+}
+
+/// Stores interned strings next to each other in large chunks, instead of allocating each string
+/// separately. The chunks are freed when the arena is dropped.
+struct Arena
+{
+  /// The chunks, which are only accessed through raw pointers since strings in them are borrowed.
+  chunks: Vec<*mut [u8]>,
+  /// The number of used bytes in the last chunk.
+  used: usize,
+}
+
+impl Arena
+{
+  fn alloc(&mut self, text: &str) -> &'static str
+  {
+    let free = self.chunks.last().map_or(0, |chunk| chunk.len() - self.used);
+
+    // The rest of the current chunk is abandoned if the text doesn't fit, which wastes little
+    // space since most strings are much smaller than a chunk
+    if free < text.len() {
+      let chunk = vec![0; CHUNK_SIZE.max(text.len())].into_boxed_slice();
+      self.chunks.push(Box::into_raw(chunk));
+      self.used = 0;
+    }
+
+    let start = self.chunks.last().unwrap().cast::<u8>();
+
+    // The copied bytes don't overlap any strings handed out before, and are valid UTF-8 since
+    // they are copied from a string
+    unsafe {
+      let bytes = start.add(self.used);
+      ptr::copy_nonoverlapping(text.as_ptr(), bytes, text.len());
+      self.used += text.len();
+
+      return str::from_utf8_unchecked(slice::from_raw_parts(bytes, text.len()));
+    }
+  }
+}
+
+impl Drop for Arena
+{
+  fn drop(&mut self)
+  {
+    for chunk in self.chunks.drain(..) {
+      // The chunks were leaked from boxes, and no symbols that borrow from them are left
+      drop(unsafe { Box::from_raw(chunk) });
+    }
+  }
+}
+
+struct Interner
+{
+  ids: HashMap<&'static str, u32>,
+  count: u32,
+  arena: Arena,
+}
+
+// The chunks of the arena are only accessed through the lock of the interner
+unsafe impl Send for Interner {}
+unsafe impl Sync for Interner {}
+
+lazy_static! {
+  /// The interner of the live symbols, which is replaced by a new one once they are all dropped.
+  static ref CURRENT_INTERNER: RwLock<Weak<RwLock<Interner>>> = RwLock::new(Weak::new());
+}
+
+/// Returns the interner of the live symbols, or a new one if there are none.
+fn get_interner() -> Arc<RwLock<Interner>>
+{
+  if let Some(interner) = CURRENT_INTERNER.read().unwrap().upgrade() {
+    return interner;
+  }
+
+  let mut current = CURRENT_INTERNER.write().unwrap();
+
+  // Another thread may have created an interner since it was looked up
+  if let Some(interner) = current.upgrade() {
+    return interner;
+  }
+
+  let interner = Arc::new(RwLock::new(Interner {
+                                        ids: HashMap::new(),
+                                        count: 1,
+                                        arena: Arena { chunks: Vec::new(), used: 0 },
+                                      }));
+  *current = Arc::downgrade(&interner);
+
+  return interner;
+}
+
+impl Symbol
+{
+  /// The empty string, which is the default value of symbols.
+  pub const EMPTY: Symbol = Symbol { id: 0, name: "", _interner: None };
+
+  /// Returns the symbol of a string, which is interned if it wasn't already.
+  pub fn intern(name: &str) -> Self
+  {
+    if let Some(symbol) = Self::get(name) {
+      return symbol;
+    }
+
+    let interner = get_interner();
+    let mut strings = interner.write().unwrap();
+
+    // Another thread may have interned the string since it was looked up
+    let (name, id) = match strings.ids.get_key_value(name) {
+      Some((&name, &id)) => (name, id),
+      None => {
+        let name = strings.arena.alloc(name);
+        let id = strings.count;
+
+        strings.ids.insert(name, id);
+        strings.count += 1;
+
+        (name, id)
+      }
+    };
+
+    drop(strings);
+    return Self { id, name, _interner: Some(interner) };
+  }
+
+  /// Returns the symbol of a string if it has been interned, which is useful for lookups of
+  /// strings that may be unknown, since all known symbols are interned by then.
+  pub fn get(name: &str) -> Option<Self>
+  {
+    if name.is_empty() {
+      return Some(Self::EMPTY);
+    }
+
+    let interner = CURRENT_INTERNER.read().unwrap().upgrade()?;
+    let found = interner.read().unwrap().ids.get_key_value(name).map(|(&name, &id)| (name, id));
+
+    return found.map(|(name, id)| Self { id, name, _interner: Some(interner) });
+  }
+
+  pub fn as_str(&self) -> &str
+  {
+    return self.name;
+  }
+}
+
+impl Default for Symbol
+{
+  fn default() -> Self
+  {
+    return Self::EMPTY;
+  }
+}
+
+impl Deref for Symbol
+{
+  type Target = str;
+
+  fn deref(&self) -> &str
+  {
+    return self.name;
+  }
+}
+
+impl AsRef<str> for Symbol
+{
+  fn as_ref(&self) -> &str
+  {
+    return self.name;
+  }
+}
+
+impl PartialEq for Symbol
+{
+  fn eq(&self, other: &Self) -> bool
+  {
+    return self.id == other.id;
+  }
+}
+
+impl Eq for Symbol {}
+
+impl PartialEq<str> for Symbol
+{
+  fn eq(&self, other: &str) -> bool
+  {
+    return self.name == other;
+  }
+}
+
+impl PartialEq<&str> for Symbol
+{
+  fn eq(&self, other: &&str) -> bool
+  {
+    return self.name == *other;
+  }
+}
+
+impl Hash for Symbol
+{
+  fn hash<H: Hasher>(&self, state: &mut H)
+  {
+    self.id.hash(state);
+  }
+}
+
+impl PartialOrd for Symbol
+{
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+  {
+    return Some(self.cmp(other));
+  }
+}
+
+impl Ord for Symbol
+{
+  fn cmp(&self, other: &Self) -> Ordering
+  {
+    return self.name.cmp(other.name);
+  }
+}
+
+impl From<&str> for Symbol
+{
+  fn from(name: &str) -> Self
+  {
+    return Self::intern(name);
+  }
+}
+
+impl From<String> for Symbol
+{
+  fn from(name: String) -> Self
+  {
+    return Self::intern(&name);
+  }
+}
+
+impl From<&String> for Symbol
+{
+  fn from(name: &String) -> Self
+  {
+    return Self::intern(name);
+  }
+}
+
+impl fmt::Display for Symbol
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+  {
+    return f.write_str(self.name);
+  }
+}
+
+impl fmt::Debug for Symbol
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+  {
+    return fmt::Debug::fmt(self.name, f);
+  }
+}
+
+impl Serialize for Symbol
+{
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  {
+    return serializer.serialize_str(self.name);
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Symbol
+{
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+  {
+    return Ok(Self::intern(&String::deserialize(deserializer)?));
+  }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use minidom::Element;
+use minidom::NSChoice::Any as AnyNS;
+
+use crate::d2m::doxygen::RefID;
+use crate::d2m::error::{D2mError, D2mResult};
+use crate::d2m::links::{self, Index, Layout, SlugStyle};
+
+/// A symbol declared in a tag file.
+struct Symbol
+{
+  kind: String,
+  name: String,
+  /// The name of the symbol, qualified by its class or namespace when known.
+  qualified_name: String,
+  /// The ID of the compound whose documentation features the symbol, e.g. "classfoo".
+  anchor_compound: RefID,
+}
+
+/// The symbols of a Doxygen tag file, resolved to the URLs of their documentation.
+///
+/// The documentation of the tag file is assumed to have been generated with the default layout,
+/// i.e. the URLs mirror the pages that would be generated for the symbols in this project.
+pub struct TagFile
+{
+  /// The file name of the tag file, which is what external references are matched against.
+  name: String,
+  urls: HashMap<RefID, String>,
+}
+
+lazy_static! {
+  static ref TAG_FILES: Mutex<Vec<TagFile>> = Mutex::new(Vec::new());
+}
+
+/// Returns the ID that Doxygen assigns to an entity documented in the specified file, e.g.
+/// "classfoo" for "classfoo.html".
+fn get_file_id(filename: &str) -> RefID
+{
+  return RefID::from(filename.strip_suffix(".html").unwrap_or(filename));
+}
+
+fn get_child_text(elem: &Element, name: &str) -> Option<String>
+{
+  return elem.get_child(name, AnyNS).map(|child| child.text().trim().to_owned());
+}
+
+/// Returns the page that documents an entity of a compound, relative to the documentation root.
+fn get_compound_page(kind: &str, name: &str) -> Option<String>
+{
+  return match kind {
+    "class" | "struct" | "interface" => Some(format!("classes/{}", links::class_filename(name))),
+    "group" => Some(format!("groups/{}", links::group_filename(name))),
+    "namespace" => Some(format!("namespaces/{}", links::namespace_filename(name))),
+    _ => None,
+  };
+}
+
+/// Determines the URL of a symbol, relative to the base URL of the documentation.
+///
+/// Members are rendered on the pages of classes and groups, otherwise on the index pages.
+fn get_symbol_url(symbol: &Symbol,
+                  compounds: &HashMap<RefID, (String, String)>,
+                  slug_style: SlugStyle) -> Option<String>
+{
+  let anchor = |heading: &str| format!("#{}", links::slugify(heading, slug_style));
+
+  let owner = compounds.get(&symbol.anchor_compound);
+  if let Some(page) = owner.and_then(|(kind, name)| get_compound_page(kind, name)) {
+    return match symbol.kind.as_str() {
+      "function" | "enumeration" | "variable" => {
+        Some(format!("{}{}", page, anchor(&symbol.qualified_name)))
+      }
+      _ => Some(page),
+    };
+  }
+
+  let index = match symbol.kind.as_str() {
+    "function" => Index::FUNCTIONS,
+    "enumeration" => Index::ENUMS,
+    "define" => Index::MACROS,
+    _ => return None,
+  };
+
+  let heading = if index == Index::MACROS { &symbol.name } else { &symbol.qualified_name };
+  return Some(links::get_index_page(Layout::DEFAULT, index) + anchor(heading).as_str());
+}
+
+impl TagFile
+{
+  /// Loads a tag file, specified as "<path>=<base URL>".
+  pub fn load(spec: &str, slug_style: SlugStyle) -> D2mResult<Self>
+  {
+    return Self::read(spec, slug_style).map_err(|reason| D2mError::TAG_FILE {
+                                                   spec: spec.to_owned(),
+                                                   reason,
+                                                 });
+  }
+
+  fn read(spec: &str, slug_style: SlugStyle) -> Result<Self, String>
+  {
+    let (path, base_url) = spec.split_once('=')
+                               .ok_or_else(|| String::from("expected <file>=<url>"))?;
+    let path = Path::new(path);
+
+    let mut base_url = base_url.to_owned();
+    if !base_url.ends_with('/') {
+      base_url.push('/');
+    }
+
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let root = contents.parse::<Element>().map_err(|e| e.to_string())?;
+
+    // Maps the IDs of compounds to their kinds and names
+    let mut compounds = HashMap::new();
+    let mut symbols: HashMap<RefID, Symbol> = HashMap::new();
+
+    for compound in root.children().filter(|e| e.is("compound", AnyNS)) {
+      let kind = compound.attr("kind").unwrap_or_default().to_owned();
+      let name = get_child_text(compound, "name").unwrap_or_default();
+
+      if let Some(filename) = get_child_text(compound, "filename") {
+        let id = get_file_id(&filename);
+        compounds.insert(id.to_owned(), (kind.to_owned(), name.to_owned()));
+        symbols.insert(id.to_owned(), Symbol {
+          kind: kind.to_owned(),
+          name: name.to_owned(),
+          qualified_name: name.to_owned(),
+          anchor_compound: id,
+        });
+      }
+    }
+
+    for compound in root.children().filter(|e| e.is("compound", AnyNS)) {
+      let kind = compound.attr("kind").unwrap_or_default();
+      let scope = get_child_text(compound, "name").unwrap_or_default();
+      let is_scope = matches!(kind, "class" | "struct" | "union" | "interface" | "namespace");
+
+      for member in compound.children().filter(|e| e.is("member", AnyNS)) {
+        let name = get_child_text(member, "name").unwrap_or_default();
+        let anchor_file = get_child_text(member, "anchorfile").unwrap_or_default();
+        let anchor = get_child_text(member, "anchor").unwrap_or_default();
+
+        let id = RefID::from(format!("{}_1{}", get_file_id(&anchor_file), anchor));
+
+        // Members of groups and files are also listed by their classes or namespaces, which
+        // determine the qualified names
+        if symbols.contains_key(&id) && !is_scope {
+          continue;
+        }
+
+        symbols.insert(id, Symbol {
+          kind: member.attr("kind").unwrap_or_default().to_owned(),
+          qualified_name: if is_scope { format!("{}::{}", scope, name) } else { name.to_owned() },
+          name,
+          anchor_compound: get_file_id(&anchor_file),
+        });
+      }
+    }
+
+    let urls = symbols.iter()
+                      .filter_map(|(id, symbol)| {
+                        get_symbol_url(symbol, &compounds, slug_style)
+                          .map(|url| (id.to_owned(), format!("{}{}", base_url, url)))
+                      })
+                      .collect();
+
+    return Ok(Self {
+      name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+      urls,
+    });
+  }
+}
+
+/// Registers the tag files that external references are resolved against.
+pub fn register(tag_files: Vec<TagFile>)
+{
+  *TAG_FILES.lock().unwrap() = tag_files;
+}
+
+/// Forgets the registered tag files, e.g. before converting the input again.
+pub fn reset()
+{
+  TAG_FILES.lock().unwrap().clear();
+}
+
+/// Returns the URL of an external entity, where `external` is the tag file that Doxygen
+/// associated with the reference.
+pub fn resolve(external: &str, referenced_id: &str) -> Option<String>
+{
+  let name = Path::new(external).file_name()?.to_string_lossy();
+
+  // Entities that are listed by tag files have been interned when the tag files were loaded
+  let referenced_id = RefID::get(referenced_id)?;
+
+  return TAG_FILES.lock()
+                  .unwrap()
+                  .iter()
+                  .filter(|tag_file| tag_file.name == name)
+                  .find_map(|tag_file| tag_file.urls.get(&referenced_id).cloned());
+}
@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use tera::{Tera, Value};
+
+use crate::d2m::callouts::{self, AdmonitionStyle, CalloutFormat};
+use crate::d2m::dialect::Dialect;
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// The default templates, which are embedded in the executable.
+const DEFAULT_TEMPLATES: [(&str, &str); 5] = [
+  ("index.md", include_str!("templates/index.md")),
+  ("class.md", include_str!("templates/class.md")),
+  ("group.md", include_str!("templates/group.md")),
+  ("namespace.md", include_str!("templates/namespace.md")),
+  ("member.md", include_str!("templates/member.md")),
+];
+
+/// Returns a description of a template error, including the underlying causes.
+fn describe_error(error: &tera::Error) -> String
+{
+  let mut description = error.to_string();
+
+  let mut source = error.source();
+  while let Some(cause) = source {
+    description += &format!(": {}", cause);
+    source = cause.source();
+  }
+
+  return description;
+}
+
+/// The Tera templates used to render Markdown pages.
+pub struct Templates
+{
+  tera: Tera,
+  /// The Markdown dialect, which is available to all templates as "dialect".
+  dialect: Dialect,
+}
+
+impl Templates
+{
+  /// Creates the default templates.
+  pub fn new() -> Self
+  {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(DEFAULT_TEMPLATES).expect("Default templates must be valid");
+
+    let mut templates = Self { tera, dialect: Dialect::GFM };
+    templates.set_callout_format(CalloutFormat {
+      style: AdmonitionStyle::PLAIN,
+      custom: BTreeMap::new(),
+    });
+
+    return templates;
+  }
+
+  /// Registers the "callout" filter, which formats a note, warning, or a list of conditions in
+  /// the specified format, e.g. `{{ note | callout(kind="note") }}`.
+  pub fn set_callout_format(&mut self, format: CalloutFormat)
+  {
+    self.tera.register_filter("callout", move |value: &Value, args: &HashMap<String, Value>| {
+      let kind = args.get("kind")
+                     .and_then(Value::as_str)
+                     .ok_or_else(|| tera::Error::msg("The callout filter requires a kind"))?;
+
+      let (items, as_list) = match value {
+        Value::Array(values) => {
+          (values.iter().map(|v| v.as_str().unwrap_or_default().to_owned()).collect(), true)
+        }
+        _ => (vec![value.as_str().unwrap_or_default().to_owned()], false),
+      };
+
+      return Ok(Value::String(callouts::format_callout(&format, kind, &items, as_list)));
+    });
+  }
+
+  pub fn set_dialect(&mut self, dialect: Dialect)
+  {
+    self.dialect = dialect;
+  }
+
+  /// Adds a template from a string, e.g. from the configuration file.
+  pub fn add(&mut self, name: &str, source: &str) -> D2mResult<()>
+  {
+    return self.tera.add_raw_template(name, source).map_err(|e| D2mError::TEMPLATE {
+                                                       name: name.to_owned(),
+                                                       reason: describe_error(&e),
+                                                     });
+  }
+
+  /// Indicates whether there is a template with the specified name.
+  pub fn contains(&self, name: &str) -> bool
+  {
+    return self.tera.get_template_names().any(|n| n == name);
+  }
+
+  /// Loads the templates in a directory, which replace the default templates with the same file
+  /// names. Other files are also loaded, so that they can be included by the templates.
+  pub fn load(dir: &Path) -> D2mResult<Self>
+  {
+    return Self::read(dir).map_err(|reason| D2mError::TEMPLATES {
+                                     path: dir.to_path_buf(),
+                                     reason,
+                                   });
+  }
+
+  fn read(dir: &Path) -> Result<Self, String>
+  {
+    let mut templates = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+      let path = entry.map_err(|e| e.to_string())?.path();
+
+      if path.is_file() {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        templates.push((path, Some(name)));
+      }
+    }
+
+    let mut result = Self::new();
+    result.tera.add_template_files(templates).map_err(|e| describe_error(&e))?;
+
+    return Ok(result);
+  }
+
+  /// Renders a template, e.g. "class.md", with the specified context.
+  pub fn render(&self, name: &str, context: &tera::Context) -> io::Result<String>
+  {
+    let mut context = context.clone();
+    context.insert("dialect", self.dialect.name());
+
+    return self.tera
+               .render(name, &context)
+               .map_err(|e| io::Error::other(format!("Failed to render template {}: {}",
+                                                     name,
+                                                     describe_error(&e))));
+  }
+}
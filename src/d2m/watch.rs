@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{Event, RecursiveMode, Watcher};
+
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// How long to wait for more changes after a change, since Doxygen rewrites many files at once.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+
+/// Returns the XML files that were created, modified, or removed by an event.
+fn get_changed_files(event: notify::Result<Event>) -> Vec<PathBuf>
+{
+  return match event {
+    Ok(event) if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() => {
+      event.paths
+           .into_iter()
+           .filter(|path| path.extension().unwrap_or_default() == "xml")
+           .collect()
+    }
+    _ => Vec::new(),
+  };
+}
+
+/// Watches a directory for changes to XML files, calling the callback with the changed files.
+///
+/// Changes that happen in quick succession are reported together. This only returns if the
+/// directory can't be watched.
+pub fn watch(dir: &Path, mut on_change: impl FnMut(&[PathBuf])) -> D2mResult<()>
+{
+  let to_error = |err: notify::Error| D2mError::WATCH {
+    path: dir.to_path_buf(),
+    reason: err.to_string(),
+  };
+
+  let (sender, receiver) = mpsc::channel();
+  let mut watcher = notify::recommended_watcher(sender).map_err(to_error)?;
+  watcher.watch(dir, RecursiveMode::NonRecursive).map_err(to_error)?;
+
+  while let Ok(event) = receiver.recv() {
+    let mut changed = get_changed_files(event);
+
+    while let Ok(event) = receiver.recv_timeout(DEBOUNCE_DELAY) {
+      changed.extend(get_changed_files(event));
+    }
+
+    if !changed.is_empty() {
+      changed.sort();
+      changed.dedup();
+      on_change(&changed);
+    }
+  }
+
+  return Err(D2mError::WATCH {
+    path: dir.to_path_buf(),
+    reason: String::from("the watcher stopped unexpectedly"),
+  });
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str;
+
+use minidom::quick_xml::events::{BytesStart, Event};
+use minidom::quick_xml::Reader;
+use minidom::{Element, Error};
+
+use crate::d2m::error::{D2mError, D2mResult};
+
+/// Elements that the parser never looks at, which tend to make up a large part of the files.
+const SKIPPED_ELEMENTS: [&str; 14] = ["basecompoundref",
+                                      "collaborationgraph",
+                                      "derivedcompoundref",
+                                      "inbodydescription",
+                                      "incdepgraph",
+                                      "includedby",
+                                      "includes",
+                                      "inheritancegraph",
+                                      "invincdepgraph",
+                                      "listofallmembers",
+                                      "referencedby",
+                                      "references",
+                                      "reimplementedby",
+                                      "reimplements"];
+
+/// Indicates whether an element, and everything in it, is left out of the element tree.
+fn is_skipped(start: &BytesStart, parent: Option<&Element>) -> bool
+{
+  let name = start.local_name();
+
+  // Files feature their source code as a listing, which is unrelated to listings in comments
+  if name == b"programlisting" {
+    return parent.is_some_and(|parent| parent.name() == "compounddef");
+  }
+
+  return SKIPPED_ELEMENTS.iter().any(|skipped| skipped.as_bytes() == name);
+}
+
+fn build_element<R: BufRead>(reader: &Reader<R>, start: &BytesStart) -> minidom::Result<Element>
+{
+  let mut elem = Element::bare(str::from_utf8(start.local_name())?);
+
+  for attribute in start.attributes() {
+    let attribute = attribute?;
+    let key = str::from_utf8(attribute.key)?;
+
+    // Namespace declarations aren't attributes, and namespaces are ignored by the parser anyway
+    if key != "xmlns" && !key.starts_with("xmlns:") {
+      elem.set_attr(key, attribute.unescape_and_decode_value(reader)?);
+    }
+  }
+
+  return Ok(elem);
+}
+
+/// Adds a completed element to its parent, returning it instead if it is the root element.
+fn add_element(stack: &mut [Element], elem: Element) -> Option<Element>
+{
+  match stack.last_mut() {
+    Some(parent) => {
+      parent.append_child(elem);
+      return None;
+    }
+    None => return Some(elem),
+  }
+}
+
+fn read_root<R: BufRead>(reader: &mut Reader<R>) -> minidom::Result<Element>
+{
+  let mut buf = Vec::new();
+  let mut skipped_buf = Vec::new();
+  let mut stack: Vec<Element> = Vec::new();
+
+  loop {
+    let root = match reader.read_event(&mut buf)? {
+      Event::Start(ref start) if is_skipped(start, stack.last()) => {
+        reader.read_to_end(start.name(), &mut skipped_buf)?;
+        skipped_buf.clear();
+        None
+      }
+      Event::Start(ref start) => {
+        stack.push(build_element(reader, start)?);
+        None
+      }
+      Event::Empty(ref start) if is_skipped(start, stack.last()) => None,
+      Event::Empty(ref start) => add_element(&mut stack, build_element(reader, start)?),
+      Event::End(_) => {
+        let elem = stack.pop().ok_or(Error::InvalidElementClosed)?;
+        add_element(&mut stack, elem)
+      }
+      Event::Text(ref text) => {
+        let text = text.unescape_and_decode(reader)?;
+
+        if let Some(elem) = stack.last_mut().filter(|_| !text.is_empty()) {
+          elem.append_text_node(text);
+        }
+
+        None
+      }
+      Event::CData(ref data) => {
+        let text = reader.decode(data)?.to_owned();
+
+        if let Some(elem) = stack.last_mut().filter(|_| !text.is_empty()) {
+          elem.append_text_node(text);
+        }
+
+        None
+      }
+      Event::Eof => return Err(Error::EndOfDocument),
+      _ => None,
+    };
+
+    if let Some(root) = root {
+      return Ok(root);
+    }
+
+    buf.clear();
+  }
+}
+
+/// Reads the element tree of an XML file, without the elements that the parser doesn't use.
+///
+/// The file is read incrementally, so that only the retained parts of it are kept in memory,
+/// rather than both the complete contents and the complete element tree.
+pub fn read_element(path: &Path) -> D2mResult<Element>
+{
+  let file = File::open(path).map_err(|source| D2mError::READ {
+                                path: path.to_path_buf(),
+                                source,
+                              })?;
+
+  return read_element_from(BufReader::new(file), path);
+}
+
+/// Reads the element tree of an XML document from a reader, e.g. stdin, where the path is only
+/// used to describe the source of the document in errors.
+pub fn read_element_from<R: BufRead>(reader: R, path: &Path) -> D2mResult<Element>
+{
+  let mut reader = Reader::from_reader(reader);
+
+  return read_root(&mut reader).map_err(|err| D2mError::MALFORMED_XML {
+                                  path: path.to_path_buf(),
+                                  reason: err.to_string(),
+                                });
+}
@@ -0,0 +1,45 @@
+//! Converts the XML output of Doxygen to Markdown.
+//!
+//! The XML files are parsed into a [`Registry`] with [`parse_xml`], which is then rendered with
+//! [`generate_markdown`]. The other output formats and utilities are available in [`d2m`].
+//!
+//! With the "serde" feature, which is enabled by default, the registry and the entities in it
+//! implement `Serialize` and `Deserialize`, so the parsed model can be saved, e.g. as JSON, and
+//! loaded again without parsing the XML files.
+
+#![allow(clippy::needless_return,
+         clippy::write_with_newline,
+         clippy::upper_case_acronyms,
+         clippy::new_without_default)]
+
+pub mod d2m;
+
+use std::path::Path;
+
+pub use crate::d2m::doxygen::{AccessModifier, Class, Comment, Compound, CompoundKind, Define,
+                              Entity, Enum, EnumValue, Function, Location, RefID, Registry,
+                              Symbol, Variable};
+pub use crate::d2m::error::{D2mError, D2mResult};
+pub use crate::d2m::filter::FileFilter;
+pub use crate::d2m::generator::GeneratorOptions;
+
+/// Parses the Doxygen XML files in a directory, aborting at the first file that fails to parse.
+///
+/// References to entities that are missing from the registry are logged as warnings. The interned
+/// names are freed once the registry, and any symbols taken from it, are dropped.
+pub fn parse_xml(input_dir: &Path) -> D2mResult<Registry>
+{
+  let (registry, _) = d2m::parser::parse_xml(input_dir, &FileFilter::new(), false, None)?;
+
+  // The recorded references would otherwise keep the symbols of every parsed registry around
+  d2m::diagnostics::check_references(&registry);
+
+  return Ok(registry);
+}
+
+/// Generates the Markdown pages of the parsed entities in the output directory, which must exist.
+pub fn generate_markdown(output_dir: &Path, registry: &Registry, options: &GeneratorOptions)
+  -> D2mResult<()>
+{
+  return d2m::generator::generate_markdown(output_dir, registry, options);
+}
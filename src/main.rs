@@ -1,45 +1,1369 @@
-mod d2m;
+#![allow(clippy::needless_return, clippy::write_with_newline, clippy::upper_case_acronyms)]
 
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
 use std::fs;
-use std::io;
-use std::path::Path;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::SystemTime;
 
-use clap::Parser;
+use clap::{CommandFactory, ErrorKind, FromArgMatches, Parser, Subcommand, ValueSource};
+use glob::Pattern;
+use log::{error, info};
 use path_absolutize::*;
+use regex::Regex;
+use serde::Serialize;
 
-use crate::d2m::generator;
-use crate::d2m::parser;
+use doxy_to_md::d2m::aliases;
+use doxy_to_md::d2m::annotations::{self, AnnotationFormat};
+use doxy_to_md::d2m::archive;
+use doxy_to_md::d2m::autolinks;
+use doxy_to_md::d2m::cache::Cache;
+use doxy_to_md::d2m::compare::{self, ChangeKind, FileChange};
+use doxy_to_md::d2m::callouts::{AdmonitionStyle, CalloutFormat};
+use doxy_to_md::d2m::config::{self, Config};
+use doxy_to_md::d2m::confluence;
+use doxy_to_md::d2m::diagnostics::{self, Warning, WarningKind};
+use doxy_to_md::d2m::dialect::Dialect;
+use doxy_to_md::d2m::doxyfile::{self, Doxyfile};
+use doxy_to_md::d2m::docfx;
+use doxy_to_md::d2m::doxygen::{AccessModifier, CompoundKind, Entity, RefID, Registry};
+use doxy_to_md::d2m::error::{D2mError, D2mResult};
+use doxy_to_md::d2m::filter::{self, FileFilter};
+use doxy_to_md::d2m::generator::{self, PageKind};
+use doxy_to_md::d2m::hook;
+use doxy_to_md::d2m::linkcheck;
+use doxy_to_md::d2m::logging::{self, LogFormat};
+use doxy_to_md::d2m::links::{FilenameCase, Layout, LinkResolver, SlugStyle};
+use doxy_to_md::d2m::man;
+use doxy_to_md::d2m::navigation::OrphanPolicy;
+use doxy_to_md::d2m::org;
+use doxy_to_md::d2m::overrides;
+use doxy_to_md::d2m::parser;
+use doxy_to_md::d2m::profile;
+use doxy_to_md::d2m::progress;
+use doxy_to_md::d2m::sarif;
+use doxy_to_md::d2m::script::Script;
+use doxy_to_md::d2m::signature;
+use doxy_to_md::d2m::sitemap::{self, SiteUrlStyle};
+use doxy_to_md::d2m::stats::{self, Statistics};
+use doxy_to_md::d2m::tagfile::{self, TagFile};
+use doxy_to_md::d2m::templates::Templates;
+use doxy_to_md::d2m::watch;
+
+/// The supported output formats.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ArgEnum)]
+enum Format
+{
+  MARKDOWN,
+  CONFLUENCE,
+  ORG,
+  DOCFX,
+  MAN,
+}
+
+/// The outcomes of a run, where the discriminants are the exit codes. Note that 2 is used for
+/// invalid command line arguments.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Status
+{
+  SUCCESS = 0,
+  /// Any other failure, e.g. an invalid configuration or output that is out of date.
+  FAILURE = 1,
+  /// Input files could not be parsed.
+  PARSE_FAILURE = 3,
+  /// The output could not be generated, e.g. due to a template error.
+  GENERATION_FAILURE = 4,
+  /// There were warnings or ignored constructs that should fail the run.
+  WARNINGS = 5,
+  /// There were broken links that should fail the run.
+  BROKEN_LINKS = 6,
+}
+
+impl Status
+{
+  /// Returns the status of a run that was aborted by an error.
+  fn of_error(error: &D2mError) -> Self
+  {
+    if error.is_parse_failure() {
+      return Status::PARSE_FAILURE;
+    }
+
+    return match error {
+      D2mError::IO(_) | D2mError::TEMPLATES { .. } | D2mError::TEMPLATE { .. } => {
+        Status::GENERATION_FAILURE
+      }
+      _ => Status::FAILURE,
+    };
+  }
+
+  /// Returns the status of a run that should fail due to warnings, which is more specific if
+  /// there were broken links.
+  fn of_warnings(warnings: &[Warning]) -> Self
+  {
+    return match warnings.iter().any(|warning| warning.kind == WarningKind::BROKEN_LINK) {
+      true => Status::BROKEN_LINKS,
+      false => Status::WARNINGS,
+    };
+  }
+}
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
+struct Cli
+{
+  #[clap(subcommand)]
+  command: Option<Command>,
+
+  #[clap(flatten)]
+  args: Args,
+}
+
+/// The available subcommands, where "generate" is used if none is specified.
+#[allow(non_camel_case_types)]
+#[derive(Subcommand, Debug)]
+enum Command
+{
+  /// Convert the Doxygen XML files, which is the default if no subcommand is specified
+  GENERATE(Args),
+
+  /// Parse the Doxygen XML files and report any problems, without writing any output. If an
+  /// output directory is specified, fail if its contents differ from the generated output
+  CHECK(Args),
+
+  /// Parse the Doxygen XML files and print statistics about the documented entities
+  STATS(Args),
+
+  /// Verify the links in previously generated Markdown output
+  LINKS(Args),
+
+  /// Print the declarations, documentation, and pages of the entities with the specified name,
+  /// e.g. "basic_window::show"
+  QUERY(QueryArgs),
+
+  /// Regenerate the page of a single class, group, or namespace, which only parses the XML files
+  /// of the compound, the index, and the groups and namespaces
+  ONE(OneArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct QueryArgs
+{
+  /// The (partially) qualified name of the entities to look up
+  name: String,
+
+  /// Print the results as a JSON array, e.g. for editor plugins
+  #[clap(long)]
+  json: bool,
+
+  #[clap(flatten)]
+  args: Args,
+}
+
+#[derive(clap::Args, Debug)]
+struct OneArgs
+{
+  /// The reference ID of the compound, which is also the name of its XML file without extension,
+  /// e.g. "classfoo_1_1bar"
+  #[clap(long, conflicts_with = "name", required_unless_present = "name")]
+  refid: Option<String>,
+
+  /// The qualified name of the compound, e.g. "foo::bar", which is looked up in the index file
+  #[clap(long)]
+  name: Option<String>,
+
+  #[clap(flatten)]
+  args: Args,
+}
+
+impl Command
+{
+  fn args(&self) -> &Args
+  {
+    return match self {
+      Command::GENERATE(args) => args,
+      Command::CHECK(args) => args,
+      Command::STATS(args) => args,
+      Command::LINKS(args) => args,
+      Command::QUERY(query) => &query.args,
+      Command::ONE(one) => &one.args,
+    };
+  }
+
+  fn args_mut(&mut self) -> &mut Args
+  {
+    return match self {
+      Command::GENERATE(args) => args,
+      Command::CHECK(args) => args,
+      Command::STATS(args) => args,
+      Command::LINKS(args) => args,
+      Command::QUERY(query) => &mut query.args,
+      Command::ONE(one) => &mut one.args,
+    };
+  }
+
+  /// Returns the directory options that the subcommand can't do without.
+  fn required_dirs(&self) -> &'static [&'static str]
+  {
+    return match self {
+      Command::GENERATE(_) | Command::ONE(_) => &["input-dir", "output-dir"],
+      Command::CHECK(_) | Command::STATS(_) | Command::QUERY(_) => &["input-dir"],
+      Command::LINKS(_) => &["output-dir"],
+    };
+  }
+}
+
+#[derive(clap::Args, Debug, Clone)]
 struct Args {
+  /// The directory with the Doxygen XML files, or a .zip, .tar, or .tar.gz archive of them,
+  /// required unless set in the configuration file
+  #[clap(short, long)]
+  input_dir: Option<String>,
+
+  /// The directory that the output is written to, required unless set in the configuration file
   #[clap(short, long)]
-  input_dir: String,
+  output_dir: Option<String>,
+
+  /// A Doxygen configuration file, which provides the defaults of the input directory and excluded
+  /// symbols, along with the project name
+  #[clap(long)]
+  doxyfile: Option<String>,
+
+  /// Run Doxygen with the Doxyfile before the conversion, where the Doxyfile is overridden so that
+  /// only XML output is generated
+  #[clap(long)]
+  run_doxygen: bool,
+
+  /// The output format, most options only apply to Markdown output
+  #[clap(long, arg_enum, default_value = "markdown")]
+  format: Format,
+
+  /// The manual section of the generated man pages, e.g. "3" or "3cpp"
+  #[clap(long, default_value = "3")]
+  man_section: String,
+
+  /// The layout of the generated files
+  #[clap(long, arg_enum, default_value = "default")]
+  layout: Layout,
+
+  /// The conventions used to compute heading anchors, which should match the site generator
+  #[clap(long, arg_enum, default_value = "github")]
+  slug_style: SlugStyle,
+
+  /// How output file names are compared when making sure that they are unique, "auto" is
+  /// case-insensitive on Windows and macOS
+  #[clap(long, arg_enum, default_value = "auto")]
+  filename_case: FilenameCase,
+
+  /// Skip input files that fail to parse instead of aborting
+  #[clap(long)]
+  keep_going: bool,
+
+  /// Exit with an error if any input construct could not be converted
+  #[clap(long)]
+  strict: bool,
+
+  /// The least visible members that are emitted, i.e. "public" omits protected and private members
+  #[clap(long, arg_enum, default_value = "private")]
+  max_visibility: AccessModifier,
+
+  /// Show badges with the access, "static", and "virtual" specifiers below member headings
+  #[clap(long)]
+  badges: bool,
+
+  /// Link the documented classes and enums named by the return and parameter types of functions,
+  /// in a line below their signatures
+  #[clap(long)]
+  type_links: bool,
+
+  /// The flavor of Markdown to emit, which affects tables, escapes, and lists
+  #[clap(long, arg_enum, default_value = "gfm")]
+  dialect: Dialect,
+
+  /// The syntax of notes, warnings, and conditions in comments
+  #[clap(long, arg_enum, default_value = "plain")]
+  admonitions: AdmonitionStyle,
+
+  /// What to do with compounds that aren't featured in any module or namespace
+  #[clap(long, arg_enum, default_value = "ignore")]
+  orphans: OrphanPolicy,
+
+  /// Only emit the signatures and briefs of entities, for compact cheat sheet style pages
+  #[clap(long)]
+  minimal: bool,
+
+  /// List the free functions of all modules in a table on the main index
+  #[clap(long)]
+  functions_by_module: bool,
+
+  /// Only generate the specified kinds of pages, e.g. "classes,namespaces", leaving other files in
+  /// the output directory as they are
+  #[clap(long, arg_enum, use_value_delimiter = true)]
+  only: Vec<PageKind>,
+
+  /// The only compound to generate a page for, set by the "one" subcommand
+  #[clap(skip)]
+  compound: Option<String>,
+
+  /// Split the class and symbol indexes into one page per initial letter when they list more
+  /// than the given number of entries
+  #[clap(long)]
+  split_indexes: Option<usize>,
+
+  /// Emit a table of contents at the top of class, group, and namespace pages
+  #[clap(long)]
+  toc: bool,
+
+  /// Emit a navigation fragment for MkDocs, called mkdocs-nav.yml
+  #[clap(long)]
+  mkdocs_nav: bool,
+
+  /// Emit a SUMMARY.md file (and book.toml, if missing) so that the output can be built with mdBook
+  #[clap(long)]
+  mdbook: bool,
+
+  /// Emit a sidebar for VitePress, called vitepress-sidebar.json
+  #[clap(long)]
+  vitepress_sidebar: bool,
+
+  /// Emit a map of the pages and anchors of all entities, called symbols.json
+  #[clap(long)]
+  symbol_map: bool,
+
+  /// Emit an llms.txt file that summarizes the API with links to its pages, for tools based on
+  /// language models
+  #[clap(long)]
+  llms_txt: bool,
+
+  /// Emit an llms-full.txt file with the contents of all pages, in navigation order
+  #[clap(long)]
+  llms_full_txt: bool,
+
+  /// Emit wiki links and alias front matter for use in an Obsidian vault
+  #[clap(long, conflicts_with_all = &["docusaurus", "front-matter"])]
+  obsidian: bool,
+
+  /// Emit page front matter and a sidebar, called sidebar.json, for Docusaurus
+  #[clap(long)]
+  docusaurus: bool,
+
+  /// Emit page front matter for the specified site generator
+  #[clap(long, arg_enum, conflicts_with = "docusaurus")]
+  front_matter: Option<generator::FrontMatterStyle>,
+
+  /// A URL or path that links to other pages are rooted at, e.g. "/docs/api/", instead of
+  /// relative links, for output that is embedded under a site prefix
+  #[clap(long)]
+  link_base: Option<String>,
+
+  /// The URL that the generated pages are hosted at, used to write a sitemap.xml file
+  #[clap(long)]
+  site_url: Option<String>,
+
+  /// How the URLs of pages in the sitemap are derived from their paths
+  #[clap(long, arg_enum, default_value = "html")]
+  site_url_style: SiteUrlStyle,
+
+  /// Link names in the std namespace to cppreference.com
+  #[clap(long)]
+  cppreference: bool,
+
+  /// Directory with Tera templates that replace the default page templates with the same names
+  #[clap(long)]
+  templates: Option<String>,
+
+  /// Rhai script with functions that customize the Markdown output, e.g. "member_heading", which
+  /// may change function headings, add sections to pages, and leave out members
+  #[clap(long)]
+  script: Option<String>,
+
+  /// A Doxygen tag file and the URL of its Markdown documentation, e.g. "dep.tag=https://...",
+  /// used to link references to external entities. May be specified several times
+  #[clap(long = "tagfile")]
+  tag_files: Vec<String>,
+
+  /// Only parse the XML files whose names match the glob pattern, e.g. "group_*.xml". May be
+  /// specified several times
+  #[clap(long, parse(try_from_str = Pattern::new))]
+  include: Vec<Pattern>,
+
+  /// Skip the XML files whose names match the glob pattern, e.g. "dir_*.xml", even if they are
+  /// included. May be specified several times
+  #[clap(long, parse(try_from_str = Pattern::new))]
+  exclude: Vec<Pattern>,
+
+  /// Leave out the classes, namespaces, and members whose qualified names match the regular
+  /// expression, e.g. "detail::.*|.*_impl", along with everything in the matching scopes
+  #[clap(long, parse(try_from_str = filter::parse_symbol_pattern))]
+  exclude_symbols: Option<Regex>,
+
+  /// Verify that all relative links in the generated Markdown refer to existing pages and headings
+  #[clap(long)]
+  check_links: bool,
+
+  /// Exit with an error if there were any warnings or ignored constructs
+  #[clap(long)]
+  fail_on_warnings: bool,
+
+  /// Write a manifest.json file that lists every output file with its source entity and checksum
+  #[clap(long)]
+  manifest: bool,
+
+  /// Write the summary of the run as JSON to the specified file
+  #[clap(long)]
+  stats_json: Option<String>,
+
+  /// Print the time spent in each stage of the run, along with the specified number of input files
+  /// that took the longest to parse, 10 by default
+  #[clap(long, value_name = "COUNT", min_values = 0, default_missing_value = "10")]
+  profile: Option<usize>,
 
+  /// Write the undocumented public members, files that failed to parse, warnings, and ignored
+  /// constructs as a SARIF log to the specified file, e.g. for code scanning dashboards
+  #[clap(long)]
+  sarif: Option<String>,
+
+  /// Keep a cache in the output directory, called .doxy-to-md-cache, so that subsequent runs only
+  /// parse changed XML files and only write pages whose contents changed
+  #[clap(long)]
+  incremental: bool,
+
+  /// Keep running and regenerate the output whenever the XML files change, which implies
+  /// --incremental
+  #[clap(long)]
+  watch: bool,
+
+  /// Read the XML file of a single class, group, or namespace from stdin, and write its Markdown
+  /// page to stdout, instead of using the input and output directories
+  #[clap(long, conflicts_with_all = &["watch", "dry-run"])]
+  stdin: bool,
+
+  /// Generate the output without writing it, and list the files in the output directory that
+  /// would be created, modified, or deleted, i.e. that aren't generated anymore
+  #[clap(long, conflicts_with = "watch")]
+  dry_run: bool,
+
+  /// Only print errors, e.g. no progress, warnings, or summary
+  #[clap(short, long, conflicts_with = "verbose")]
+  quiet: bool,
+
+  /// Print details about each file, or each member if specified twice
+  #[clap(short, long, parse(from_occurrences))]
+  verbose: usize,
+
+  /// The format of the console output, where "json" emits one JSON object per line, including an
+  /// event for every parsed and written file
+  #[clap(long, arg_enum, default_value = "text")]
+  log_format: LogFormat,
+
+  /// Print annotations for the files that failed to parse, warnings, and ignored constructs, e.g.
+  /// "github" for workflow commands that show the problems on pull requests
+  #[clap(long, arg_enum)]
+  annotations: Option<AnnotationFormat>,
+
+  /// Path to a TOML configuration file, "doxy-to-md.toml" in the working directory is used by
+  /// default if it exists
   #[clap(short, long)]
-  output_dir: String,
+  config: Option<String>,
+}
+
+/// Parses the command line arguments, which override the options in the configuration file.
+fn parse_args() -> D2mResult<(Command, Config)>
+{
+  let matches = Cli::command().get_matches();
+  let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+  let (subcommand, matches) = match matches.subcommand() {
+    Some((name, sub_matches)) => (Some(name), sub_matches),
+    None => (None, &matches),
+  };
+
+  let args = cli.command.as_ref().map_or(&cli.args, Command::args);
+
+  let config_path = match &args.config {
+    Some(path) => Some(PathBuf::from(path)),
+    None => Some(PathBuf::from(config::DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+  };
+
+  let mut config = match &config_path {
+    Some(path) => Config::load(path)?,
+    None => Config::default(),
+  };
+
+  let command = Cli::command();
+  let mut overridden = Vec::new();
+
+  for name in config.options.keys() {
+    match command.get_arguments().find(|arg| arg.get_long() == Some(name)) {
+      Some(arg) if name != "config" => {
+        if matches.value_source(arg.get_id()) == Some(ValueSource::CommandLine) {
+          overridden.push(name.to_owned());
+        }
+      }
+      _ => {
+        return Err(D2mError::CONFIG {
+          path: config_path.unwrap_or_default(),
+          reason: format!("unknown option '{}'", name),
+        });
+      }
+    }
+  }
+
+  // Options that are specified on the command line take precedence over the configured ones
+  config.options.retain(|name, _| !overridden.iter().any(|o| o == name));
+
+  // The Doxyfile provides defaults for options that are specified neither on the command line nor
+  // in the configuration file
+  let doxyfile_path = match &args.doxyfile {
+    Some(path) => Some(path.to_owned()),
+    None => config.options.get("doxyfile").and_then(|value| value.as_str()).map(str::to_owned),
+  };
+
+  if let Some(path) = doxyfile_path {
+    let doxyfile = Doxyfile::load(Path::new(&path))?;
+    let defaults = [("input-dir", Some(doxyfile.get_xml_dir().to_string_lossy().to_string())),
+                    ("exclude-symbols", doxyfile.get_exclude_symbols())];
+
+    for (name, value) in defaults {
+      let arg = command.get_arguments().find(|arg| arg.get_long() == Some(name)).unwrap();
+      let is_specified = config.options.contains_key(name)
+                         || matches.value_source(arg.get_id()) == Some(ValueSource::CommandLine);
+
+      if let Some(value) = value.filter(|_| !is_specified) {
+        config.options.insert(name.to_owned(), toml::Value::String(value));
+      }
+    }
+
+    if config.project_name.is_none() {
+      config.project_name = doxyfile.get_project_name();
+    }
+
+    // Aliases in the configuration file replace the ones with the same names in the Doxyfile
+    for (name, expansion) in doxyfile.get_aliases() {
+      config.aliases.entry(name).or_insert_with(|| aliases::to_markdown(&expansion));
+    }
+  }
+
+  // The configured options are inserted after the subcommand, if there is one
+  let skipped = if subcommand.is_some() { 2 } else { 1 };
+
+  let mut arguments: Vec<OsString> = env::args_os().take(skipped).collect();
+  arguments.extend(config.get_option_arguments().into_iter().map(OsString::from));
+  arguments.extend(env::args_os().skip(skipped));
+
+  let cli = Cli::try_parse_from(arguments).unwrap_or_else(|err| err.exit());
+  let command = cli.command.unwrap_or(Command::GENERATE(cli.args));
+  let args = command.args();
+
+  // Query results and pages read from stdin are printed to stdout, so other output is only shown
+  // if requested, and then on stderr in the case of pages
+  let prints_output = matches!(command, Command::QUERY(_)) || args.stdin;
+  let quiet = args.quiet || (prints_output && args.verbose == 0);
+  logging::configure(args.log_format, quiet, args.verbose);
+  annotations::set_format(args.annotations);
+
+  if args.stdin {
+    logging::redirect_to_stderr();
+  }
+
+  // Progress bars would only garble the output when it is redirected or more detailed
+  progress::set_enabled(io::stdout().is_terminal()
+                        && args.log_format == LogFormat::TEXT
+                        && !quiet
+                        && args.verbose == 0);
+
+  if let Some(path) = &config_path {
+    info!("Configuration file: {}", path.display());
+  }
+
+  let exit_with_error = |kind: ErrorKind, message: String| -> ! {
+    let mut cli_command = Cli::command();
+    cli_command.build();
+    let command = match subcommand {
+      Some(name) => cli_command.find_subcommand_mut(name).unwrap(),
+      None => &mut cli_command,
+    };
+
+    command.error(kind, message).exit();
+  };
+
+  if args.run_doxygen && args.doxyfile.is_none() {
+    exit_with_error(ErrorKind::MissingRequiredArgument,
+                    String::from("--run-doxygen requires a Doxyfile, which must be specified with \
+                                  --doxyfile or in the configuration file"));
+  }
+
+  if args.stdin && !matches!(command, Command::GENERATE(_)) {
+    exit_with_error(ErrorKind::ArgumentConflict,
+                    String::from("--stdin is only supported when generating output"));
+  }
+
+  if args.stdin && args.format != Format::MARKDOWN {
+    exit_with_error(ErrorKind::ArgumentConflict,
+                    String::from("--stdin only supports the Markdown format"));
+  }
+
+  for (name, value) in [("input-dir", &args.input_dir), ("output-dir", &args.output_dir)] {
+    if value.is_none() && command.required_dirs().contains(&name) && !args.stdin {
+      let message = format!("--{} must be specified on the command line or in the \
+                             configuration file",
+                            name);
+      exit_with_error(ErrorKind::MissingRequiredArgument, message);
+    }
+  }
+
+  return Ok((command, config));
 }
 
-fn main() -> io::Result<()> {
-  let args = Args::parse();
-  let input_dir = Path::new(&args.input_dir).absolutize()?.to_path_buf();
-  let output_dir = Path::new(&args.output_dir).absolutize()?.to_path_buf();
+/// Returns the absolute path of a directory option, which parse_args makes sure is specified.
+fn get_dir(dir: &Option<String>) -> D2mResult<PathBuf>
+{
+  let dir = Path::new(dir.as_deref().unwrap_or_default()).absolutize()?.to_path_buf();
+  assert!(dir.is_absolute());
 
-  assert!(input_dir.is_absolute());
-  assert!(output_dir.is_absolute());
+  return Ok(dir);
+}
 
-  println!("Input directory: {}", input_dir.display());
-  println!("Output directory: {}", output_dir.display());
+fn load_tag_files(args: &Args) -> D2mResult<()>
+{
+  let mut tag_files = Vec::new();
+  for spec in &args.tag_files {
+    tag_files.push(TagFile::load(spec, args.slug_style)?);
+  }
+  tagfile::register(tag_files);
+
+  return Ok(());
+}
 
+/// Parses the XML files in the input directory, along with the referenced tag files.
+fn load_registry(args: &Args, input_dir: &Path, cache: Option<&mut Cache>)
+  -> D2mResult<(Registry, Vec<D2mError>)>
+{
   if !input_dir.exists() {
-    panic!("Input directory does not exist!");
+    return Err(D2mError::MISSING_INPUT(input_dir.to_path_buf()));
   }
 
-  // Makes sure that the directories we'll write to exist
+  load_tag_files(args)?;
+
+  let mut filter = FileFilter::new();
+  filter.include = args.include.clone();
+  filter.exclude = args.exclude.clone();
+
+  // Besides the file of a single compound, only the files of groups and namespaces are parsed,
+  // which determine where the compound is featured. The others are still declared by the index
+  if let Some(id) = &args.compound {
+    // Escaped patterns are always valid
+    let file = Pattern::new(&format!("{}.xml", Pattern::escape(id))).unwrap();
+    let scopes = ["group__*.xml", "namespace*.xml"].map(|pattern| Pattern::new(pattern).unwrap());
+
+    filter.include = [file].into_iter().chain(scopes).collect();
+    filter.keep_skipped = true;
+  }
+
+  let (mut registry, failures) = parser::parse_xml(input_dir, &filter, args.keep_going, cache)?;
+
+  if let Some(pattern) = &args.exclude_symbols {
+    let _stage = profile::stage("Symbol exclusion");
+    filter::exclude_symbols(&mut registry, pattern);
+  }
+
+  if let Some(path) = &args.script {
+    let _stage = profile::stage("Member skipping");
+    Script::load(Path::new(path))?.skip_members(&mut registry)?;
+  }
+
+  let stage = profile::stage("Reference checking");
+  diagnostics::check_references(&registry);
+  drop(stage);
+
+  stats::set_filename_case(args.filename_case);
+  registry.remove_hidden_members(args.max_visibility);
+
+  return Ok((registry, failures));
+}
+
+/// Applies the parts of the configuration that change the parsed entities, i.e. the aliases and
+/// compound overrides, returning the paths of the pages that replace the ones from the layout.
+fn apply_config(registry: &mut Registry, config: &Config) -> HashMap<RefID, String>
+{
+  let aliases = config.get_aliases();
+  if !aliases.is_empty() {
+    let _stage = profile::stage("Alias expansion");
+    aliases::expand_aliases(registry, &aliases);
+  }
+
+  if config.compounds.is_empty() {
+    return HashMap::new();
+  }
+
+  let _stage = profile::stage("Compound overrides");
+  return overrides::apply_overrides(registry, &config.compounds);
+}
+
+/// Reports the files that failed to parse, warnings, and ignored constructs, returning the status
+/// of the run, which is a failure if any of them should fail the run.
+fn report_problems(args: &Args, registry: &Registry, failures: &[D2mError]) -> D2mResult<Status>
+{
+  if !failures.is_empty() {
+    let files: Vec<String> = failures.iter().map(|failure| format!("\n  {}", failure)).collect();
+    error!("failed to parse {} file(s):{}", failures.len(), files.concat());
+  }
+
+  let warnings = diagnostics::warnings();
+  let ignored = diagnostics::take_ignored();
+  diagnostics::print_warning_summary(&warnings, &ignored);
+
+  if args.strict && !ignored.is_empty() {
+    diagnostics::print_ignored_report(&ignored);
+  }
+
+  annotations::print_problems(failures, &warnings, &ignored);
+
+  if let Some(path) = &args.sarif {
+    sarif::write_sarif(Path::new(path), registry, failures, &warnings, &ignored)?;
+  }
+
+  if !failures.is_empty() {
+    return Ok(Status::PARSE_FAILURE);
+  } else if (args.strict && !ignored.is_empty())
+            || (args.fail_on_warnings && (!warnings.is_empty() || !ignored.is_empty()))
+  {
+    return Ok(Status::of_warnings(&warnings));
+  }
+
+  return Ok(Status::SUCCESS);
+}
+
+/// Returns the options of the Markdown generator, and sets the format of function signatures.
+fn get_generator_options(args: &Args, config: Config) -> D2mResult<generator::GeneratorOptions>
+{
+  let mut options = generator::GeneratorOptions::new();
+  options.replacements = config.get_replacements();
+  options.project_name = config.project_name;
+  options.layout = args.layout;
+  options.slug_style = args.slug_style;
+  options.dialect = args.dialect;
+  options.filename_scheme = config.filename_scheme;
+  options.filename_case = args.filename_case;
+  options.link_base = args.link_base.to_owned();
+  options.toc = args.toc;
+  options.badges = args.badges;
+  options.type_links = args.type_links;
+  options.orphans = args.orphans;
+  options.split_indexes = args.split_indexes;
+  options.minimal = args.minimal;
+  options.functions_by_module = args.functions_by_module;
+  options.only = args.only.clone();
+  options.compound = args.compound.as_deref().map(RefID::from);
+  options.page_navigation = config.page_navigation;
+  options.provenance = config.provenance;
+  options.mkdocs_nav = args.mkdocs_nav;
+  options.mdbook = args.mdbook;
+  options.vitepress_sidebar = args.vitepress_sidebar;
+  options.symbol_map = args.symbol_map;
+  options.llms_txt = args.llms_txt;
+  options.llms_full_txt = args.llms_full_txt;
+  options.obsidian = args.obsidian;
+  options.docusaurus = args.docusaurus;
+  options.nav_prefix = config.nav_prefix;
+  options.front_matter = args.front_matter;
+  options.front_matter_format = config.front_matter_format;
+  options.extra_front_matter = config.front_matter;
+  options.autolinks = config.autolinks.into_iter().collect();
+
+  // Links configured for the standard library take precedence
+  if args.cppreference && !options.autolinks.iter().any(|(prefix, _)| prefix == "std::") {
+    options.autolinks.push((String::from("std::"), String::from(autolinks::CPPREFERENCE_URL)));
+  }
+
+  if let Some(dir) = &args.templates {
+    options.templates = Templates::load(Path::new(dir))?;
+  }
+
+  if let Some(path) = &args.script {
+    options.script = Some(Script::load(Path::new(path))?);
+  }
+
+  let decorations = [(generator::PAGE_HEADER_TEMPLATE, &config.page_header),
+                     (generator::PAGE_FOOTER_TEMPLATE, &config.page_footer)];
+
+  for (name, source) in decorations {
+    if let Some(source) = source {
+      options.templates.add(name, source)?;
+    }
+  }
+
+  options.templates.set_dialect(args.dialect);
+  options.templates.set_callout_format(CalloutFormat {
+    style: args.admonitions,
+    custom: config.callouts,
+  });
+
+  signature::set_format(config.signatures);
+
+  return Ok(options);
+}
+
+/// Converts the XML files, returning the status of the run, which may still be a failure, e.g.
+/// due to skipped files.
+fn generate(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let start_time = SystemTime::now();
+
+  let input_dir = get_dir(&args.input_dir)?;
+  let output_dir = get_dir(&args.output_dir)?;
+
+  info!("Input directory: {}", input_dir.display());
+  info!("Output directory: {}", output_dir.display());
+
+  if !input_dir.exists() {
+    return Err(D2mError::MISSING_INPUT(input_dir));
+  }
+
+  // Makes sure that the directory we'll write to exists, subdirectories are created as needed
   fs::create_dir_all(&output_dir)?;
-  fs::create_dir_all(output_dir.join("groups"))?;
-  fs::create_dir_all(output_dir.join("classes"))?;
 
-  let registry = parser::parse_xml(&input_dir);
-  generator::generate_markdown(&output_dir, &registry)
+  // The cache only describes complete runs, so it is ignored when generating a single page
+  let is_incremental = (args.incremental || args.watch) && args.compound.is_none();
+  let mut cache = is_incremental.then(|| Cache::load(&output_dir, &args.tag_files));
+  let (mut registry, failures) = load_registry(args, &input_dir, cache.as_mut())?;
+  let page_paths = apply_config(&mut registry, &config);
+
+  if let Some(cache) = &cache {
+    stats::set_previous_outputs(&output_dir, cache.get_outputs());
+  }
+
+  let post_hook = config.post_hook.clone();
+  let mut options = get_generator_options(args, config)?;
+  options.page_paths = page_paths;
+
+  let stage = profile::stage(match args.format {
+    Format::MARKDOWN => "Markdown generation",
+    Format::CONFLUENCE => "Confluence generation",
+    Format::ORG => "Org generation",
+    Format::DOCFX => "DocFX generation",
+    Format::MAN => "Man page generation",
+  });
+
+  match args.format {
+    Format::MARKDOWN => generator::generate_markdown(&output_dir, &registry, &options)?,
+    Format::CONFLUENCE => confluence::generate_confluence(&output_dir, &registry)?,
+    Format::ORG => org::generate_org(&output_dir, &registry)?,
+    Format::DOCFX => docfx::generate_docfx(&output_dir, &registry, args.filename_case)?,
+    Format::MAN => {
+      man::generate_man(&output_dir, &registry, &args.man_section, args.filename_case)?
+    }
+  }
+
+  drop(stage);
+
+  if let Some(site_url) = args.site_url.as_ref().filter(|_| args.format == Format::MARKDOWN) {
+    sitemap::emit_sitemap(&output_dir, site_url, args.site_url_style)?;
+  }
+
+  if args.manifest {
+    stats::write_manifest(&output_dir)?;
+  }
+
+  if args.check_links && args.format == Format::MARKDOWN {
+    let _stage = profile::stage("Link checking");
+    linkcheck::check_links(&output_dir, args.slug_style, args.link_base.as_deref())?;
+  }
+
+  if let Some(hook) = &post_hook {
+    let _stage = profile::stage("Post-generation hook");
+    hook::run_post_hook(hook, &output_dir, &stats::get_manifest(&output_dir))?;
+  }
+
+  if let Some(cache) = &mut cache {
+    cache.set_outputs(stats::get_outputs(&output_dir));
+    cache.save(&output_dir)?;
+  }
+
+  let elapsed_ms = start_time.elapsed().map_or(0, |duration| duration.as_millis());
+  let stats = Statistics::collect(&registry, failures.len(), elapsed_ms);
+  stats.print();
+
+  if let Some(path) = &args.stats_json {
+    stats.write_json(Path::new(path))?;
+  }
+
+  return report_problems(args, &registry, &failures);
+}
+
+/// Converts the XML files, and then converts them again whenever they change.
+fn watch(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let regenerate = || {
+    // Failures are reported without stopping, since they may be fixed by the next change
+    if let Err(err) = generate(args, config.clone()) {
+      error!("{}", err);
+      annotations::print_error(&err);
+    }
+
+    if let Some(count) = args.profile {
+      profile::print_report(count);
+    }
+  };
+
+  regenerate();
+
+  let input_dir = get_dir(&args.input_dir)?;
+  info!("\nWatching {} for changes, press Ctrl+C to stop", input_dir.display());
+
+  watch::watch(&input_dir, |changed| {
+    let files: Vec<String> = changed.iter().map(|path| format!("\n  {}", path.display())).collect();
+    info!("\nDetected changes to {} file(s):{}", changed.len(), files.concat());
+
+    // Nothing refers to the symbols of the previous conversion after this, so the strings they
+    // interned are freed before the input is parsed again
+    diagnostics::reset();
+    stats::reset();
+    profile::reset();
+    tagfile::reset();
+    regenerate();
+  })?;
+
+  return Ok(Status::SUCCESS);
+}
+
+/// Generates the output into a temporary directory, returning the differences compared to the
+/// output directory, along with the status of the conversion.
+fn generate_to_temp_dir(args: &Args, mut config: Config) -> D2mResult<(Status, Vec<FileChange>)>
+{
+  // The hook may have side effects, e.g. uploading the output, which a preview must not have
+  config.post_hook = None;
+
+  let output_dir = get_dir(&args.output_dir)?;
+  let temp_dir = tempfile::Builder::new().prefix("doxy-to-md-").tempdir()?;
+
+  let mut temp_args = args.clone();
+  temp_args.output_dir = Some(temp_dir.path().to_string_lossy().to_string());
+  temp_args.incremental = false;
+
+  let result = generate(&temp_args, config).and_then(|status| {
+    let mut changes = compare::compare_dirs(&output_dir, temp_dir.path())?;
+
+    // Other files are left as they are when only some pages are generated
+    if !args.only.is_empty() || args.compound.is_some() {
+      changes.retain(|change| change.kind != ChangeKind::DELETED);
+    }
+
+    Ok((status, changes))
+  });
+
+  // The temporary directory is removed even if the generation failed
+  temp_dir.close()?;
+
+  return result;
+}
+
+/// Generates the output without writing it, listing the files that would be changed instead.
+fn dry_run(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let output_dir = get_dir(&args.output_dir)?;
+  let (status, changes) = generate_to_temp_dir(args, config)?;
+
+  if changes.is_empty() {
+    info!("\nDry run, no files in {} would be changed", output_dir.display());
+  } else {
+    info!("\nDry run, {} file(s) in {} would be changed:", changes.len(), output_dir.display());
+
+    for change in &changes {
+      info!("  {}", change);
+    }
+  }
+
+  return Ok(status);
+}
+
+/// Parses the input without writing any output, failing if there are any problems with it.
+///
+/// If an output directory is specified, the output is generated into a temporary directory
+/// instead, and compared with the contents of the output directory, failing if they differ.
+fn check(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let status = match args.output_dir {
+    None => {
+      let input_dir = get_dir(&args.input_dir)?;
+      info!("Input directory: {}", input_dir.display());
+
+      let (registry, failures) = load_registry(args, &input_dir, None)?;
+      report_problems(args, &registry, &failures)?
+    }
+    Some(_) => check_output(args, config)?,
+  };
+
+  // Warnings always fail the check, but other failures take precedence
+  let warnings = diagnostics::warnings();
+  if status == Status::SUCCESS && !warnings.is_empty() {
+    return Ok(Status::of_warnings(&warnings));
+  }
+
+  return Ok(status);
+}
+
+/// Generates the output into a temporary directory, and compares it with the contents of the
+/// output directory, which is a failure if they differ.
+fn check_output(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let output_dir = get_dir(&args.output_dir)?;
+  let (status, changes) = generate_to_temp_dir(args, config)?;
+
+  if changes.is_empty() {
+    info!("\nThe output in {} is up to date", output_dir.display());
+  } else {
+    let files: Vec<String> = changes.iter().map(|change| format!("\n  {}", change)).collect();
+    error!("the output in {} is out of date, {} file(s) differ:{}",
+           output_dir.display(),
+           changes.len(),
+           files.concat());
+  }
+
+  if status == Status::SUCCESS && !changes.is_empty() {
+    return Ok(Status::FAILURE);
+  }
+
+  return Ok(status);
+}
+
+/// Parses the input and prints statistics about it, without writing any output.
+fn print_stats(args: &Args) -> D2mResult<Status>
+{
+  let start_time = SystemTime::now();
+
+  let input_dir = get_dir(&args.input_dir)?;
+  info!("Input directory: {}", input_dir.display());
+
+  let (registry, failures) = load_registry(args, &input_dir, None)?;
+
+  let elapsed_ms = start_time.elapsed().map_or(0, |duration| duration.as_millis());
+  let stats = Statistics::collect(&registry, failures.len(), elapsed_ms);
+  stats.print();
+
+  if let Some(path) = &args.stats_json {
+    stats.write_json(Path::new(path))?;
+  }
+
+  return Ok(if failures.is_empty() { Status::SUCCESS } else { Status::PARSE_FAILURE });
+}
+
+/// Verifies the links in previously generated Markdown output.
+fn check_links(args: &Args) -> D2mResult<Status>
+{
+  let output_dir = get_dir(&args.output_dir)?;
+  info!("Output directory: {}", output_dir.display());
+
+  if !output_dir.exists() {
+    return Err(D2mError::MISSING_INPUT(output_dir));
+  }
+
+  linkcheck::check_links(&output_dir, args.slug_style, args.link_base.as_deref())?;
+
+  let warnings = diagnostics::warnings();
+  diagnostics::print_warning_summary(&warnings, &[]);
+  annotations::print_problems(&[], &warnings, &[]);
+
+  return Ok(if warnings.is_empty() { Status::SUCCESS } else { Status::BROKEN_LINKS });
+}
+
+/// Returns true if the qualified name refers to the queried name, e.g. "a::b::c" matches "b::c".
+fn matches_name(qualified_name: &str, query: &str) -> bool
+{
+  return match qualified_name.strip_suffix(query) {
+    Some(prefix) => prefix.is_empty() || prefix.ends_with("::"),
+    None => false,
+  };
+}
+
+/// Returns the declaration of an entity as C++ code, which is empty for groups, namespaces, etc.
+fn get_declaration(registry: &Registry, id: &RefID, entity: &Entity) -> io::Result<String>
+{
+  return match entity {
+    Entity::COMPOUND(_) => match registry.classes.get(id) {
+      Some(class) => {
+        generator::render_code(|code| generator::generate_class_declaration(code, class))
+      }
+      None => Ok(String::new()),
+    },
+    Entity::FUNCTION(func) => {
+      generator::render_code(|code| generator::generate_function_signature(code, func))
+    }
+    Entity::ENUM(enumeration) => {
+      generator::render_code(|code| generator::generate_enum_declaration(code, enumeration))
+    }
+    Entity::VARIABLE(var) if var.initializer.is_empty() => Ok(format!("{};", &var.definition)),
+    Entity::VARIABLE(var) => Ok(format!("{} = {};", &var.definition, &var.initializer)),
+    Entity::DEFINE(define) => {
+      generator::render_code(|code| generator::generate_define_signature(code, define))
+    }
+  };
+}
+
+/// An entity that matches a query, along with the page it is rendered in.
+#[derive(Serialize)]
+struct QueryResult<'a>
+{
+  refid: &'a RefID,
+  kind: &'static str,
+  name: &'a str,
+  declaration: String,
+  brief: &'a [String],
+  details: &'a [String],
+  /// The URL of the entity, relative to the output directory unless there is a link base.
+  location: Option<String>,
+}
+
+impl QueryResult<'_>
+{
+  /// Prints the result as text, where everything but the first line is indented.
+  fn print(&self)
+  {
+    println!("{} {}", self.kind, self.name);
+
+    if let Some(location) = &self.location {
+      println!("  Location: {}", location);
+    }
+
+    let blocks = [&self.declaration].into_iter().chain(self.brief).chain(self.details);
+
+    for block in blocks.filter(|block| !block.is_empty()) {
+      println!();
+      for line in block.lines() {
+        println!("{}", format!("  {}", line).trim_end());
+      }
+    }
+  }
+}
+
+/// Prints the declarations, documentation, and page locations of the entities with the specified
+/// name. The parsed files of a previous incremental run are reused if they are up to date.
+fn query(query: &QueryArgs, config: Config) -> D2mResult<Status>
+{
+  let args = &query.args;
+  let input_dir = get_dir(&args.input_dir)?;
+
+  let mut cache = match &args.output_dir {
+    Some(_) => Some(Cache::load(&get_dir(&args.output_dir)?, &args.tag_files)),
+    None => None,
+  };
+
+  let (mut registry, _) = load_registry(args, &input_dir, cache.as_mut())?;
+  let page_paths = apply_config(&mut registry, &config);
+
+  // The pages are determined like when generating the output, since they depend on the options
+  let mut options = get_generator_options(args, config)?;
+  options.page_paths = page_paths;
+  let links = LinkResolver::new(&registry, &generator::get_link_options(&registry, &options)?);
+
+  let mut entities: Vec<(&RefID, Entity)> =
+    registry.get_entities()
+            .filter(|(_, entity)| matches_name(entity.get_name(), &query.name))
+            .collect();
+  entities.sort_by_key(|(id, entity)| (entity.get_name(), entity.get_kind_name(), *id));
+
+  let mut results = Vec::new();
+  for (id, entity) in &entities {
+    results.push(QueryResult {
+      refid: id,
+      kind: entity.get_kind_name(),
+      name: entity.get_name(),
+      declaration: get_declaration(&registry, id, entity)?,
+      brief: &entity.get_docs().brief,
+      details: &entity.get_docs().details,
+      location: links.get(id).map(|link| links.url_from(link, "")),
+    });
+  }
+
+  if query.json {
+    println!("{}", serde_json::to_string_pretty(&results).map_err(io::Error::from)?);
+  } else {
+    for (index, result) in results.iter().enumerate() {
+      if index > 0 {
+        println!();
+      }
+
+      result.print();
+    }
+  }
+
+  return Ok(if results.is_empty() { Status::FAILURE } else { Status::SUCCESS });
+}
+
+fn has_page(kind: CompoundKind) -> bool
+{
+  return matches!(kind,
+                  CompoundKind::CLASS
+                  | CompoundKind::STRUCT
+                  | CompoundKind::GROUP
+                  | CompoundKind::NAMESPACE);
+}
+
+/// Regenerates the page of a single compound, which is looked up by name in the index file.
+fn generate_one(one: &OneArgs, config: Config) -> D2mResult<Status>
+{
+  let input_dir = get_dir(&one.args.input_dir)?;
+
+  let id = match (&one.refid, &one.name) {
+    (Some(refid), _) => RefID::from(refid),
+    (None, Some(name)) => {
+      let index = parser::parse_index(&input_dir)?;
+      let id = index.compounds
+                    .iter()
+                    .filter(|(_, compound)| has_page(compound.kind))
+                    .find(|(_, compound)| compound.name == name.as_str())
+                    .map(|(id, _)| id.clone());
+
+      id.ok_or_else(|| D2mError::UNKNOWN_COMPOUND(name.to_owned()))?
+    }
+    (None, None) => unreachable!(),
+  };
+
+  if !input_dir.join(format!("{}.xml", id)).is_file() {
+    return Err(D2mError::UNKNOWN_COMPOUND(id.to_string()));
+  }
+
+  // Summaries of the whole output would only list the page of the compound
+  let mut args = one.args.clone();
+  args.compound = Some(id.to_string());
+  args.manifest = false;
+  args.site_url = None;
+
+  if args.watch {
+    return watch(&args, config);
+  } else if args.dry_run {
+    return dry_run(&args, config);
+  }
+
+  return generate(&args, config);
+}
+
+/// Converts the XML of a single compound read from stdin, and writes its page to stdout.
+///
+/// The page is generated into a temporary directory, since the generator writes files.
+fn generate_stdin(args: &Args, config: Config) -> D2mResult<Status>
+{
+  let path = Path::new("<stdin>");
+
+  load_tag_files(args)?;
+
+  // There is no index, so references to other compounds are expected to be unknown
+  let mut registry = parser::parse_compound(io::stdin().lock(), path)?;
+  stats::set_filename_case(args.filename_case);
+  registry.remove_hidden_members(args.max_visibility);
+
+  let id = registry.compounds
+                   .iter()
+                   .find(|(_, compound)| has_page(compound.kind))
+                   .map(|(id, _)| id.clone())
+                   .ok_or_else(|| D2mError::NO_COMPOUND(path.to_path_buf()))?;
+
+  let mut options = get_generator_options(args, config)?;
+  options.compound = Some(id);
+
+  let temp_dir = tempfile::Builder::new().prefix("doxy-to-md-").tempdir()?;
+  let temp_path = temp_dir.path();
+
+  let result = generator::generate_markdown(temp_path, &registry, &options).and_then(|_| {
+    for page in stats::get_outputs(temp_path).keys() {
+      io::stdout().write_all(&fs::read(temp_path.join(page))?)?;
+    }
+
+    Ok(())
+  });
+
+  // The temporary directory is removed even if the generation failed
+  temp_dir.close()?;
+  result?;
+
+  return report_problems(args, &registry, &[]);
+}
+
+/// Runs the subcommand, after running Doxygen and extracting an input archive if needed,
+/// returning the status of the run, which may still be a failure, e.g. due to skipped files.
+fn run(mut command: Command, config: Config) -> D2mResult<Status>
+{
+  let args = command.args();
+
+  if let Some(doxyfile) = args.doxyfile.as_ref().filter(|_| args.run_doxygen) {
+    let _stage = profile::stage("Doxygen");
+    let show_output = args.verbose > 0 && args.log_format == LogFormat::TEXT;
+    doxyfile::run_doxygen(Path::new(doxyfile), show_output)?;
+  }
+
+  let archive = args.input_dir
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .filter(|path| path.is_file() && archive::is_archive(path));
+
+  let Some(archive) = archive else {
+    return run_command(&command, config);
+  };
+
+  if args.watch {
+    return Err(D2mError::ARCHIVE {
+      path: archive,
+      reason: String::from("archives can't be watched for changes"),
+    });
+  }
+
+  // The archive is extracted into a temporary directory, which is used as the input directory
+  let temp_dir = tempfile::Builder::new().prefix("doxy-to-md-input-").tempdir()?;
+
+  let stage = profile::stage("Archive extraction");
+  let xml_dir = archive::extract(&archive, temp_dir.path());
+  drop(stage);
+
+  let result = xml_dir.and_then(|xml_dir| {
+    command.args_mut().input_dir = Some(xml_dir.to_string_lossy().to_string());
+    run_command(&command, config)
+  });
+
+  // The temporary directory is removed even if the subcommand failed
+  temp_dir.close()?;
+
+  return result;
+}
+
+/// Runs the subcommand with the input directory as it is.
+fn run_command(command: &Command, config: Config) -> D2mResult<Status>
+{
+  let status = match command {
+    Command::GENERATE(args) if args.stdin => generate_stdin(args, config),
+    Command::GENERATE(args) if args.watch => watch(args, config),
+    Command::GENERATE(args) if args.dry_run => dry_run(args, config),
+    Command::GENERATE(args) => generate(args, config),
+    Command::CHECK(args) => check(args, config),
+    Command::STATS(args) => print_stats(args),
+    Command::LINKS(args) => check_links(args),
+    Command::QUERY(args) => query(args, config),
+    Command::ONE(args) => generate_one(args, config),
+  };
+
+  // Each conversion in watch mode is profiled separately
+  let args = command.args();
+  if let Some(count) = args.profile.filter(|_| status.is_ok() && !args.watch) {
+    profile::print_report(count);
+  }
+
+  return status;
+}
+
+fn main() {
+  logging::init();
+
+  let result = parse_args().and_then(|(command, config)| run(command, config));
+
+  let status = result.unwrap_or_else(|err| {
+    error!("{}", err);
+    annotations::print_error(&err);
+    Status::of_error(&err)
+  });
+
+  if status != Status::SUCCESS {
+    process::exit(status as i32);
+  }
 }
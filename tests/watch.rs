@@ -0,0 +1,70 @@
+//! Runs the converter in watch mode, with a script that stands in for Doxygen.
+#![cfg(unix)]
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const INDEX_XML: &str = "<doxygenindex version=\"1.9.8\"></doxygenindex>";
+
+/// Counts its runs, and writes an empty index to where Doxygen would write the XML files.
+const DOXYGEN_SCRIPT: &str = "#!/bin/sh
+cat > /dev/null
+echo run >> runs.txt
+mkdir -p xml
+echo '<doxygenindex version=\"1.9.8\"></doxygenindex>' > xml/index.xml
+";
+
+/// Waits for a file to exist, for at most ten seconds.
+fn wait_for(path: &Path) -> bool
+{
+  let start = Instant::now();
+
+  while !path.exists() {
+    if start.elapsed() > Duration::from_secs(10) {
+      return false;
+    }
+
+    thread::sleep(Duration::from_millis(50));
+  }
+
+  return true;
+}
+
+#[test]
+fn runs_doxygen_once_when_watching()
+{
+  let dir = tempfile::tempdir().unwrap();
+  let root = dir.path();
+
+  let doxygen = root.join("doxygen.sh");
+  fs::write(&doxygen, DOXYGEN_SCRIPT).unwrap();
+  fs::set_permissions(&doxygen, fs::Permissions::from_mode(0o755)).unwrap();
+  fs::write(root.join("Doxyfile"), "XML_OUTPUT = xml\n").unwrap();
+
+  let args = ["--doxyfile", "Doxyfile", "--run-doxygen", "--watch", "-o", "api", "-q"];
+  let mut converter = Command::new(env!("CARGO_BIN_EXE_doxy-to-md")).args(args)
+                                                                     .current_dir(root)
+                                                                     .env("DOXYGEN", &doxygen)
+                                                                     .stdout(Stdio::null())
+                                                                     .stderr(Stdio::null())
+                                                                     .spawn()
+                                                                     .unwrap();
+
+  let converted = wait_for(&root.join("api").join("index.md"));
+
+  // Changes to the XML files are converted, which must not run Doxygen and change them again
+  thread::sleep(Duration::from_secs(1));
+  fs::write(root.join("xml").join("index.xml"), INDEX_XML).unwrap();
+  thread::sleep(Duration::from_secs(2));
+
+  converter.kill().unwrap();
+  converter.wait().unwrap();
+
+  assert!(converted);
+  assert_eq!(fs::read_to_string(root.join("runs.txt")).unwrap().lines().count(), 1);
+}